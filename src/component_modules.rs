@@ -0,0 +1,592 @@
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+use crate::program_component::from_wasm_component;
+
+/// The index spaces of one component that refer to core modules, instances and their items.
+#[derive(Default)]
+struct Scope {
+    path: Vec<u32>,
+    /// Extracted module ids, `None` for modules the component imports.
+    modules: Vec<Option<u32>>,
+    /// Ids of the manifest's core instances.
+    instances: Vec<u32>,
+    functions: Vec<CoreItemSource>,
+    tables: Vec<CoreItemSource>,
+    memories: Vec<CoreItemSource>,
+    globals: Vec<CoreItemSource>,
+    tags: Vec<CoreItemSource>,
+    nested_components: u32,
+}
+
+impl Scope {
+    fn items(&mut self, kind: ExternalKind) -> &mut Vec<CoreItemSource> {
+        match kind {
+            ExternalKind::ExtFunc | ExternalKind::ExtFuncExact => &mut self.functions,
+            ExternalKind::ExtTable => &mut self.tables,
+            ExternalKind::ExtMemory => &mut self.memories,
+            ExternalKind::ExtGlobal => &mut self.globals,
+            ExternalKind::ExtTag => &mut self.tags,
+        }
+    }
+}
+
+fn lookup<T: Clone>(space: &[T], index: Option<u32>, what: &'static str) -> Result<T> {
+    let index = index.ok_or(Error::Missing(what))?;
+    space
+        .get(index as usize)
+        .cloned()
+        .ok_or_else(|| Error::Invalid(format!("{} {} is out of bounds", what, index)))
+}
+
+/// Pulls every core module out of a component, including the ones defined by nested components,
+/// along with a manifest of where each module lives and how the core instances are wired.
+pub fn extract_modules(bytes: &[u8]) -> Result<ComponentModules> {
+    let component = from_wasm_component(bytes)?;
+    let mut modules: Vec<ProgramModule> = Vec::new();
+    let mut manifest = ComponentModuleManifest::default();
+    let mut scopes: Vec<Scope> = Vec::new();
+    extract_component(component, vec![], &mut scopes, &mut modules, &mut manifest)?;
+    Ok(ComponentModules {
+        modules,
+        manifest: Some(manifest),
+    })
+}
+
+fn extract_component(
+    component: ProgramComponent,
+    path: Vec<u32>,
+    scopes: &mut Vec<Scope>,
+    modules: &mut Vec<ProgramModule>,
+    manifest: &mut ComponentModuleManifest,
+) -> Result<()> {
+    use component_section::Section;
+    scopes.push(Scope {
+        path,
+        ..Default::default()
+    });
+    let depth = scopes.len() - 1;
+    for section in component.sections {
+        match section.section.ok_or(Error::Missing("Component section"))? {
+            Section::Module(module) => {
+                let scope = &mut scopes[depth];
+                manifest.modules.push(ModuleLocation {
+                    component_path: scope.path.clone(),
+                    module_index: Some(scope.modules.len() as u32),
+                });
+                scope.modules.push(Some(modules.len() as u32));
+                modules.push(module);
+            }
+            Section::Component(nested) => {
+                let scope = &mut scopes[depth];
+                let mut nested_path = scope.path.clone();
+                nested_path.push(scope.nested_components);
+                scope.nested_components += 1;
+                extract_component(nested, nested_path, scopes, modules, manifest)?;
+            }
+            Section::CoreInstanceSection(section) => {
+                for instance in section.instances {
+                    wire_instance(instance, &mut scopes[depth], manifest)?;
+                }
+            }
+            Section::AliasSection(section) => {
+                for alias in section.aliases {
+                    alias_item(alias, scopes)?;
+                }
+            }
+            Section::CanonicalSection(section) => {
+                for function in section.functions {
+                    if let Some(source) = lowered_source(function)? {
+                        scopes[depth].functions.push(source);
+                    }
+                }
+            }
+            Section::ImportSection(section) => {
+                for import in section.imports {
+                    if let Some(component_type_ref::TypeRef::Module(_)) =
+                        import.ty.and_then(|ty| ty.type_ref)
+                    {
+                        scopes[depth].modules.push(None);
+                    }
+                }
+            }
+            Section::ExportSection(section) => {
+                let scope = &mut scopes[depth];
+                for export in section.exports {
+                    if export.kind == Some(ComponentExternalKind::Module as i32) {
+                        let module = lookup(&scope.modules, export.index, "Core module index")?;
+                        scope.modules.push(module);
+                    }
+                }
+            }
+            Section::CoreTypeSection(_)
+            | Section::InstanceSection(_)
+            | Section::TypeSection(_)
+            | Section::StartSection(_)
+            | Section::CustomSection(_) => {}
+        }
+    }
+    scopes.pop();
+    Ok(())
+}
+
+fn wire_instance(
+    instance: CoreInstance,
+    scope: &mut Scope,
+    manifest: &mut ComponentModuleManifest,
+) -> Result<()> {
+    use core_instance_wiring::Wiring;
+    let wiring = match instance.instance.ok_or(Error::Missing("Core instance"))? {
+        core_instance::Instance::Instantiate(instantiation) => {
+            let mut args: Vec<CoreInstanceArgWiring> = Vec::new();
+            for arg in instantiation.args {
+                args.push(CoreInstanceArgWiring {
+                    instance: Some(lookup(
+                        &scope.instances,
+                        arg.instance_index,
+                        "Core instance index",
+                    )?),
+                    name: arg.name,
+                });
+            }
+            Wiring::Instantiate(ModuleInstantiationWiring {
+                module: lookup(
+                    &scope.modules,
+                    instantiation.module_index,
+                    "Core module index",
+                )?,
+                args,
+            })
+        }
+        core_instance::Instance::FromExports(exports) => {
+            let mut wired: Vec<CoreExportWiring> = Vec::new();
+            for export in exports.exports {
+                let kind =
+                    ExternalKind::try_from(export.kind.ok_or(Error::Missing("Core export kind"))?)?;
+                wired.push(CoreExportWiring {
+                    source: Some(lookup(scope.items(kind), export.index, "Core item index")?),
+                    name: export.name,
+                    kind: Some(kind as i32),
+                });
+            }
+            Wiring::FromExports(CoreExportsWiring { exports: wired })
+        }
+    };
+    manifest.instances.push(CoreInstanceWiring {
+        component_path: scope.path.clone(),
+        instance_index: Some(scope.instances.len() as u32),
+        wiring: Some(wiring),
+    });
+    scope.instances.push(manifest.instances.len() as u32 - 1);
+    Ok(())
+}
+
+fn alias_item(alias: ComponentAlias, scopes: &mut [Scope]) -> Result<()> {
+    use component_alias::Alias;
+    let depth = scopes.len() - 1;
+    match alias.alias.ok_or(Error::Missing("Alias"))? {
+        Alias::CoreInstanceExport(export) => {
+            let scope = &mut scopes[depth];
+            let kind = ExternalKind::try_from(export.kind.ok_or(Error::Missing("Alias kind"))?)?;
+            let source = core_item_source::Source::InstanceExport(CoreExportSource {
+                instance: Some(lookup(
+                    &scope.instances,
+                    export.instance_index,
+                    "Core instance index",
+                )?),
+                name: export.name,
+            });
+            scope.items(kind).push(CoreItemSource {
+                source: Some(source),
+            });
+        }
+        // a module exported by a component instance is only known once the instance exists
+        Alias::InstanceExport(export) => {
+            if export.kind == Some(ComponentExternalKind::Module as i32) {
+                scopes[depth].modules.push(None);
+            }
+        }
+        Alias::Outer(outer) => {
+            if outer.kind == Some(ComponentOuterAliasKind::CoreModule as i32) {
+                let count = outer.count.ok_or(Error::Missing("Alias count"))?;
+                let ancestor = depth.checked_sub(count as usize).ok_or_else(|| {
+                    Error::Invalid(format!("Outer alias count {} is out of bounds", count))
+                })?;
+                let module = lookup(&scopes[ancestor].modules, outer.index, "Core module index")?;
+                scopes[depth].modules.push(module);
+            }
+        }
+    }
+    Ok(())
+}
+
+/// The source of the core function a canonical function defines, `None` for lifted functions.
+fn lowered_source(function: CanonicalFunction) -> Result<Option<CoreItemSource>> {
+    use canonical_function::Function;
+    use core_item_source::Source;
+    let source = match function
+        .function
+        .ok_or(Error::Missing("Canonical function"))?
+    {
+        Function::Lift(_) => return Ok(None),
+        Function::Lower(lower) => Source::LoweredFunction(
+            lower
+                .func_index
+                .ok_or(Error::Missing("Lower function index"))?,
+        ),
+        Function::ResourceNew(resource) => Source::ResourceNew(resource),
+        Function::ResourceDrop(resource) => Source::ResourceDrop(resource),
+        Function::ResourceRep(resource) => Source::ResourceRep(resource),
+    };
+    Ok(Some(CoreItemSource {
+        source: Some(source),
+    }))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_encoder::{
+        Alias, CanonicalFunctionSection, CanonicalOption, CodeSection, Component,
+        ComponentAliasSection, ComponentExportKind, ComponentImportSection,
+        ComponentOuterAliasKind, ComponentTypeRef, ComponentTypeSection, ElementSection, Elements,
+        EntityType, ExportKind, Function, FunctionSection, ImportSection, InstanceSection,
+        InstanceType, Instruction, MemorySection, MemoryType, Module, ModuleArg, ModuleSection,
+        NestedComponentSection, PrimitiveValType, RefType, TableSection, TableType, TypeSection,
+        ValType,
+    };
+
+    fn log_type() -> TypeSection {
+        let mut types = TypeSection::new();
+        types.ty().function([ValType::I32, ValType::I32], []);
+        types
+    }
+
+    /// The main module calls the host logger and exports its memory
+    fn create_main_module() -> Module {
+        let mut module = Module::new();
+        let mut types = log_type();
+        types.ty().function([], []);
+        module.section(&types);
+        let mut imports = ImportSection::new();
+        imports.import("host", "log", EntityType::Function(0));
+        module.section(&imports);
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        module.section(&functions);
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        });
+        module.section(&memories);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("memory", ExportKind::Memory, 0);
+        exports.export("run", ExportKind::Func, 1);
+        module.section(&exports);
+        let mut code = CodeSection::new();
+        let mut function = Function::new(vec![]);
+        function.instruction(&Instruction::I32Const(0));
+        function.instruction(&Instruction::I32Const(0));
+        function.instruction(&Instruction::Call(0));
+        function.instruction(&Instruction::End);
+        code.function(&function);
+        module.section(&code);
+        module
+    }
+
+    /// The shim module forwards calls through a table the fixup module fills in later
+    fn create_shim_module() -> Module {
+        let mut module = Module::new();
+        module.section(&log_type());
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+        let mut tables = TableSection::new();
+        tables.table(TableType {
+            element_type: RefType::FUNCREF,
+            table64: false,
+            minimum: 1,
+            maximum: Some(1),
+            shared: false,
+        });
+        module.section(&tables);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("0", ExportKind::Func, 0);
+        exports.export("$imports", ExportKind::Table, 0);
+        module.section(&exports);
+        let mut code = CodeSection::new();
+        let mut function = Function::new(vec![]);
+        function.instruction(&Instruction::LocalGet(0));
+        function.instruction(&Instruction::LocalGet(1));
+        function.instruction(&Instruction::I32Const(0));
+        function.instruction(&Instruction::CallIndirect {
+            type_index: 0,
+            table_index: 0,
+        });
+        function.instruction(&Instruction::End);
+        code.function(&function);
+        module.section(&code);
+        module
+    }
+
+    /// The fixup module has no code, it only fills the shim's table
+    fn create_fixup_module() -> Module {
+        let mut module = Module::new();
+        module.section(&log_type());
+        let mut imports = ImportSection::new();
+        imports.import("", "0", EntityType::Function(0));
+        imports.import(
+            "",
+            "$imports",
+            EntityType::Table(TableType {
+                element_type: RefType::FUNCREF,
+                table64: false,
+                minimum: 1,
+                maximum: Some(1),
+                shared: false,
+            }),
+        );
+        module.section(&imports);
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &wasm_encoder::ConstExpr::i32_const(0),
+            Elements::Functions([0].as_slice().into()),
+        );
+        module.section(&elements);
+        module
+    }
+
+    /// Lays out a component the way `cargo component` does, with a shim and fixup module
+    /// breaking the cycle between the main module and the lowered host import, plus two nested
+    /// components: one defining its own module and one aliasing the shim from its parent
+    fn create_shim_component() -> Vec<u8> {
+        let mut component = Component::new();
+
+        let mut host = InstanceType::new();
+        host.ty()
+            .function()
+            .params([("msg", PrimitiveValType::String)])
+            .result(None);
+        host.export("log", ComponentTypeRef::Func(0));
+        let mut types = ComponentTypeSection::new();
+        types.instance(&host);
+        component.section(&types);
+        let mut imports = ComponentImportSection::new();
+        imports.import("host", ComponentTypeRef::Instance(0));
+        component.section(&imports);
+        let mut aliases = ComponentAliasSection::new();
+        aliases.alias(Alias::InstanceExport {
+            instance: 0,
+            kind: ComponentExportKind::Func,
+            name: "log",
+        });
+        component.section(&aliases);
+
+        component.section(&ModuleSection(&create_main_module()));
+        component.section(&ModuleSection(&create_shim_module()));
+        component.section(&ModuleSection(&create_fixup_module()));
+
+        let mut instances = InstanceSection::new();
+        instances.instantiate(1, Vec::<(&str, ModuleArg)>::new());
+        component.section(&instances);
+        let mut aliases = ComponentAliasSection::new();
+        aliases.alias(Alias::CoreInstanceExport {
+            instance: 0,
+            kind: ExportKind::Func,
+            name: "0",
+        });
+        aliases.alias(Alias::CoreInstanceExport {
+            instance: 0,
+            kind: ExportKind::Table,
+            name: "$imports",
+        });
+        component.section(&aliases);
+        let mut instances = InstanceSection::new();
+        instances.export_items([("log", ExportKind::Func, 0)]);
+        instances.instantiate(0, [("host", ModuleArg::Instance(1))]);
+        component.section(&instances);
+        let mut aliases = ComponentAliasSection::new();
+        aliases.alias(Alias::CoreInstanceExport {
+            instance: 2,
+            kind: ExportKind::Memory,
+            name: "memory",
+        });
+        component.section(&aliases);
+        let mut canonical = CanonicalFunctionSection::new();
+        canonical.lower(0, [CanonicalOption::UTF8, CanonicalOption::Memory(0)]);
+        component.section(&canonical);
+        let mut instances = InstanceSection::new();
+        instances.export_items([
+            ("0", ExportKind::Func, 1),
+            ("$imports", ExportKind::Table, 0),
+        ]);
+        instances.instantiate(2, [("", ModuleArg::Instance(3))]);
+        component.section(&instances);
+
+        let mut inner = Component::new();
+        inner.section(&ModuleSection(&Module::new()));
+        let mut inner_instances = InstanceSection::new();
+        inner_instances.instantiate(0, Vec::<(&str, ModuleArg)>::new());
+        inner.section(&inner_instances);
+        component.section(&NestedComponentSection(&inner));
+
+        let mut aliasing = Component::new();
+        let mut outer = ComponentAliasSection::new();
+        outer.alias(Alias::Outer {
+            kind: ComponentOuterAliasKind::CoreModule,
+            count: 1,
+            index: 1,
+        });
+        aliasing.section(&outer);
+        let mut aliasing_instances = InstanceSection::new();
+        aliasing_instances.instantiate(0, Vec::<(&str, ModuleArg)>::new());
+        aliasing.section(&aliasing_instances);
+        component.section(&NestedComponentSection(&aliasing));
+
+        component.finish()
+    }
+
+    fn instance_export(instance: u32, name: &str) -> CoreItemSource {
+        CoreItemSource {
+            source: Some(core_item_source::Source::InstanceExport(CoreExportSource {
+                instance: Some(instance),
+                name: Some(name.to_string()),
+            })),
+        }
+    }
+
+    #[test]
+    fn test_extract_modules() {
+        let bytes = create_shim_component();
+        wasmparser::Validator::new().validate_all(&bytes).unwrap();
+        let extracted = extract_modules(&bytes).unwrap();
+        assert_eq!(extracted.modules.len(), 4);
+        assert!(extracted.modules[0].code_section.is_some());
+        assert!(extracted.modules[2].code_section.is_none());
+        assert!(extracted.modules[2].element_section.is_some());
+
+        let manifest = extracted.manifest.unwrap();
+        let locations: Vec<(Vec<u32>, Option<u32>)> = manifest
+            .modules
+            .into_iter()
+            .map(|location| (location.component_path, location.module_index))
+            .collect();
+        assert_eq!(
+            locations,
+            vec![
+                (vec![], Some(0)),
+                (vec![], Some(1)),
+                (vec![], Some(2)),
+                (vec![0], Some(0)),
+            ]
+        );
+        assert_eq!(manifest.instances.len(), 7);
+    }
+
+    #[test]
+    fn test_extract_modules_wiring() {
+        use core_instance_wiring::Wiring;
+        let manifest = extract_modules(&create_shim_component())
+            .unwrap()
+            .manifest
+            .unwrap();
+
+        let Some(Wiring::Instantiate(main)) = &manifest.instances[2].wiring else {
+            panic!("Expected the main module instantiation");
+        };
+        assert_eq!(main.module, Some(0));
+        assert_eq!(main.args[0].name, Some("host".to_string()));
+        assert_eq!(main.args[0].instance, Some(1));
+
+        let Some(Wiring::FromExports(host)) = &manifest.instances[1].wiring else {
+            panic!("Expected the host exports");
+        };
+        assert_eq!(host.exports[0].source, Some(instance_export(0, "0")));
+
+        let Some(Wiring::FromExports(fixup_args)) = &manifest.instances[3].wiring else {
+            panic!("Expected the fixup arguments");
+        };
+        assert_eq!(
+            fixup_args.exports[0].source,
+            Some(CoreItemSource {
+                source: Some(core_item_source::Source::LoweredFunction(0)),
+            })
+        );
+        assert_eq!(
+            fixup_args.exports[1].kind,
+            Some(ExternalKind::ExtTable as i32)
+        );
+        assert_eq!(
+            fixup_args.exports[1].source,
+            Some(instance_export(0, "$imports"))
+        );
+
+        let Some(Wiring::Instantiate(fixup)) = &manifest.instances[4].wiring else {
+            panic!("Expected the fixup module instantiation");
+        };
+        assert_eq!(fixup.module, Some(2));
+        assert_eq!(fixup.args[0].instance, Some(3));
+    }
+
+    #[test]
+    fn test_extract_modules_nested_components() {
+        use core_instance_wiring::Wiring;
+        let manifest = extract_modules(&create_shim_component())
+            .unwrap()
+            .manifest
+            .unwrap();
+        let inner = &manifest.instances[5];
+        assert_eq!(inner.component_path, vec![0]);
+        assert_eq!(inner.instance_index, Some(0));
+        let Some(Wiring::Instantiate(instantiation)) = &inner.wiring else {
+            panic!("Expected an instantiation");
+        };
+        assert_eq!(instantiation.module, Some(3));
+
+        let aliasing = &manifest.instances[6];
+        assert_eq!(aliasing.component_path, vec![1]);
+        let Some(Wiring::Instantiate(instantiation)) = &aliasing.wiring else {
+            panic!("Expected an instantiation");
+        };
+        assert_eq!(instantiation.module, Some(1));
+    }
+
+    #[test]
+    fn test_extracted_modules_render() {
+        let extracted = extract_modules(&create_shim_component()).unwrap();
+        let expected = [
+            create_main_module().finish(),
+            create_shim_module().finish(),
+            create_fixup_module().finish(),
+            Module::new().finish(),
+        ];
+        for (module, expected) in extracted.modules.into_iter().zip(expected) {
+            let rendered = crate::render_wasm(module).unwrap();
+            wasmparser::Validator::new()
+                .validate_all(&rendered)
+                .unwrap();
+            assert_eq!(rendered, expected);
+        }
+    }
+
+    #[test]
+    fn test_extract_modules_rejects_module() {
+        assert!(extract_modules(&create_main_module().finish()).is_err());
+    }
+
+    #[test]
+    fn test_extract_modules_out_of_bounds() {
+        let mut component = Component::new();
+        let mut instances = InstanceSection::new();
+        instances.instantiate(0, Vec::<(&str, ModuleArg)>::new());
+        component.section(&instances);
+        assert!(matches!(
+            extract_modules(&component.finish()),
+            Err(Error::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,1392 @@
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+
+impl From<wasmparser::PrimitiveValType> for ComponentPrimitiveType {
+    fn from(primitive: wasmparser::PrimitiveValType) -> Self {
+        use wasmparser::PrimitiveValType;
+        match primitive {
+            PrimitiveValType::Bool => ComponentPrimitiveType::Bool,
+            PrimitiveValType::S8 => ComponentPrimitiveType::S8,
+            PrimitiveValType::U8 => ComponentPrimitiveType::U8,
+            PrimitiveValType::S16 => ComponentPrimitiveType::S16,
+            PrimitiveValType::U16 => ComponentPrimitiveType::U16,
+            PrimitiveValType::S32 => ComponentPrimitiveType::S32,
+            PrimitiveValType::U32 => ComponentPrimitiveType::U32,
+            PrimitiveValType::S64 => ComponentPrimitiveType::S64,
+            PrimitiveValType::U64 => ComponentPrimitiveType::U64,
+            PrimitiveValType::F32 => ComponentPrimitiveType::F32,
+            PrimitiveValType::F64 => ComponentPrimitiveType::F64,
+            PrimitiveValType::Char => ComponentPrimitiveType::Char,
+            PrimitiveValType::String => ComponentPrimitiveType::String,
+            PrimitiveValType::ErrorContext => ComponentPrimitiveType::ErrorContext,
+        }
+    }
+}
+
+impl From<ComponentPrimitiveType> for wasm_encoder::PrimitiveValType {
+    fn from(primitive: ComponentPrimitiveType) -> Self {
+        use wasm_encoder::PrimitiveValType;
+        match primitive {
+            ComponentPrimitiveType::Bool => PrimitiveValType::Bool,
+            ComponentPrimitiveType::S8 => PrimitiveValType::S8,
+            ComponentPrimitiveType::U8 => PrimitiveValType::U8,
+            ComponentPrimitiveType::S16 => PrimitiveValType::S16,
+            ComponentPrimitiveType::U16 => PrimitiveValType::U16,
+            ComponentPrimitiveType::S32 => PrimitiveValType::S32,
+            ComponentPrimitiveType::U32 => PrimitiveValType::U32,
+            ComponentPrimitiveType::S64 => PrimitiveValType::S64,
+            ComponentPrimitiveType::U64 => PrimitiveValType::U64,
+            ComponentPrimitiveType::F32 => PrimitiveValType::F32,
+            ComponentPrimitiveType::F64 => PrimitiveValType::F64,
+            ComponentPrimitiveType::Char => PrimitiveValType::Char,
+            ComponentPrimitiveType::String => PrimitiveValType::String,
+            ComponentPrimitiveType::ErrorContext => PrimitiveValType::ErrorContext,
+        }
+    }
+}
+
+impl From<wasmparser::ComponentValType> for ComponentValueType {
+    fn from(value_type: wasmparser::ComponentValType) -> Self {
+        use component_value_type::ValueType;
+        let value_type = match value_type {
+            wasmparser::ComponentValType::Primitive(primitive) => {
+                ValueType::Primitive(ComponentPrimitiveType::from(primitive) as i32)
+            }
+            wasmparser::ComponentValType::Type(index) => ValueType::TypeIndex(index),
+        };
+        ComponentValueType {
+            value_type: Some(value_type),
+        }
+    }
+}
+
+impl TryFrom<ComponentValueType> for wasm_encoder::ComponentValType {
+    type Error = Error;
+
+    fn try_from(value_type: ComponentValueType) -> Result<Self> {
+        use component_value_type::ValueType;
+        match value_type
+            .value_type
+            .ok_or(Error::Missing("Component value type"))?
+        {
+            ValueType::Primitive(primitive) => Ok(wasm_encoder::ComponentValType::Primitive(
+                ComponentPrimitiveType::try_from(primitive)?.into(),
+            )),
+            ValueType::TypeIndex(index) => Ok(wasm_encoder::ComponentValType::Type(index)),
+        }
+    }
+}
+
+impl From<wasmparser::ComponentExternalKind> for ComponentExternalKind {
+    fn from(kind: wasmparser::ComponentExternalKind) -> Self {
+        use wasmparser::ComponentExternalKind as Kind;
+        match kind {
+            Kind::Module => ComponentExternalKind::Module,
+            Kind::Func => ComponentExternalKind::Func,
+            Kind::Value => ComponentExternalKind::Value,
+            Kind::Type => ComponentExternalKind::Type,
+            Kind::Instance => ComponentExternalKind::Instance,
+            Kind::Component => ComponentExternalKind::Component,
+        }
+    }
+}
+
+impl From<ComponentExternalKind> for wasm_encoder::ComponentExportKind {
+    fn from(kind: ComponentExternalKind) -> Self {
+        use wasm_encoder::ComponentExportKind as Kind;
+        match kind {
+            ComponentExternalKind::Module => Kind::Module,
+            ComponentExternalKind::Func => Kind::Func,
+            ComponentExternalKind::Value => Kind::Value,
+            ComponentExternalKind::Type => Kind::Type,
+            ComponentExternalKind::Instance => Kind::Instance,
+            ComponentExternalKind::Component => Kind::Component,
+        }
+    }
+}
+
+impl From<wasmparser::ComponentOuterAliasKind> for ComponentOuterAliasKind {
+    fn from(kind: wasmparser::ComponentOuterAliasKind) -> Self {
+        use wasmparser::ComponentOuterAliasKind as Kind;
+        match kind {
+            Kind::CoreModule => ComponentOuterAliasKind::CoreModule,
+            Kind::CoreType => ComponentOuterAliasKind::CoreType,
+            Kind::Type => ComponentOuterAliasKind::Type,
+            Kind::Component => ComponentOuterAliasKind::Component,
+        }
+    }
+}
+
+impl From<ComponentOuterAliasKind> for wasm_encoder::ComponentOuterAliasKind {
+    fn from(kind: ComponentOuterAliasKind) -> Self {
+        use wasm_encoder::ComponentOuterAliasKind as Kind;
+        match kind {
+            ComponentOuterAliasKind::CoreModule => Kind::CoreModule,
+            ComponentOuterAliasKind::CoreType => Kind::CoreType,
+            ComponentOuterAliasKind::Type => Kind::Type,
+            ComponentOuterAliasKind::Component => Kind::Component,
+        }
+    }
+}
+
+impl From<wasmparser::ComponentTypeRef> for ComponentTypeRef {
+    fn from(type_ref: wasmparser::ComponentTypeRef) -> Self {
+        use component_type_bounds::Bounds;
+        use component_type_ref::TypeRef;
+        let type_ref = match type_ref {
+            wasmparser::ComponentTypeRef::Module(index) => TypeRef::Module(index),
+            wasmparser::ComponentTypeRef::Func(index) => TypeRef::Func(index),
+            wasmparser::ComponentTypeRef::Value(value_type) => TypeRef::Value(value_type.into()),
+            wasmparser::ComponentTypeRef::Type(bounds) => {
+                TypeRef::TypeBounds(ComponentTypeBounds {
+                    bounds: Some(match bounds {
+                        wasmparser::TypeBounds::Eq(index) => Bounds::Eq(index),
+                        wasmparser::TypeBounds::SubResource => Bounds::SubResource(true),
+                    }),
+                })
+            }
+            wasmparser::ComponentTypeRef::Instance(index) => TypeRef::Instance(index),
+            wasmparser::ComponentTypeRef::Component(index) => TypeRef::Component(index),
+        };
+        ComponentTypeRef {
+            type_ref: Some(type_ref),
+        }
+    }
+}
+
+impl TryFrom<ComponentTypeRef> for wasm_encoder::ComponentTypeRef {
+    type Error = Error;
+
+    fn try_from(type_ref: ComponentTypeRef) -> Result<Self> {
+        use component_type_bounds::Bounds;
+        use component_type_ref::TypeRef;
+        match type_ref
+            .type_ref
+            .ok_or(Error::Missing("Component type ref"))?
+        {
+            TypeRef::Module(index) => Ok(wasm_encoder::ComponentTypeRef::Module(index)),
+            TypeRef::Func(index) => Ok(wasm_encoder::ComponentTypeRef::Func(index)),
+            TypeRef::Value(value_type) => Ok(wasm_encoder::ComponentTypeRef::Value(
+                value_type.try_into()?,
+            )),
+            TypeRef::TypeBounds(bounds) => Ok(wasm_encoder::ComponentTypeRef::Type(
+                match bounds.bounds.ok_or(Error::Missing("Type bounds"))? {
+                    Bounds::Eq(index) => wasm_encoder::TypeBounds::Eq(index),
+                    Bounds::SubResource(_) => wasm_encoder::TypeBounds::SubResource,
+                },
+            )),
+            TypeRef::Instance(index) => Ok(wasm_encoder::ComponentTypeRef::Instance(index)),
+            TypeRef::Component(index) => Ok(wasm_encoder::ComponentTypeRef::Component(index)),
+        }
+    }
+}
+
+impl From<wasmparser::ComponentImport<'_>> for ComponentImport {
+    fn from(import: wasmparser::ComponentImport<'_>) -> Self {
+        ComponentImport {
+            name: Some(import.name.0.to_string()),
+            ty: Some(import.ty.into()),
+        }
+    }
+}
+
+impl ComponentImport {
+    fn to_wasm_encoder(&self) -> Result<(&str, wasm_encoder::ComponentTypeRef)> {
+        Ok((
+            self.name
+                .as_deref()
+                .ok_or(Error::Missing("Component import name"))?,
+            self.ty
+                .ok_or(Error::Missing("Component import type"))?
+                .try_into()?,
+        ))
+    }
+}
+
+impl From<wasmparser::ComponentExport<'_>> for ComponentExport {
+    fn from(export: wasmparser::ComponentExport<'_>) -> Self {
+        ComponentExport {
+            name: Some(export.name.0.to_string()),
+            kind: Some(ComponentExternalKind::from(export.kind) as i32),
+            index: Some(export.index),
+            ty: export.ty.map(ComponentTypeRef::from),
+        }
+    }
+}
+
+impl ComponentExport {
+    fn to_wasm_encoder(&self) -> Result<(&str, wasm_encoder::ComponentExportKind, u32)> {
+        Ok((
+            self.name
+                .as_deref()
+                .ok_or(Error::Missing("Component export name"))?,
+            ComponentExternalKind::try_from(
+                self.kind.ok_or(Error::Missing("Component export kind"))?,
+            )?
+            .into(),
+            self.index.ok_or(Error::Missing("Component export index"))?,
+        ))
+    }
+}
+
+impl TryFrom<wasmparser::ComponentAlias<'_>> for ComponentAlias {
+    type Error = Error;
+
+    fn try_from(alias: wasmparser::ComponentAlias<'_>) -> Result<Self> {
+        use component_alias::Alias;
+        let alias = match alias {
+            wasmparser::ComponentAlias::InstanceExport {
+                kind,
+                instance_index,
+                name,
+            } => Alias::InstanceExport(ComponentInstanceExportAlias {
+                kind: Some(ComponentExternalKind::from(kind) as i32),
+                instance_index: Some(instance_index),
+                name: Some(name.to_string()),
+            }),
+            wasmparser::ComponentAlias::CoreInstanceExport {
+                kind,
+                instance_index,
+                name,
+            } => Alias::CoreInstanceExport(CoreInstanceExportAlias {
+                kind: Some(ExternalKind::try_from(kind)? as i32),
+                instance_index: Some(instance_index),
+                name: Some(name.to_string()),
+            }),
+            wasmparser::ComponentAlias::Outer { kind, count, index } => {
+                Alias::Outer(ComponentOuterAlias {
+                    kind: Some(ComponentOuterAliasKind::from(kind) as i32),
+                    count: Some(count),
+                    index: Some(index),
+                })
+            }
+        };
+        Ok(ComponentAlias { alias: Some(alias) })
+    }
+}
+
+impl<'a> TryFrom<&'a ComponentAlias> for wasm_encoder::Alias<'a> {
+    type Error = Error;
+
+    fn try_from(alias: &'a ComponentAlias) -> Result<Self> {
+        use component_alias::Alias;
+        match alias.alias.as_ref().ok_or(Error::Missing("Alias"))? {
+            Alias::InstanceExport(export) => Ok(wasm_encoder::Alias::InstanceExport {
+                instance: export
+                    .instance_index
+                    .ok_or(Error::Missing("Alias instance index"))?,
+                kind: ComponentExternalKind::try_from(
+                    export.kind.ok_or(Error::Missing("Alias kind"))?,
+                )?
+                .into(),
+                name: export.name.as_deref().ok_or(Error::Missing("Alias name"))?,
+            }),
+            Alias::CoreInstanceExport(export) => Ok(wasm_encoder::Alias::CoreInstanceExport {
+                instance: export
+                    .instance_index
+                    .ok_or(Error::Missing("Alias instance index"))?,
+                kind: ExternalKind::try_from(export.kind.ok_or(Error::Missing("Alias kind"))?)?
+                    .try_into()?,
+                name: export.name.as_deref().ok_or(Error::Missing("Alias name"))?,
+            }),
+            Alias::Outer(outer) => Ok(wasm_encoder::Alias::Outer {
+                kind: ComponentOuterAliasKind::try_from(
+                    outer.kind.ok_or(Error::Missing("Alias kind"))?,
+                )?
+                .into(),
+                count: outer.count.ok_or(Error::Missing("Alias count"))?,
+                index: outer.index.ok_or(Error::Missing("Alias index"))?,
+            }),
+        }
+    }
+}
+
+impl TryFrom<&wasmparser::RecGroup> for CoreRecGroup {
+    type Error = Error;
+
+    fn try_from(group: &wasmparser::RecGroup) -> Result<Self> {
+        let mut types: Vec<SubType> = Vec::new();
+        for ty in group.types() {
+            types.push(SubType::try_from(ty)?);
+        }
+        Ok(CoreRecGroup {
+            types,
+            explicit: Some(group.is_explicit_rec_group()),
+        })
+    }
+}
+
+impl CoreRecGroup {
+    fn render_wasm(&self, encoder: wasm_encoder::CoreTypeEncoder<'_>) -> Result<()> {
+        let mut sub_types: Vec<wasm_encoder::SubType> = Vec::new();
+        for ty in &self.types {
+            sub_types.push(ty.try_into()?);
+        }
+        if self.explicit.unwrap_or(false) {
+            encoder.rec(sub_types);
+        } else if let [sub_type] = sub_types.as_slice() {
+            encoder.subtype(sub_type);
+        } else {
+            return Err(Error::Invalid(format!(
+                "Implicit rec group must hold exactly 1 type, got {}",
+                sub_types.len()
+            )));
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&wasmparser::ModuleTypeDeclaration<'_>> for ModuleTypeDeclaration {
+    type Error = Error;
+
+    fn try_from(declaration: &wasmparser::ModuleTypeDeclaration<'_>) -> Result<Self> {
+        use module_type_declaration::Declaration;
+        let declaration = match declaration {
+            wasmparser::ModuleTypeDeclaration::Type(group) => {
+                Declaration::RecGroup(group.try_into()?)
+            }
+            wasmparser::ModuleTypeDeclaration::Import(import) => Declaration::Import(Import {
+                module: Some(import.module.to_string()),
+                name: Some(import.name.to_string()),
+                ty: Some(import.ty.try_into()?),
+            }),
+            wasmparser::ModuleTypeDeclaration::Export { name, ty } => Declaration::Export(Import {
+                module: None,
+                name: Some(name.to_string()),
+                ty: Some((*ty).try_into()?),
+            }),
+            wasmparser::ModuleTypeDeclaration::OuterAlias { count, index, .. } => {
+                Declaration::OuterAlias(ComponentOuterAlias {
+                    kind: Some(ComponentOuterAliasKind::CoreType as i32),
+                    count: Some(*count),
+                    index: Some(*index),
+                })
+            }
+        };
+        Ok(ModuleTypeDeclaration {
+            declaration: Some(declaration),
+        })
+    }
+}
+
+impl TryFrom<&wasmparser::CoreType<'_>> for CoreType {
+    type Error = Error;
+
+    fn try_from(core_type: &wasmparser::CoreType<'_>) -> Result<Self> {
+        let core_type = match core_type {
+            wasmparser::CoreType::Rec(group) => core_type::CoreType::RecGroup(group.try_into()?),
+            wasmparser::CoreType::Module(declarations) => {
+                let mut module_declarations: Vec<ModuleTypeDeclaration> = Vec::new();
+                for declaration in declarations {
+                    module_declarations.push(declaration.try_into()?);
+                }
+                core_type::CoreType::Module(ModuleTypeDeclarations {
+                    declarations: module_declarations,
+                })
+            }
+        };
+        Ok(CoreType {
+            core_type: Some(core_type),
+        })
+    }
+}
+
+impl CoreType {
+    fn render_wasm(&self, encoder: wasm_encoder::ComponentCoreTypeEncoder<'_>) -> Result<()> {
+        use module_type_declaration::Declaration;
+        match self.core_type.as_ref().ok_or(Error::Missing("Core type"))? {
+            core_type::CoreType::RecGroup(group) => group.render_wasm(encoder.core()),
+            core_type::CoreType::Module(module) => {
+                let mut module_type = wasm_encoder::ModuleType::new();
+                for declaration in &module.declarations {
+                    match declaration
+                        .declaration
+                        .as_ref()
+                        .ok_or(Error::Missing("Module type declaration"))?
+                    {
+                        Declaration::RecGroup(group) => group.render_wasm(module_type.ty())?,
+                        Declaration::Import(import) => {
+                            module_type.import(
+                                import
+                                    .module
+                                    .as_deref()
+                                    .ok_or(Error::Missing("Import module"))?,
+                                import
+                                    .name
+                                    .as_deref()
+                                    .ok_or(Error::Missing("Import name"))?,
+                                import
+                                    .ty
+                                    .as_ref()
+                                    .ok_or(Error::Missing("Import type"))?
+                                    .try_into()?,
+                            );
+                        }
+                        Declaration::Export(export) => {
+                            module_type.export(
+                                export
+                                    .name
+                                    .as_deref()
+                                    .ok_or(Error::Missing("Export name"))?,
+                                export
+                                    .ty
+                                    .as_ref()
+                                    .ok_or(Error::Missing("Export type"))?
+                                    .try_into()?,
+                            );
+                        }
+                        Declaration::OuterAlias(alias) => {
+                            module_type.alias_outer_core_type(
+                                alias.count.ok_or(Error::Missing("Alias count"))?,
+                                alias.index.ok_or(Error::Missing("Alias index"))?,
+                            );
+                        }
+                    }
+                }
+                encoder.module(&module_type);
+                Ok(())
+            }
+        }
+    }
+}
+
+fn named_types(types: &[(&str, wasmparser::ComponentValType)]) -> Vec<ComponentNamedType> {
+    types
+        .iter()
+        .map(|(name, value_type)| ComponentNamedType {
+            name: Some(name.to_string()),
+            value_type: Some((*value_type).into()),
+        })
+        .collect()
+}
+
+fn name_list(names: &[&str]) -> ComponentNameList {
+    ComponentNameList {
+        names: names.iter().map(|name| name.to_string()).collect(),
+    }
+}
+
+impl From<&wasmparser::ComponentDefinedType<'_>> for ComponentDefinedType {
+    fn from(defined_type: &wasmparser::ComponentDefinedType<'_>) -> Self {
+        use component_defined_type::DefinedType;
+        use wasmparser::ComponentDefinedType as Defined;
+        let defined_type = match defined_type {
+            Defined::Primitive(primitive) => {
+                DefinedType::Primitive(ComponentPrimitiveType::from(*primitive) as i32)
+            }
+            Defined::Record(fields) => DefinedType::Record(ComponentRecordType {
+                fields: named_types(fields),
+            }),
+            Defined::Variant(cases) => DefinedType::Variant(ComponentVariantType {
+                cases: cases
+                    .iter()
+                    .map(|case| ComponentVariantCase {
+                        name: Some(case.name.to_string()),
+                        value_type: case.ty.map(ComponentValueType::from),
+                        refines: case.refines,
+                    })
+                    .collect(),
+            }),
+            Defined::List(element_type) => DefinedType::List((*element_type).into()),
+            Defined::Map(key, value) => DefinedType::Map(ComponentMapType {
+                key: Some((*key).into()),
+                value: Some((*value).into()),
+            }),
+            Defined::FixedSizeList(element_type, size) => {
+                DefinedType::FixedSizeList(ComponentFixedSizeListType {
+                    element_type: Some((*element_type).into()),
+                    size: Some(*size),
+                })
+            }
+            Defined::Tuple(types) => DefinedType::Tuple(ComponentTupleType {
+                types: types.iter().map(|ty| (*ty).into()).collect(),
+            }),
+            Defined::Flags(names) => DefinedType::Flags(name_list(names)),
+            Defined::Enum(names) => DefinedType::EnumCases(name_list(names)),
+            Defined::Option(value_type) => DefinedType::Option((*value_type).into()),
+            Defined::Result { ok, err } => DefinedType::Result(ComponentResultType {
+                ok: ok.map(ComponentValueType::from),
+                err: err.map(ComponentValueType::from),
+            }),
+            Defined::Own(index) => DefinedType::Own(*index),
+            Defined::Borrow(index) => DefinedType::Borrow(*index),
+            Defined::Future(payload) => DefinedType::Future(ComponentPayloadType {
+                payload: payload.map(ComponentValueType::from),
+            }),
+            Defined::Stream(payload) => DefinedType::Stream(ComponentPayloadType {
+                payload: payload.map(ComponentValueType::from),
+            }),
+        };
+        ComponentDefinedType {
+            defined_type: Some(defined_type),
+        }
+    }
+}
+
+fn optional_value_type(
+    value_type: Option<ComponentValueType>,
+) -> Result<Option<wasm_encoder::ComponentValType>> {
+    value_type.map(TryInto::try_into).transpose()
+}
+
+fn required_value_type(
+    value_type: Option<ComponentValueType>,
+    what: &'static str,
+) -> Result<wasm_encoder::ComponentValType> {
+    value_type.ok_or(Error::Missing(what))?.try_into()
+}
+
+fn encode_named_types(
+    types: &[ComponentNamedType],
+) -> Result<Vec<(&str, wasm_encoder::ComponentValType)>> {
+    let mut encoded = Vec::new();
+    for ty in types {
+        encoded.push((
+            ty.name
+                .as_deref()
+                .ok_or(Error::Missing("Named type name"))?,
+            required_value_type(ty.value_type, "Named type value type")?,
+        ));
+    }
+    Ok(encoded)
+}
+
+impl ComponentDefinedType {
+    fn render_wasm(&self, encoder: wasm_encoder::ComponentDefinedTypeEncoder<'_>) -> Result<()> {
+        use component_defined_type::DefinedType;
+        match self
+            .defined_type
+            .as_ref()
+            .ok_or(Error::Missing("Component defined type"))?
+        {
+            DefinedType::Primitive(primitive) => {
+                encoder.primitive(ComponentPrimitiveType::try_from(*primitive)?.into())
+            }
+            DefinedType::Record(record) => encoder.record(encode_named_types(&record.fields)?),
+            DefinedType::Variant(variant) => {
+                let mut cases = Vec::new();
+                for case in &variant.cases {
+                    cases.push((
+                        case.name
+                            .as_deref()
+                            .ok_or(Error::Missing("Variant case name"))?,
+                        optional_value_type(case.value_type)?,
+                        case.refines,
+                    ));
+                }
+                encoder.variant(cases);
+            }
+            DefinedType::List(element_type) => {
+                encoder.list(wasm_encoder::ComponentValType::try_from(*element_type)?)
+            }
+            DefinedType::Map(map) => encoder.map(
+                required_value_type(map.key, "Map key type")?,
+                required_value_type(map.value, "Map value type")?,
+            ),
+            DefinedType::FixedSizeList(list) => encoder.fixed_size_list(
+                required_value_type(list.element_type, "Fixed size list element type")?,
+                list.size.ok_or(Error::Missing("Fixed size list size"))?,
+            ),
+            DefinedType::Tuple(tuple) => {
+                let mut types: Vec<wasm_encoder::ComponentValType> = Vec::new();
+                for ty in &tuple.types {
+                    types.push((*ty).try_into()?);
+                }
+                encoder.tuple(types);
+            }
+            DefinedType::Flags(flags) => {
+                encoder.flags(flags.names.iter().map(String::as_str));
+            }
+            DefinedType::EnumCases(cases) => {
+                encoder.enum_type(cases.names.iter().map(String::as_str));
+            }
+            DefinedType::Option(value_type) => {
+                encoder.option(wasm_encoder::ComponentValType::try_from(*value_type)?)
+            }
+            DefinedType::Result(result) => encoder.result(
+                optional_value_type(result.ok)?,
+                optional_value_type(result.err)?,
+            ),
+            DefinedType::Own(index) => encoder.own(*index),
+            DefinedType::Borrow(index) => encoder.borrow(*index),
+            DefinedType::Future(future) => encoder.future(optional_value_type(future.payload)?),
+            DefinedType::Stream(stream) => encoder.stream(optional_value_type(stream.payload)?),
+        }
+        Ok(())
+    }
+}
+
+impl TryFrom<&wasmparser::ComponentTypeDeclaration<'_>> for ComponentTypeDeclaration {
+    type Error = Error;
+
+    fn try_from(declaration: &wasmparser::ComponentTypeDeclaration<'_>) -> Result<Self> {
+        use component_type_declaration::Declaration;
+        use wasmparser::ComponentTypeDeclaration as Decl;
+        let declaration = match declaration {
+            Decl::CoreType(core_type) => Declaration::CoreType(core_type.try_into()?),
+            Decl::Type(ty) => Declaration::ComponentType(ty.try_into()?),
+            Decl::Alias(alias) => Declaration::Alias(alias.clone().try_into()?),
+            Decl::Export { name, ty } => Declaration::Export(ComponentImport {
+                name: Some(name.0.to_string()),
+                ty: Some((*ty).into()),
+            }),
+            Decl::Import(import) => Declaration::Import((*import).into()),
+        };
+        Ok(ComponentTypeDeclaration {
+            declaration: Some(declaration),
+        })
+    }
+}
+
+impl TryFrom<&wasmparser::InstanceTypeDeclaration<'_>> for ComponentTypeDeclaration {
+    type Error = Error;
+
+    fn try_from(declaration: &wasmparser::InstanceTypeDeclaration<'_>) -> Result<Self> {
+        use component_type_declaration::Declaration;
+        use wasmparser::InstanceTypeDeclaration as Decl;
+        let declaration = match declaration {
+            Decl::CoreType(core_type) => Declaration::CoreType(core_type.try_into()?),
+            Decl::Type(ty) => Declaration::ComponentType(ty.try_into()?),
+            Decl::Alias(alias) => Declaration::Alias(alias.clone().try_into()?),
+            Decl::Export { name, ty } => Declaration::Export(ComponentImport {
+                name: Some(name.0.to_string()),
+                ty: Some((*ty).into()),
+            }),
+        };
+        Ok(ComponentTypeDeclaration {
+            declaration: Some(declaration),
+        })
+    }
+}
+
+impl TryFrom<&wasmparser::ComponentType<'_>> for ComponentType {
+    type Error = Error;
+
+    fn try_from(ty: &wasmparser::ComponentType<'_>) -> Result<Self> {
+        use component_type::ComponentType as Type;
+        let ty = match ty {
+            wasmparser::ComponentType::Defined(defined_type) => Type::Defined(defined_type.into()),
+            wasmparser::ComponentType::Func(func_type) => Type::Func(ComponentFuncType {
+                is_async: Some(func_type.async_),
+                params: named_types(&func_type.params),
+                result: func_type.result.map(ComponentValueType::from),
+            }),
+            wasmparser::ComponentType::Component(declarations) => {
+                let mut component_declarations: Vec<ComponentTypeDeclaration> = Vec::new();
+                for declaration in declarations {
+                    component_declarations.push(declaration.try_into()?);
+                }
+                Type::Component(ComponentTypeDeclarations {
+                    declarations: component_declarations,
+                })
+            }
+            wasmparser::ComponentType::Instance(declarations) => {
+                let mut instance_declarations: Vec<ComponentTypeDeclaration> = Vec::new();
+                for declaration in declarations {
+                    instance_declarations.push(declaration.try_into()?);
+                }
+                Type::Instance(ComponentTypeDeclarations {
+                    declarations: instance_declarations,
+                })
+            }
+            wasmparser::ComponentType::Resource { rep, dtor } => {
+                Type::Resource(ComponentResourceType {
+                    representation: Some(ValueType::try_from(*rep)?),
+                    destructor: *dtor,
+                })
+            }
+        };
+        Ok(ComponentType {
+            component_type: Some(ty),
+        })
+    }
+}
+
+impl ComponentType {
+    fn render_wasm(&self, encoder: wasm_encoder::ComponentTypeEncoder<'_>) -> Result<()> {
+        use component_type::ComponentType as Type;
+        use component_type_declaration::Declaration;
+        match self
+            .component_type
+            .as_ref()
+            .ok_or(Error::Missing("Component type"))?
+        {
+            Type::Defined(defined_type) => defined_type.render_wasm(encoder.defined_type())?,
+            Type::Func(func_type) => {
+                encoder
+                    .function()
+                    .async_(func_type.is_async.unwrap_or(false))
+                    .params(encode_named_types(&func_type.params)?)
+                    .result(optional_value_type(func_type.result)?);
+            }
+            Type::Component(declarations) => {
+                let mut component_type = wasm_encoder::ComponentType::new();
+                for declaration in &declarations.declarations {
+                    match declaration
+                        .declaration
+                        .as_ref()
+                        .ok_or(Error::Missing("Component type declaration"))?
+                    {
+                        Declaration::CoreType(core_type) => {
+                            core_type.render_wasm(component_type.core_type())?
+                        }
+                        Declaration::ComponentType(ty) => ty.render_wasm(component_type.ty())?,
+                        Declaration::Alias(alias) => {
+                            component_type.alias(alias.try_into()?);
+                        }
+                        Declaration::Import(import) => {
+                            let (name, ty) = import.to_wasm_encoder()?;
+                            component_type.import(name, ty);
+                        }
+                        Declaration::Export(export) => {
+                            let (name, ty) = export.to_wasm_encoder()?;
+                            component_type.export(name, ty);
+                        }
+                    }
+                }
+                encoder.component(&component_type);
+            }
+            Type::Instance(declarations) => {
+                let mut instance_type = wasm_encoder::InstanceType::new();
+                for declaration in &declarations.declarations {
+                    match declaration
+                        .declaration
+                        .as_ref()
+                        .ok_or(Error::Missing("Instance type declaration"))?
+                    {
+                        Declaration::CoreType(core_type) => {
+                            core_type.render_wasm(instance_type.core_type())?
+                        }
+                        Declaration::ComponentType(ty) => ty.render_wasm(instance_type.ty())?,
+                        Declaration::Alias(alias) => {
+                            instance_type.alias(alias.try_into()?);
+                        }
+                        Declaration::Import(_) => {
+                            return Err(Error::Invalid(
+                                "Instance types can't declare imports".to_string(),
+                            ));
+                        }
+                        Declaration::Export(export) => {
+                            let (name, ty) = export.to_wasm_encoder()?;
+                            instance_type.export(name, ty);
+                        }
+                    }
+                }
+                encoder.instance(&instance_type);
+            }
+            Type::Resource(resource) => encoder.resource(
+                resource
+                    .representation
+                    .ok_or(Error::Missing("Resource representation"))?
+                    .try_into()?,
+                resource.destructor,
+            ),
+        }
+        Ok(())
+    }
+}
+
+impl From<wasmparser::CanonicalOption> for CanonicalOption {
+    fn from(option: wasmparser::CanonicalOption) -> Self {
+        use canonical_option::Option as CanonOption;
+        let option = match option {
+            wasmparser::CanonicalOption::UTF8 => {
+                CanonOption::StringEncoding(CanonicalStringEncoding::Utf8 as i32)
+            }
+            wasmparser::CanonicalOption::UTF16 => {
+                CanonOption::StringEncoding(CanonicalStringEncoding::Utf16 as i32)
+            }
+            wasmparser::CanonicalOption::CompactUTF16 => {
+                CanonOption::StringEncoding(CanonicalStringEncoding::CompactUtf16 as i32)
+            }
+            wasmparser::CanonicalOption::Memory(index) => CanonOption::Memory(index),
+            wasmparser::CanonicalOption::Realloc(index) => CanonOption::Realloc(index),
+            wasmparser::CanonicalOption::PostReturn(index) => CanonOption::PostReturn(index),
+            wasmparser::CanonicalOption::Async => CanonOption::IsAsync(true),
+            wasmparser::CanonicalOption::Callback(index) => CanonOption::Callback(index),
+            wasmparser::CanonicalOption::CoreType(index) => CanonOption::CoreType(index),
+            wasmparser::CanonicalOption::Gc => CanonOption::Gc(true),
+        };
+        CanonicalOption {
+            option: Some(option),
+        }
+    }
+}
+
+impl TryFrom<CanonicalOption> for wasm_encoder::CanonicalOption {
+    type Error = Error;
+
+    fn try_from(option: CanonicalOption) -> Result<Self> {
+        use canonical_option::Option as CanonOption;
+        match option.option.ok_or(Error::Missing("Canonical option"))? {
+            CanonOption::StringEncoding(encoding) => {
+                match CanonicalStringEncoding::try_from(encoding)? {
+                    CanonicalStringEncoding::Utf8 => Ok(wasm_encoder::CanonicalOption::UTF8),
+                    CanonicalStringEncoding::Utf16 => Ok(wasm_encoder::CanonicalOption::UTF16),
+                    CanonicalStringEncoding::CompactUtf16 => {
+                        Ok(wasm_encoder::CanonicalOption::CompactUTF16)
+                    }
+                }
+            }
+            CanonOption::Memory(index) => Ok(wasm_encoder::CanonicalOption::Memory(index)),
+            CanonOption::Realloc(index) => Ok(wasm_encoder::CanonicalOption::Realloc(index)),
+            CanonOption::PostReturn(index) => Ok(wasm_encoder::CanonicalOption::PostReturn(index)),
+            CanonOption::IsAsync(_) => Ok(wasm_encoder::CanonicalOption::Async),
+            CanonOption::Callback(index) => Ok(wasm_encoder::CanonicalOption::Callback(index)),
+            CanonOption::CoreType(index) => Ok(wasm_encoder::CanonicalOption::CoreType(index)),
+            CanonOption::Gc(_) => Ok(wasm_encoder::CanonicalOption::Gc),
+        }
+    }
+}
+
+fn encode_canonical_options(
+    options: &[CanonicalOption],
+) -> Result<Vec<wasm_encoder::CanonicalOption>> {
+    let mut encoded = Vec::new();
+    for option in options {
+        encoded.push((*option).try_into()?);
+    }
+    Ok(encoded)
+}
+
+impl TryFrom<wasmparser::CanonicalFunction> for CanonicalFunction {
+    type Error = Error;
+
+    fn try_from(function: wasmparser::CanonicalFunction) -> Result<Self> {
+        use canonical_function::Function;
+        let function = match function {
+            wasmparser::CanonicalFunction::Lift {
+                core_func_index,
+                type_index,
+                options,
+            } => Function::Lift(CanonicalLift {
+                core_func_index: Some(core_func_index),
+                type_index: Some(type_index),
+                options: options.iter().map(|option| (*option).into()).collect(),
+            }),
+            wasmparser::CanonicalFunction::Lower {
+                func_index,
+                options,
+            } => Function::Lower(CanonicalLower {
+                func_index: Some(func_index),
+                options: options.iter().map(|option| (*option).into()).collect(),
+            }),
+            wasmparser::CanonicalFunction::ResourceNew { resource } => {
+                Function::ResourceNew(resource)
+            }
+            wasmparser::CanonicalFunction::ResourceDrop { resource } => {
+                Function::ResourceDrop(resource)
+            }
+            wasmparser::CanonicalFunction::ResourceRep { resource } => {
+                Function::ResourceRep(resource)
+            }
+            function => {
+                return Err(Error::Unsupported(format!(
+                    "Unsupported canonical function {:?}",
+                    function
+                )));
+            }
+        };
+        Ok(CanonicalFunction {
+            function: Some(function),
+        })
+    }
+}
+
+impl TryFrom<wasmparser::Instance<'_>> for CoreInstance {
+    type Error = Error;
+
+    fn try_from(instance: wasmparser::Instance<'_>) -> Result<Self> {
+        use core_instance::Instance;
+        let instance = match instance {
+            wasmparser::Instance::Instantiate { module_index, args } => {
+                Instance::Instantiate(CoreInstantiation {
+                    module_index: Some(module_index),
+                    args: args
+                        .iter()
+                        .map(|arg| CoreInstantiationArg {
+                            name: Some(arg.name.to_string()),
+                            instance_index: Some(arg.index),
+                        })
+                        .collect(),
+                })
+            }
+            wasmparser::Instance::FromExports(exports) => {
+                let mut instance_exports: Vec<CoreInstanceExport> = Vec::new();
+                for export in exports {
+                    instance_exports.push(CoreInstanceExport {
+                        name: Some(export.name.to_string()),
+                        kind: Some(ExternalKind::try_from(export.kind)? as i32),
+                        index: Some(export.index),
+                    });
+                }
+                Instance::FromExports(CoreInstanceExports {
+                    exports: instance_exports,
+                })
+            }
+        };
+        Ok(CoreInstance {
+            instance: Some(instance),
+        })
+    }
+}
+
+impl From<wasmparser::ComponentInstance<'_>> for ComponentInstance {
+    fn from(instance: wasmparser::ComponentInstance<'_>) -> Self {
+        use component_instance::Instance;
+        let instance = match instance {
+            wasmparser::ComponentInstance::Instantiate {
+                component_index,
+                args,
+            } => Instance::Instantiate(ComponentInstantiation {
+                component_index: Some(component_index),
+                args: args
+                    .iter()
+                    .map(|arg| ComponentInstantiationArg {
+                        name: Some(arg.name.to_string()),
+                        kind: Some(ComponentExternalKind::from(arg.kind) as i32),
+                        index: Some(arg.index),
+                    })
+                    .collect(),
+            }),
+            wasmparser::ComponentInstance::FromExports(exports) => {
+                Instance::FromExports(ComponentInstanceExports {
+                    exports: exports.iter().cloned().map(ComponentExport::from).collect(),
+                })
+            }
+        };
+        ComponentInstance {
+            instance: Some(instance),
+        }
+    }
+}
+
+impl CoreInstanceSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::InstanceSectionReader<'_>,
+    ) -> Result<CoreInstanceSection> {
+        let mut instances: Vec<CoreInstance> = Vec::new();
+        for instance in reader {
+            instances.push(instance?.try_into()?);
+        }
+        Ok(CoreInstanceSection { instances })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        use core_instance::Instance;
+        let mut section = wasm_encoder::InstanceSection::new();
+        for instance in &self.instances {
+            match instance
+                .instance
+                .as_ref()
+                .ok_or(Error::Missing("Core instance"))?
+            {
+                Instance::Instantiate(instantiation) => {
+                    let mut args = Vec::new();
+                    for arg in &instantiation.args {
+                        args.push((
+                            arg.name
+                                .as_deref()
+                                .ok_or(Error::Missing("Instantiation argument name"))?,
+                            wasm_encoder::ModuleArg::Instance(
+                                arg.instance_index
+                                    .ok_or(Error::Missing("Instantiation argument instance"))?,
+                            ),
+                        ));
+                    }
+                    section.instantiate(
+                        instantiation
+                            .module_index
+                            .ok_or(Error::Missing("Instantiation module index"))?,
+                        args,
+                    );
+                }
+                Instance::FromExports(exports) => {
+                    let mut items = Vec::new();
+                    for export in &exports.exports {
+                        items.push((
+                            export
+                                .name
+                                .as_deref()
+                                .ok_or(Error::Missing("Instance export name"))?,
+                            wasm_encoder::ExportKind::try_from(ExternalKind::try_from(
+                                export.kind.ok_or(Error::Missing("Instance export kind"))?,
+                            )?)?,
+                            export
+                                .index
+                                .ok_or(Error::Missing("Instance export index"))?,
+                        ));
+                    }
+                    section.export_items(items);
+                }
+            }
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl CoreTypeSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::CoreTypeSectionReader<'_>,
+    ) -> Result<CoreTypeSection> {
+        let mut types: Vec<CoreType> = Vec::new();
+        for ty in reader {
+            types.push((&ty?).try_into()?);
+        }
+        Ok(CoreTypeSection { types })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        let mut section = wasm_encoder::CoreTypeSection::new();
+        for ty in &self.types {
+            ty.render_wasm(section.ty())?;
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl ComponentInstanceSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::ComponentInstanceSectionReader<'_>,
+    ) -> Result<ComponentInstanceSection> {
+        let mut instances: Vec<ComponentInstance> = Vec::new();
+        for instance in reader {
+            instances.push(instance?.into());
+        }
+        Ok(ComponentInstanceSection { instances })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        use component_instance::Instance;
+        let mut section = wasm_encoder::ComponentInstanceSection::new();
+        for instance in &self.instances {
+            match instance
+                .instance
+                .as_ref()
+                .ok_or(Error::Missing("Component instance"))?
+            {
+                Instance::Instantiate(instantiation) => {
+                    let mut args = Vec::new();
+                    for arg in &instantiation.args {
+                        args.push((
+                            arg.name
+                                .as_deref()
+                                .ok_or(Error::Missing("Instantiation argument name"))?,
+                            ComponentExternalKind::try_from(
+                                arg.kind
+                                    .ok_or(Error::Missing("Instantiation argument kind"))?,
+                            )?
+                            .into(),
+                            arg.index
+                                .ok_or(Error::Missing("Instantiation argument index"))?,
+                        ));
+                    }
+                    section.instantiate(
+                        instantiation
+                            .component_index
+                            .ok_or(Error::Missing("Instantiation component index"))?,
+                        args,
+                    );
+                }
+                Instance::FromExports(exports) => {
+                    let mut items = Vec::new();
+                    for export in &exports.exports {
+                        items.push(export.to_wasm_encoder()?);
+                    }
+                    section.export_items(items);
+                }
+            }
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl ComponentAliasSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::ComponentAliasSectionReader<'_>,
+    ) -> Result<ComponentAliasSection> {
+        let mut aliases: Vec<ComponentAlias> = Vec::new();
+        for alias in reader {
+            aliases.push(alias?.try_into()?);
+        }
+        Ok(ComponentAliasSection { aliases })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        let mut section = wasm_encoder::ComponentAliasSection::new();
+        for alias in &self.aliases {
+            section.alias(alias.try_into()?);
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl ComponentTypeSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::ComponentTypeSectionReader<'_>,
+    ) -> Result<ComponentTypeSection> {
+        let mut types: Vec<ComponentType> = Vec::new();
+        for ty in reader {
+            types.push((&ty?).try_into()?);
+        }
+        Ok(ComponentTypeSection { types })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        let mut section = wasm_encoder::ComponentTypeSection::new();
+        for ty in &self.types {
+            ty.render_wasm(section.ty())?;
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl ComponentCanonicalSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::ComponentCanonicalSectionReader<'_>,
+    ) -> Result<ComponentCanonicalSection> {
+        let mut functions: Vec<CanonicalFunction> = Vec::new();
+        for function in reader {
+            functions.push(function?.try_into()?);
+        }
+        Ok(ComponentCanonicalSection { functions })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        use canonical_function::Function;
+        let mut section = wasm_encoder::CanonicalFunctionSection::new();
+        for function in &self.functions {
+            match function
+                .function
+                .as_ref()
+                .ok_or(Error::Missing("Canonical function"))?
+            {
+                Function::Lift(lift) => {
+                    section.lift(
+                        lift.core_func_index
+                            .ok_or(Error::Missing("Lift core function index"))?,
+                        lift.type_index.ok_or(Error::Missing("Lift type index"))?,
+                        encode_canonical_options(&lift.options)?,
+                    );
+                }
+                Function::Lower(lower) => {
+                    section.lower(
+                        lower
+                            .func_index
+                            .ok_or(Error::Missing("Lower function index"))?,
+                        encode_canonical_options(&lower.options)?,
+                    );
+                }
+                Function::ResourceNew(resource) => {
+                    section.resource_new(*resource);
+                }
+                Function::ResourceDrop(resource) => {
+                    section.resource_drop(*resource);
+                }
+                Function::ResourceRep(resource) => {
+                    section.resource_rep(*resource);
+                }
+            }
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl ComponentStartFunction {
+    pub fn from_wasmparser(start: wasmparser::ComponentStartFunction) -> ComponentStartFunction {
+        ComponentStartFunction {
+            func_index: Some(start.func_index),
+            arguments: start.arguments.to_vec(),
+            results: Some(start.results),
+        }
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        component.section(&wasm_encoder::ComponentStartSection {
+            function_index: self
+                .func_index
+                .ok_or(Error::Missing("Start function index"))?,
+            args: &self.arguments,
+            results: self.results.ok_or(Error::Missing("Start results"))?,
+        });
+        Ok(())
+    }
+}
+
+impl ComponentImportSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::ComponentImportSectionReader<'_>,
+    ) -> Result<ComponentImportSection> {
+        let mut imports: Vec<ComponentImport> = Vec::new();
+        for import in reader {
+            imports.push(import?.into());
+        }
+        Ok(ComponentImportSection { imports })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        let mut section = wasm_encoder::ComponentImportSection::new();
+        for import in &self.imports {
+            let (name, ty) = import.to_wasm_encoder()?;
+            section.import(name, ty);
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+impl ComponentExportSection {
+    pub fn from_wasmparser(
+        reader: wasmparser::ComponentExportSectionReader<'_>,
+    ) -> Result<ComponentExportSection> {
+        let mut exports: Vec<ComponentExport> = Vec::new();
+        for export in reader {
+            exports.push(export?.into());
+        }
+        Ok(ComponentExportSection { exports })
+    }
+
+    pub fn render_wasm(&self, component: &mut wasm_encoder::Component) -> Result<()> {
+        let mut section = wasm_encoder::ComponentExportSection::new();
+        for export in &self.exports {
+            let (name, kind, index) = export.to_wasm_encoder()?;
+            section.export(
+                name,
+                kind,
+                index,
+                export.ty.map(TryInto::try_into).transpose()?,
+            );
+        }
+        component.section(&section);
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_primitive_roundtrip() {
+        use wasmparser::PrimitiveValType as Primitive;
+        for primitive in [
+            Primitive::Bool,
+            Primitive::S8,
+            Primitive::U64,
+            Primitive::F32,
+            Primitive::Char,
+            Primitive::String,
+            Primitive::ErrorContext,
+        ] {
+            let value_type =
+                ComponentValueType::from(wasmparser::ComponentValType::Primitive(primitive));
+            let encoded = wasm_encoder::ComponentValType::try_from(value_type).unwrap();
+            assert_eq!(
+                format!("{:?}", encoded),
+                format!("Primitive({:?})", primitive)
+            );
+        }
+    }
+
+    #[test]
+    fn test_value_type_index() {
+        let value_type = ComponentValueType::from(wasmparser::ComponentValType::Type(7));
+        assert_eq!(
+            value_type.value_type,
+            Some(component_value_type::ValueType::TypeIndex(7))
+        );
+        assert!(matches!(
+            wasm_encoder::ComponentValType::try_from(value_type).unwrap(),
+            wasm_encoder::ComponentValType::Type(7)
+        ));
+    }
+
+    #[test]
+    fn test_value_type_missing() {
+        assert!(matches!(
+            wasm_encoder::ComponentValType::try_from(ComponentValueType::default()),
+            Err(Error::Missing(_))
+        ));
+    }
+
+    #[test]
+    fn test_canonical_options_roundtrip() {
+        let options = [
+            wasmparser::CanonicalOption::UTF8,
+            wasmparser::CanonicalOption::UTF16,
+            wasmparser::CanonicalOption::CompactUTF16,
+            wasmparser::CanonicalOption::Memory(1),
+            wasmparser::CanonicalOption::Realloc(2),
+            wasmparser::CanonicalOption::PostReturn(3),
+            wasmparser::CanonicalOption::Async,
+            wasmparser::CanonicalOption::Callback(4),
+            wasmparser::CanonicalOption::CoreType(5),
+            wasmparser::CanonicalOption::Gc,
+        ];
+        for option in options {
+            let encoded =
+                wasm_encoder::CanonicalOption::try_from(CanonicalOption::from(option)).unwrap();
+            assert_eq!(format!("{:?}", encoded), format!("{:?}", option));
+        }
+    }
+
+    #[test]
+    fn test_canonical_function_unsupported() {
+        let result = CanonicalFunction::try_from(wasmparser::CanonicalFunction::TaskCancel);
+        assert!(matches!(result, Err(Error::Unsupported(_))));
+    }
+
+    #[test]
+    fn test_type_ref_sub_resource() {
+        let type_ref = ComponentTypeRef::from(wasmparser::ComponentTypeRef::Type(
+            wasmparser::TypeBounds::SubResource,
+        ));
+        assert!(matches!(
+            wasm_encoder::ComponentTypeRef::try_from(type_ref).unwrap(),
+            wasm_encoder::ComponentTypeRef::Type(wasm_encoder::TypeBounds::SubResource)
+        ));
+    }
+
+    #[test]
+    fn test_instance_type_rejects_import() {
+        use component_type_declaration::Declaration;
+        let ty = ComponentType {
+            component_type: Some(component_type::ComponentType::Instance(
+                ComponentTypeDeclarations {
+                    declarations: vec![ComponentTypeDeclaration {
+                        declaration: Some(Declaration::Import(ComponentImport {
+                            name: Some("f".to_string()),
+                            ty: Some(ComponentTypeRef {
+                                type_ref: Some(component_type_ref::TypeRef::Func(0)),
+                            }),
+                        })),
+                    }],
+                },
+            )),
+        };
+        let mut section = wasm_encoder::ComponentTypeSection::new();
+        assert!(matches!(
+            ty.render_wasm(section.ty()),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_implicit_rec_group_needs_one_type() {
+        let group = CoreRecGroup {
+            types: vec![],
+            explicit: Some(false),
+        };
+        let mut section = wasm_encoder::CoreTypeSection::new();
+        assert!(matches!(
+            group.render_wasm(section.ty().core()),
+            Err(Error::Invalid(_))
+        ));
+    }
+}
@@ -0,0 +1,598 @@
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+use crate::remap::{
+    IndexSpace, Remapping, imported_count, renumbering, retain_live, visit_indices,
+};
+
+#[derive(Clone, Copy, Debug)]
+enum Item {
+    Function(u32),
+    Global(u32),
+    Element(u32),
+}
+
+fn reference(operator: &Operator) -> Option<Item> {
+    match &operator.operator {
+        Some(operator::Operator::FunctionIndex(index)) => Some(Item::Function(*index)),
+        Some(operator::Operator::GlobalIndex(index)) => Some(Item::Global(*index)),
+        Some(operator::Operator::ElementIndex(index)) => Some(Item::Element(*index)),
+        Some(operator::Operator::TableInit(table_init)) => {
+            table_init.element_index.map(Item::Element)
+        }
+        Some(operator::Operator::ArrayElem(array_elem)) => {
+            array_elem.element_index.map(Item::Element)
+        }
+        _ => None,
+    }
+}
+
+fn element_kind_type(element: &Element) -> Result<Option<ElementKindType>> {
+    match element.kind.as_ref().and_then(|kind| kind.r#type) {
+        Some(ty) => Ok(Some(ElementKindType::try_from(ty)?)),
+        None => Ok(None),
+    }
+}
+
+/// Reachability of functions, globals and element segments. Imports are always live, since
+/// removing them would change what the module links against.
+struct Liveness<'a> {
+    program: &'a ProgramModule,
+    imported_functions: u32,
+    imported_globals: u32,
+    functions: Vec<bool>,
+    globals: Vec<bool>,
+    elements: Vec<bool>,
+    pending: Vec<Item>,
+}
+
+impl<'a> Liveness<'a> {
+    fn new(program: &'a ProgramModule) -> Self {
+        let imported_functions = imported_count(program, IndexSpace::Function);
+        let imported_globals = imported_count(program, IndexSpace::Global);
+        let defined_functions = program
+            .function_section
+            .as_ref()
+            .map_or(0, |section| section.type_idxs.len());
+        let defined_globals = program
+            .global_section
+            .as_ref()
+            .map_or(0, |section| section.globals.len());
+        let elements = program
+            .element_section
+            .as_ref()
+            .map_or(0, |section| section.elements.len());
+        Liveness {
+            program,
+            imported_functions,
+            imported_globals,
+            functions: vec![false; imported_functions as usize + defined_functions],
+            globals: vec![false; imported_globals as usize + defined_globals],
+            elements: vec![false; elements],
+            pending: Vec::new(),
+        }
+    }
+
+    fn mark(&mut self, item: Item) -> Result<()> {
+        let (live, index) = match item {
+            Item::Function(index) => (&mut self.functions, index),
+            Item::Global(index) => (&mut self.globals, index),
+            Item::Element(index) => (&mut self.elements, index),
+        };
+        let live = live
+            .get_mut(index as usize)
+            .ok_or_else(|| Error::Invalid(format!("{:?} is out of bounds", item)))?;
+        if !*live {
+            *live = true;
+            self.pending.push(item);
+        }
+        Ok(())
+    }
+
+    fn mark_operators<'b>(&mut self, operators: impl Iterator<Item = &'b Operator>) -> Result<()> {
+        for operator in operators {
+            if let Some(item) = reference(operator) {
+                self.mark(item)?;
+            }
+        }
+        Ok(())
+    }
+
+    fn mark_expression(&mut self, expression: Option<&Expression>) -> Result<()> {
+        self.mark_operators(
+            expression
+                .iter()
+                .flat_map(|expression| &expression.operators),
+        )
+    }
+
+    /// Marks what the module can't run without: imports, exports, the start function, active
+    /// element segments and the constant expressions initializing tables and data segments.
+    fn mark_roots(&mut self) -> Result<()> {
+        let program = self.program;
+        for index in 0..self.imported_functions {
+            self.mark(Item::Function(index))?;
+        }
+        for index in 0..self.imported_globals {
+            self.mark(Item::Global(index))?;
+        }
+        let exports = program
+            .export_section
+            .iter()
+            .flat_map(|section| &section.exports);
+        for export in exports {
+            let index = export.index.ok_or(Error::Missing("Export index"))?;
+            match ExternalKind::try_from(export.kind.ok_or(Error::Missing("Export kind"))?)? {
+                ExternalKind::ExtFunc | ExternalKind::ExtFuncExact => {
+                    self.mark(Item::Function(index))?
+                }
+                ExternalKind::ExtGlobal => self.mark(Item::Global(index))?,
+                _ => {}
+            }
+        }
+        if let Some(index) = program
+            .start_section
+            .as_ref()
+            .and_then(|section| section.function_index)
+        {
+            self.mark(Item::Function(index))?;
+        }
+        let elements = program
+            .element_section
+            .iter()
+            .flat_map(|section| &section.elements);
+        for (index, element) in elements.enumerate() {
+            if !matches!(
+                element_kind_type(element)?,
+                Some(ElementKindType::ElPassive | ElementKindType::ElDeclared)
+            ) {
+                self.mark(Item::Element(index as u32))?;
+            }
+        }
+        let tables = program
+            .table_section
+            .iter()
+            .flat_map(|section| &section.types);
+        for table in tables {
+            self.mark_expression(table.init_expr.as_ref())?;
+        }
+        let datas = program
+            .data_section
+            .iter()
+            .flat_map(|section| &section.datas);
+        for data in datas {
+            self.mark_expression(data.kind.as_ref().and_then(|kind| kind.expression.as_ref()))?;
+        }
+        Ok(())
+    }
+
+    /// Marks everything the live items refer to, through calls, `ref.func`, global accesses and
+    /// element segment operators.
+    fn propagate(&mut self) -> Result<()> {
+        let program = self.program;
+        while let Some(item) = self.pending.pop() {
+            match item {
+                Item::Function(index) => {
+                    if let Some(defined) = index.checked_sub(self.imported_functions) {
+                        let entry = program
+                            .code_section
+                            .as_ref()
+                            .and_then(|section| section.code_section_entry.get(defined as usize))
+                            .ok_or(Error::Missing("Function body"))?;
+                        self.mark_operators(entry.body.iter())?;
+                    }
+                }
+                Item::Global(index) => {
+                    if let Some(defined) = index.checked_sub(self.imported_globals) {
+                        let global = program
+                            .global_section
+                            .as_ref()
+                            .and_then(|section| section.globals.get(defined as usize))
+                            .ok_or(Error::Missing("Global"))?;
+                        self.mark_expression(global.init_expr.as_ref())?;
+                    }
+                }
+                Item::Element(index) => {
+                    let element = program
+                        .element_section
+                        .as_ref()
+                        .and_then(|section| section.elements.get(index as usize))
+                        .ok_or(Error::Missing("Element"))?;
+                    self.mark_expression(
+                        element
+                            .kind
+                            .as_ref()
+                            .and_then(|kind| kind.expression.as_ref()),
+                    )?;
+                    match &element.items {
+                        Some(element::Items::Functions(functions)) => {
+                            for index in &functions.functions {
+                                self.mark(Item::Function(*index))?;
+                            }
+                        }
+                        Some(element::Items::Expressions(expressions)) => {
+                            for expression in &expressions.expressions {
+                                self.mark_expression(Some(expression))?;
+                            }
+                        }
+                        None => {}
+                    }
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+fn is_live(live: &[bool], index: u32) -> bool {
+    live.get(index as usize).copied().unwrap_or(true)
+}
+
+fn renumber(renumbering: &[u32], index: u32) -> u32 {
+    renumbering.get(index as usize).copied().unwrap_or(index)
+}
+
+/// Prunes declarative element segments down to their live functions, keeping the ones that
+/// still declare something.
+fn prune_declarations(
+    program: &mut ProgramModule,
+    functions: &[bool],
+    elements: &mut [bool],
+) -> Result<()> {
+    let segments = program
+        .element_section
+        .iter_mut()
+        .flat_map(|section| section.elements.iter_mut());
+    for (index, element) in segments.enumerate() {
+        if elements[index] || element_kind_type(element)? != Some(ElementKindType::ElDeclared) {
+            continue;
+        }
+        let declares_live = |expression: &Expression| match expression.operators.first() {
+            Some(Operator {
+                operator: Some(operator::Operator::FunctionIndex(function)),
+                ..
+            }) => is_live(functions, *function),
+            _ => true,
+        };
+        elements[index] = match &mut element.items {
+            Some(element::Items::Functions(declared)) => {
+                declared
+                    .functions
+                    .retain(|function| is_live(functions, *function));
+                !declared.functions.is_empty()
+            }
+            Some(element::Items::Expressions(declared)) => {
+                declared.expressions.retain(declares_live);
+                !declared.expressions.is_empty()
+            }
+            None => false,
+        };
+    }
+    Ok(())
+}
+
+/// Whether the type section groups types explicitly. Removing a type from a recursion group
+/// changes the identity of the others, so the type section of such modules is left alone.
+fn has_explicit_rec_groups(program: &ProgramModule) -> bool {
+    program.type_section.as_ref().is_some_and(|section| {
+        section
+            .rec_groups
+            .iter()
+            .any(|group| group.explicit == Some(true) || group.type_count != Some(1))
+    })
+}
+
+fn retain_names(names: Option<&mut NameMap>, live: &[bool]) {
+    if let Some(names) = names {
+        names
+            .names
+            .retain(|naming| naming.index.is_none_or(|index| is_live(live, index)));
+    }
+}
+
+fn retain_indirect_names(names: Option<&mut IndirectNameMap>, live: &[bool]) {
+    if let Some(names) = names {
+        names
+            .names
+            .retain(|naming| naming.index.is_none_or(|index| is_live(live, index)));
+    }
+}
+
+/// Removes the functions, globals, element segments and types the module can't reach from its
+/// exports, start function, active element segments and `ref.func` operators, then renumbers
+/// every reference to the ones left.
+pub fn eliminate_dead_code(mut program: ProgramModule) -> Result<ProgramModule> {
+    let mut liveness = Liveness::new(&program);
+    liveness.mark_roots()?;
+    liveness.propagate()?;
+    let Liveness {
+        imported_functions,
+        imported_globals,
+        functions,
+        globals,
+        mut elements,
+        ..
+    } = liveness;
+    prune_declarations(&mut program, &functions, &mut elements)?;
+
+    if let Some(section) = &mut program.function_section {
+        retain_live(
+            &mut section.type_idxs,
+            &functions[imported_functions as usize..],
+        );
+    }
+    if let Some(section) = &mut program.code_section {
+        retain_live(
+            &mut section.code_section_entry,
+            &functions[imported_functions as usize..],
+        );
+    }
+    if let Some(section) = &mut program.global_section {
+        retain_live(&mut section.globals, &globals[imported_globals as usize..]);
+    }
+    if let Some(section) = &mut program.element_section {
+        retain_live(&mut section.elements, &elements);
+    }
+
+    // types referenced by dead types are kept too, the dead types being gone only afterwards
+    let types = if has_explicit_rec_groups(&program) {
+        None
+    } else {
+        let type_count = program
+            .type_section
+            .as_ref()
+            .map_or(0, |section| section.types.len());
+        let mut types = vec![false; type_count];
+        visit_indices(&mut program, &mut |space, index| {
+            if space == IndexSpace::Type
+                && let Some(live) = types.get_mut(*index as usize)
+            {
+                *live = true;
+            }
+        });
+        Some(types)
+    };
+    if let (Some(types), Some(section)) = (&types, &mut program.type_section) {
+        retain_live(&mut section.types, types);
+        retain_live(&mut section.rec_groups, types);
+    }
+
+    let name_sections = program
+        .custom_sections
+        .iter_mut()
+        .filter_map(|section| section.name_section.as_mut());
+    for name_section in name_sections {
+        retain_names(name_section.function_names.as_mut(), &functions);
+        retain_indirect_names(name_section.local_names.as_mut(), &functions);
+        retain_indirect_names(name_section.label_names.as_mut(), &functions);
+        retain_names(name_section.global_names.as_mut(), &globals);
+        retain_names(name_section.element_names.as_mut(), &elements);
+        if let Some(types) = &types {
+            retain_names(name_section.type_names.as_mut(), types);
+            retain_indirect_names(name_section.field_names.as_mut(), types);
+        }
+    }
+
+    let function_renumbering = renumbering(&functions);
+    let global_renumbering = renumbering(&globals);
+    let element_renumbering = renumbering(&elements);
+    let type_renumbering = types.as_deref().map(renumbering);
+    let mut remapping = Remapping::new()
+        .with(IndexSpace::Function, |index| {
+            renumber(&function_renumbering, index)
+        })
+        .with(IndexSpace::Global, |index| {
+            renumber(&global_renumbering, index)
+        })
+        .with(IndexSpace::Element, |index| {
+            renumber(&element_renumbering, index)
+        });
+    if let Some(type_renumbering) = &type_renumbering {
+        remapping = remapping.with(IndexSpace::Type, |index| renumber(type_renumbering, index));
+    }
+    remapping.apply(&mut program);
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        base_types, code_section, funcref_table, log_import, operand, validate,
+    };
+    use wasm_encoder::{
+        ConstExpr, ElementSection, Elements, ExportKind, FunctionSection, GlobalSection,
+        IndirectNameMap as EncoderIndirectNameMap, Instruction, Module, NameMap as EncoderNameMap,
+        NameSection as EncoderNameSection, ValType,
+    };
+
+    /// Creates a module where `run` calls a helper reading a global and taking a reference to
+    /// a declared function, next to a dead function only held by a passive segment, a table
+    /// entry, and dead globals, types and declarations. `drop_passive` makes the table entry
+    /// drop the passive segment, which keeps it and its function alive.
+    fn create_module(drop_passive: bool) -> Vec<u8> {
+        let mut module = Module::new();
+        let mut types = base_types();
+        types.ty().function([ValType::I64], []);
+        types.ty().function([ValType::F32], []);
+        module.section(&types);
+        module.section(&log_import(0));
+        let mut functions = FunctionSection::new();
+        for type_index in [1, 2, 0, 1, 0, 0] {
+            functions.function(type_index);
+        }
+        module.section(&functions);
+        module.section(&funcref_table());
+        let mut globals = GlobalSection::new();
+        for _ in 0..3 {
+            globals.global(
+                wasm_encoder::GlobalType {
+                    val_type: ValType::I32,
+                    mutable: true,
+                    shared: false,
+                },
+                &ConstExpr::i32_const(0),
+            );
+        }
+        module.section(&globals);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("run", ExportKind::Func, 1);
+        exports.export("counter", ExportKind::Global, 2);
+        module.section(&exports);
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            Elements::Functions([4].as_slice().into()),
+        );
+        elements.passive(Elements::Functions([2].as_slice().into()));
+        elements.declared(Elements::Functions([5, 6].as_slice().into()));
+        module.section(&elements);
+
+        let mut bodies: Vec<Vec<Instruction>> = vec![
+            vec![Instruction::Call(3), Instruction::LocalGet(0)],
+            vec![
+                Instruction::I32Const(0),
+                Instruction::Call(1),
+                Instruction::Drop,
+            ],
+            vec![
+                Instruction::GlobalGet(1),
+                Instruction::Drop,
+                Instruction::RefFunc(5),
+                Instruction::Drop,
+            ],
+            vec![Instruction::LocalGet(0)],
+            vec![Instruction::Call(0)],
+            vec![],
+        ];
+        if drop_passive {
+            bodies[3].insert(0, Instruction::ElemDrop(1));
+        }
+        module.section(&code_section(bodies));
+
+        let mut names = EncoderNameSection::new();
+        let mut function_names = EncoderNameMap::new();
+        function_names.append(1, "run");
+        function_names.append(2, "dead");
+        names.functions(&function_names);
+        let mut local_names = EncoderIndirectNameMap::new();
+        let mut argument = EncoderNameMap::new();
+        argument.append(0, "argument");
+        local_names.append(2, &argument);
+        local_names.append(4, &argument);
+        names.locals(&local_names);
+        module.section(&names);
+        module.finish()
+    }
+
+    fn name_section(program: &ProgramModule) -> &NameSection {
+        program
+            .custom_sections
+            .iter()
+            .find_map(|section| section.name_section.as_ref())
+            .unwrap()
+    }
+
+    #[test]
+    fn test_eliminate_dead_code() {
+        let program = crate::from_wasm(&create_module(false)).unwrap();
+        let program = eliminate_dead_code(program).unwrap();
+        validate(&program);
+
+        assert_eq!(
+            program.function_section.as_ref().unwrap().type_idxs,
+            vec![1, 0, 1, 0]
+        );
+        assert_eq!(program.type_section.as_ref().unwrap().types.len(), 2);
+        assert_eq!(program.global_section.as_ref().unwrap().globals.len(), 2);
+
+        let exports = &program.export_section.as_ref().unwrap().exports;
+        assert_eq!(exports[0].index, Some(1));
+        assert_eq!(exports[1].index, Some(1));
+        assert_eq!(
+            operand(&program, 0, 0),
+            operator::Operator::FunctionIndex(2)
+        );
+        assert_eq!(operand(&program, 1, 0), operator::Operator::GlobalIndex(0));
+        assert_eq!(
+            operand(&program, 1, 2),
+            operator::Operator::FunctionIndex(4)
+        );
+        assert_eq!(
+            operand(&program, 3, 0),
+            operator::Operator::FunctionIndex(0)
+        );
+    }
+
+    #[test]
+    fn test_eliminate_dead_code_elements() {
+        let program = crate::from_wasm(&create_module(false)).unwrap();
+        let program = eliminate_dead_code(program).unwrap();
+        let elements = &program.element_section.as_ref().unwrap().elements;
+        assert_eq!(elements.len(), 2);
+        assert_eq!(
+            elements[0].items,
+            Some(element::Items::Functions(ElementFunctions {
+                functions: vec![3]
+            }))
+        );
+        // the declaration of the dead function is pruned
+        assert_eq!(
+            elements[1].items,
+            Some(element::Items::Functions(ElementFunctions {
+                functions: vec![4]
+            }))
+        );
+    }
+
+    #[test]
+    fn test_eliminate_dead_code_passive_element() {
+        let program = crate::from_wasm(&create_module(true)).unwrap();
+        let program = eliminate_dead_code(program).unwrap();
+        validate(&program);
+
+        assert_eq!(
+            program.function_section.as_ref().unwrap().type_idxs,
+            vec![1, 2, 0, 1, 0]
+        );
+        assert_eq!(program.type_section.as_ref().unwrap().types.len(), 3);
+        let elements = &program.element_section.as_ref().unwrap().elements;
+        assert_eq!(elements.len(), 3);
+        assert_eq!(operand(&program, 3, 0), operator::Operator::ElementIndex(1));
+    }
+
+    #[test]
+    fn test_eliminate_dead_code_names() {
+        let program = crate::from_wasm(&create_module(false)).unwrap();
+        let program = eliminate_dead_code(program).unwrap();
+        let names = name_section(&program);
+        assert_eq!(
+            names.function_names,
+            Some(NameMap {
+                names: vec![Naming {
+                    index: Some(1),
+                    name: Some("run".to_string()),
+                }],
+            })
+        );
+        let local_names = names.local_names.as_ref().unwrap();
+        assert_eq!(local_names.names.len(), 1);
+        assert_eq!(local_names.names[0].index, Some(3));
+    }
+
+    #[test]
+    fn test_eliminate_dead_code_keeps_rec_groups() {
+        let mut program = crate::from_wasm(&create_module(false)).unwrap();
+        program.type_section.as_mut().unwrap().rec_groups = vec![RecGroup {
+            type_count: Some(4),
+            explicit: Some(true),
+        }];
+        let program = eliminate_dead_code(program).unwrap();
+        validate(&program);
+        assert_eq!(program.type_section.as_ref().unwrap().types.len(), 4);
+        assert_eq!(
+            program.function_section.as_ref().unwrap().type_idxs,
+            vec![1, 0, 1, 0]
+        );
+    }
+}
@@ -0,0 +1,966 @@
+use std::fmt;
+
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+use crate::remap::{IndexSpace, imported_count};
+
+/// A feature whose behavior may differ between engines or runs.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Violation {
+    RelaxedSimd(OpCode),
+    Threads(OpCode),
+    /// A float operator whose NaN results have an unspecified bit pattern.
+    NanProducingFloat(OpCode),
+    UnboundedMemoryGrow {
+        memory: u32,
+    },
+    SharedMemory {
+        memory: u32,
+    },
+    SharedTable {
+        table: u32,
+    },
+    SharedGlobal {
+        global: u32,
+    },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct ProfileViolation {
+    /// Index in the function index space, `None` for violations in the module's declarations.
+    pub function_index: Option<u32>,
+    /// Index of the operator in the function body.
+    pub operator_index: Option<u32>,
+    pub violation: Violation,
+}
+
+impl fmt::Display for Violation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Violation::RelaxedSimd(opcode) => write!(f, "relaxed SIMD operator {:?}", opcode),
+            Violation::Threads(opcode) => write!(f, "threads operator {:?}", opcode),
+            Violation::NanProducingFloat(opcode) => {
+                write!(f, "NaN-producing float operator {:?}", opcode)
+            }
+            Violation::UnboundedMemoryGrow { memory } => {
+                write!(f, "memory.grow on memory {} without a maximum", memory)
+            }
+            Violation::SharedMemory { memory } => write!(f, "shared memory {}", memory),
+            Violation::SharedTable { table } => write!(f, "shared table {}", table),
+            Violation::SharedGlobal { global } => write!(f, "shared global {}", global),
+        }
+    }
+}
+
+impl fmt::Display for ProfileViolation {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        if let Some(function_index) = self.function_index {
+            write!(f, "function {}", function_index)?;
+            if let Some(operator_index) = self.operator_index {
+                write!(f, ", operator {}", operator_index)?;
+            }
+            write!(f, ": ")?;
+        }
+        write!(f, "{}", self.violation)
+    }
+}
+
+fn opcode_violation(opcode: OpCode) -> Option<Violation> {
+    match opcode {
+        OpCode::RelaxedSimdExtI8x16RelaxedSwizzle
+        | OpCode::RelaxedSimdExtI32x4RelaxedTruncF32x4Signed
+        | OpCode::RelaxedSimdExtI32x4RelaxedTruncF32x4Unsigned
+        | OpCode::RelaxedSimdExtI32x4RelaxedTruncF64x2SignedZero
+        | OpCode::RelaxedSimdExtI32x4RelaxedTruncF64x2UnsignedZero
+        | OpCode::RelaxedSimdExtF32x4RelaxedMadd
+        | OpCode::RelaxedSimdExtF32x4RelaxedNmadd
+        | OpCode::RelaxedSimdExtF64x2RelaxedMadd
+        | OpCode::RelaxedSimdExtF64x2RelaxedNmadd
+        | OpCode::RelaxedSimdExtI8x16RelaxedLaneselect
+        | OpCode::RelaxedSimdExtI16x8RelaxedLaneselect
+        | OpCode::RelaxedSimdExtI32x4RelaxedLaneselect
+        | OpCode::RelaxedSimdExtI64x2RelaxedLaneselect
+        | OpCode::RelaxedSimdExtF32x4RelaxedMin
+        | OpCode::RelaxedSimdExtF32x4RelaxedMax
+        | OpCode::RelaxedSimdExtF64x2RelaxedMin
+        | OpCode::RelaxedSimdExtF64x2RelaxedMax
+        | OpCode::RelaxedSimdExtI16x8RelaxedQ15mulrSigned
+        | OpCode::RelaxedSimdExtI16x8RelaxedDotI8x16I7x16Signed
+        | OpCode::RelaxedSimdExtI32x4RelaxedDotI8x16I7x16AddSigned => {
+            Some(Violation::RelaxedSimd(opcode))
+        }
+        OpCode::ThreadsExtMemoryAtomicNotify
+        | OpCode::ThreadsExtMemoryAtomicWait32
+        | OpCode::ThreadsExtMemoryAtomicWait64
+        | OpCode::ThreadsExtAtomicFence
+        | OpCode::ThreadsExtI32AtomicLoad
+        | OpCode::ThreadsExtI64AtomicLoad
+        | OpCode::ThreadsExtI32AtomicLoad8Unsigned
+        | OpCode::ThreadsExtI32AtomicLoad16Unsigned
+        | OpCode::ThreadsExtI64AtomicLoad8Unsigned
+        | OpCode::ThreadsExtI64AtomicLoad16Unsigned
+        | OpCode::ThreadsExtI64AtomicLoad32Unsigned
+        | OpCode::ThreadsExtI32AtomicStore
+        | OpCode::ThreadsExtI64AtomicStore
+        | OpCode::ThreadsExtI32AtomicStore8
+        | OpCode::ThreadsExtI32AtomicStore16
+        | OpCode::ThreadsExtI64AtomicStore8
+        | OpCode::ThreadsExtI64AtomicStore16
+        | OpCode::ThreadsExtI64AtomicStore32
+        | OpCode::ThreadsExtI32AtomicRmwAdd
+        | OpCode::ThreadsExtI64AtomicRmwAdd
+        | OpCode::ThreadsExtI32AtomicRmw8AddUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16AddUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8AddUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16AddUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32AddUnsigned
+        | OpCode::ThreadsExtI32AtomicRmwSub
+        | OpCode::ThreadsExtI64AtomicRmwSub
+        | OpCode::ThreadsExtI32AtomicRmw8SubUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16SubUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8SubUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16SubUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32SubUnsigned
+        | OpCode::ThreadsExtI32AtomicRmwAnd
+        | OpCode::ThreadsExtI64AtomicRmwAnd
+        | OpCode::ThreadsExtI32AtomicRmw8AndUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16AndUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8AndUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16AndUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32AndUnsigned
+        | OpCode::ThreadsExtI32AtomicRmwOr
+        | OpCode::ThreadsExtI64AtomicRmwOr
+        | OpCode::ThreadsExtI32AtomicRmw8OrUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16OrUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8OrUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16OrUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32OrUnsigned
+        | OpCode::ThreadsExtI32AtomicRmwXor
+        | OpCode::ThreadsExtI64AtomicRmwXor
+        | OpCode::ThreadsExtI32AtomicRmw8XorUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16XorUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8XorUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16XorUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32XorUnsigned
+        | OpCode::ThreadsExtI32AtomicRmwXchg
+        | OpCode::ThreadsExtI64AtomicRmwXchg
+        | OpCode::ThreadsExtI32AtomicRmw8XchgUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16XchgUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8XchgUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16XchgUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32XchgUnsigned
+        | OpCode::ThreadsExtI32AtomicRmwCmpxchg
+        | OpCode::ThreadsExtI64AtomicRmwCmpxchg
+        | OpCode::ThreadsExtI32AtomicRmw8CmpxchgUnsigned
+        | OpCode::ThreadsExtI32AtomicRmw16CmpxchgUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw8CmpxchgUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw16CmpxchgUnsigned
+        | OpCode::ThreadsExtI64AtomicRmw32CmpxchgUnsigned => Some(Violation::Threads(opcode)),
+        // NaN results of these have an unspecified bit pattern
+        OpCode::F32Ceil
+        | OpCode::F32Floor
+        | OpCode::F32Trunc
+        | OpCode::F32Nearest
+        | OpCode::F32Sqrt
+        | OpCode::F32Add
+        | OpCode::F32Sub
+        | OpCode::F32Mul
+        | OpCode::F32Div
+        | OpCode::F32Min
+        | OpCode::F32Max
+        | OpCode::F64Ceil
+        | OpCode::F64Floor
+        | OpCode::F64Trunc
+        | OpCode::F64Nearest
+        | OpCode::F64Sqrt
+        | OpCode::F64Add
+        | OpCode::F64Sub
+        | OpCode::F64Mul
+        | OpCode::F64Div
+        | OpCode::F64Min
+        | OpCode::F64Max
+        | OpCode::F32DemoteF64
+        | OpCode::F64PromoteF32
+        | OpCode::SimdExtF32x4Ceil
+        | OpCode::SimdExtF32x4Floor
+        | OpCode::SimdExtF32x4Trunc
+        | OpCode::SimdExtF32x4Nearest
+        | OpCode::SimdExtF32x4Sqrt
+        | OpCode::SimdExtF32x4Add
+        | OpCode::SimdExtF32x4Sub
+        | OpCode::SimdExtF32x4Mul
+        | OpCode::SimdExtF32x4Div
+        | OpCode::SimdExtF32x4Min
+        | OpCode::SimdExtF32x4Max
+        | OpCode::SimdExtF64x2Ceil
+        | OpCode::SimdExtF64x2Floor
+        | OpCode::SimdExtF64x2Trunc
+        | OpCode::SimdExtF64x2Nearest
+        | OpCode::SimdExtF64x2Sqrt
+        | OpCode::SimdExtF64x2Add
+        | OpCode::SimdExtF64x2Sub
+        | OpCode::SimdExtF64x2Mul
+        | OpCode::SimdExtF64x2Div
+        | OpCode::SimdExtF64x2Min
+        | OpCode::SimdExtF64x2Max
+        | OpCode::SimdExtF32x4DemoteF64x2Zero
+        | OpCode::SimdExtF64x2PromoteLowF32x4 => Some(Violation::NanProducingFloat(opcode)),
+        OpCode::I32Constant
+        | OpCode::End
+        | OpCode::Unreachable
+        | OpCode::Nop
+        | OpCode::Block
+        | OpCode::Loop
+        | OpCode::If
+        | OpCode::Else
+        | OpCode::Br
+        | OpCode::BrIf
+        | OpCode::BrTable
+        | OpCode::Return
+        | OpCode::Call
+        | OpCode::CallIndirect
+        | OpCode::Drop
+        | OpCode::Select
+        | OpCode::LocalGet
+        | OpCode::LocalSet
+        | OpCode::LocalTee
+        | OpCode::GlobalGet
+        | OpCode::GlobalSet
+        | OpCode::I32Load
+        | OpCode::I64Load
+        | OpCode::F32Load
+        | OpCode::F64Load
+        | OpCode::I32Load8Signed
+        | OpCode::I32Load8Unsigned
+        | OpCode::I32Load16Signed
+        | OpCode::I32Load16Unsigned
+        | OpCode::I64Load8Signed
+        | OpCode::I64Load8Unsigned
+        | OpCode::I64Load16Signed
+        | OpCode::I64Load16Unsigned
+        | OpCode::I64Load32Signed
+        | OpCode::I64Load32Unsigned
+        | OpCode::I32Store
+        | OpCode::I64Store
+        | OpCode::F32Store
+        | OpCode::F64Store
+        | OpCode::I32Store8
+        | OpCode::I32Store16
+        | OpCode::I64Store8
+        | OpCode::I64Store16
+        | OpCode::I64Store32
+        | OpCode::MemorySize
+        | OpCode::MemoryGrow
+        | OpCode::I64Constant
+        | OpCode::F32Constant
+        | OpCode::F64Constant
+        | OpCode::I32Eqz
+        | OpCode::I32Eq
+        | OpCode::I32Ne
+        | OpCode::I32LtSigned
+        | OpCode::I32LtUnsigned
+        | OpCode::I32GtSigned
+        | OpCode::I32GtUnsigned
+        | OpCode::I32LeSigned
+        | OpCode::I32LeUnsigned
+        | OpCode::I32GeSigned
+        | OpCode::I32GeUnsigned
+        | OpCode::I64Eqz
+        | OpCode::I64Eq
+        | OpCode::I64Ne
+        | OpCode::I64LtSigned
+        | OpCode::I64LtUnsigned
+        | OpCode::I64GtSigned
+        | OpCode::I64GtUnsigned
+        | OpCode::I64LeSigned
+        | OpCode::I64LeUnsigned
+        | OpCode::I64GeSigned
+        | OpCode::I64GeUnsigned
+        | OpCode::F32Eq
+        | OpCode::F32Ne
+        | OpCode::F32Lt
+        | OpCode::F32Gt
+        | OpCode::F32Le
+        | OpCode::F32Ge
+        | OpCode::F64Eq
+        | OpCode::F64Ne
+        | OpCode::F64Lt
+        | OpCode::F64Gt
+        | OpCode::F64Le
+        | OpCode::F64Ge
+        | OpCode::I32Clz
+        | OpCode::I32Ctz
+        | OpCode::I32Popcnt
+        | OpCode::I32Add
+        | OpCode::I32Sub
+        | OpCode::I32Mul
+        | OpCode::I32DivSigned
+        | OpCode::I32DivUnsigned
+        | OpCode::I32RemSigned
+        | OpCode::I32RemUnsigned
+        | OpCode::I32And
+        | OpCode::I32Or
+        | OpCode::I32Xor
+        | OpCode::I32Shl
+        | OpCode::I32ShrSigned
+        | OpCode::I32ShrUnsigned
+        | OpCode::I32Rotl
+        | OpCode::I32Rotr
+        | OpCode::I64Clz
+        | OpCode::I64Ctz
+        | OpCode::I64Popcnt
+        | OpCode::I64Add
+        | OpCode::I64Sub
+        | OpCode::I64Mul
+        | OpCode::I64DivSigned
+        | OpCode::I64DivUnsigned
+        | OpCode::I64RemSigned
+        | OpCode::I64RemUnsigned
+        | OpCode::I64And
+        | OpCode::I64Or
+        | OpCode::I64Xor
+        | OpCode::I64Shl
+        | OpCode::I64ShrSigned
+        | OpCode::I64ShrUnsigned
+        | OpCode::I64Rotl
+        | OpCode::I64Rotr
+        | OpCode::F32Abs
+        | OpCode::F32Neg
+        | OpCode::F32Copysign
+        | OpCode::F64Abs
+        | OpCode::F64Neg
+        | OpCode::F64Copysign
+        | OpCode::I32WrapI64
+        | OpCode::I32TruncF32Signed
+        | OpCode::I32TruncF32Unsigned
+        | OpCode::I32TruncF64Signed
+        | OpCode::I32TruncF64Unsigned
+        | OpCode::I64ExtendI32Signed
+        | OpCode::I64ExtendI32Unsigned
+        | OpCode::I64TruncF32Signed
+        | OpCode::I64TruncF32Unsigned
+        | OpCode::I64TruncF64Signed
+        | OpCode::I64TruncF64Unsigned
+        | OpCode::F32ConvertI32Signed
+        | OpCode::F32ConvertI32Unsigned
+        | OpCode::F32ConvertI64Signed
+        | OpCode::F32ConvertI64Unsigned
+        | OpCode::F64ConvertI32Signed
+        | OpCode::F64ConvertI32Unsigned
+        | OpCode::F64ConvertI64Signed
+        | OpCode::F64ConvertI64Unsigned
+        | OpCode::I32ReinterpretF32
+        | OpCode::I64ReinterpretF64
+        | OpCode::F32ReinterpretI32
+        | OpCode::F64ReinterpretI64
+        | OpCode::SignExtI32Extend8Signed
+        | OpCode::SignExtI32Extend16Signed
+        | OpCode::SignExtI64Extend8Signed
+        | OpCode::SignExtI64Extend16Signed
+        | OpCode::SignExtI64Extend32Signed
+        | OpCode::SaturatingFloatToIntExtI32TruncSatF32Signed
+        | OpCode::SaturatingFloatToIntExtI32TruncSatF32Unsigned
+        | OpCode::SaturatingFloatToIntExtI32TruncSatF64Signed
+        | OpCode::SaturatingFloatToIntExtI32TruncSatF64Unsigned
+        | OpCode::SaturatingFloatToIntExtI64TruncSatF32Signed
+        | OpCode::SaturatingFloatToIntExtI64TruncSatF32Unsigned
+        | OpCode::SaturatingFloatToIntExtI64TruncSatF64Signed
+        | OpCode::SaturatingFloatToIntExtI64TruncSatF64Unsigned
+        | OpCode::BulkMemoryExtMemoryInit
+        | OpCode::BulkMemoryExtDataDrop
+        | OpCode::BulkMemoryExtMemoryCopy
+        | OpCode::BulkMemoryExtMemoryFill
+        | OpCode::BulkMemoryExtTableInit
+        | OpCode::BulkMemoryExtElemDrop
+        | OpCode::BulkMemoryExtTableCopy
+        | OpCode::ExceptionsExtTryTable
+        | OpCode::ExceptionsExtThrow
+        | OpCode::ExceptionsExtThrowRef
+        | OpCode::LegacyExceptionsExtTry
+        | OpCode::LegacyExceptionsExtCatch
+        | OpCode::LegacyExceptionsExtRethrow
+        | OpCode::LegacyExceptionsExtDelegate
+        | OpCode::LegacyExceptionsExtCatchAll
+        | OpCode::SimdExtV128Load
+        | OpCode::SimdExtV128Load8x8Signed
+        | OpCode::SimdExtV128Load8x8Unsigned
+        | OpCode::SimdExtV128Load16x4Signed
+        | OpCode::SimdExtV128Load16x4Unsigned
+        | OpCode::SimdExtV128Load32x2Signed
+        | OpCode::SimdExtV128Load32x2Unsigned
+        | OpCode::SimdExtV128Load8Splat
+        | OpCode::SimdExtV128Load16Splat
+        | OpCode::SimdExtV128Load32Splat
+        | OpCode::SimdExtV128Load64Splat
+        | OpCode::SimdExtV128Load32Zero
+        | OpCode::SimdExtV128Load64Zero
+        | OpCode::SimdExtV128Store
+        | OpCode::SimdExtV128Load8Lane
+        | OpCode::SimdExtV128Load16Lane
+        | OpCode::SimdExtV128Load32Lane
+        | OpCode::SimdExtV128Load64Lane
+        | OpCode::SimdExtV128Store8Lane
+        | OpCode::SimdExtV128Store16Lane
+        | OpCode::SimdExtV128Store32Lane
+        | OpCode::SimdExtV128Store64Lane
+        | OpCode::SimdExtV128Const
+        | OpCode::SimdExtI8x16Shuffle
+        | OpCode::SimdExtI8x16ExtractLaneSigned
+        | OpCode::SimdExtI8x16ExtractLaneUnsigned
+        | OpCode::SimdExtI8x16ReplaceLane
+        | OpCode::SimdExtI16x8ExtractLaneSigned
+        | OpCode::SimdExtI16x8ExtractLaneUnsigned
+        | OpCode::SimdExtI16x8ReplaceLane
+        | OpCode::SimdExtI32x4ExtractLane
+        | OpCode::SimdExtI32x4ReplaceLane
+        | OpCode::SimdExtI64x2ExtractLane
+        | OpCode::SimdExtI64x2ReplaceLane
+        | OpCode::SimdExtF32x4ExtractLane
+        | OpCode::SimdExtF32x4ReplaceLane
+        | OpCode::SimdExtF64x2ExtractLane
+        | OpCode::SimdExtF64x2ReplaceLane
+        | OpCode::SimdExtI8x16Swizzle
+        | OpCode::SimdExtI8x16Splat
+        | OpCode::SimdExtI16x8Splat
+        | OpCode::SimdExtI32x4Splat
+        | OpCode::SimdExtI64x2Splat
+        | OpCode::SimdExtF32x4Splat
+        | OpCode::SimdExtF64x2Splat
+        | OpCode::SimdExtI8x16Eq
+        | OpCode::SimdExtI8x16Ne
+        | OpCode::SimdExtI8x16LtSigned
+        | OpCode::SimdExtI8x16LtUnsigned
+        | OpCode::SimdExtI8x16GtSigned
+        | OpCode::SimdExtI8x16GtUnsigned
+        | OpCode::SimdExtI8x16LeSigned
+        | OpCode::SimdExtI8x16LeUnsigned
+        | OpCode::SimdExtI8x16GeSigned
+        | OpCode::SimdExtI8x16GeUnsigned
+        | OpCode::SimdExtI16x8Eq
+        | OpCode::SimdExtI16x8Ne
+        | OpCode::SimdExtI16x8LtSigned
+        | OpCode::SimdExtI16x8LtUnsigned
+        | OpCode::SimdExtI16x8GtSigned
+        | OpCode::SimdExtI16x8GtUnsigned
+        | OpCode::SimdExtI16x8LeSigned
+        | OpCode::SimdExtI16x8LeUnsigned
+        | OpCode::SimdExtI16x8GeSigned
+        | OpCode::SimdExtI16x8GeUnsigned
+        | OpCode::SimdExtI32x4Eq
+        | OpCode::SimdExtI32x4Ne
+        | OpCode::SimdExtI32x4LtSigned
+        | OpCode::SimdExtI32x4LtUnsigned
+        | OpCode::SimdExtI32x4GtSigned
+        | OpCode::SimdExtI32x4GtUnsigned
+        | OpCode::SimdExtI32x4LeSigned
+        | OpCode::SimdExtI32x4LeUnsigned
+        | OpCode::SimdExtI32x4GeSigned
+        | OpCode::SimdExtI32x4GeUnsigned
+        | OpCode::SimdExtI64x2Eq
+        | OpCode::SimdExtI64x2Ne
+        | OpCode::SimdExtI64x2LtSigned
+        | OpCode::SimdExtI64x2GtSigned
+        | OpCode::SimdExtI64x2LeSigned
+        | OpCode::SimdExtI64x2GeSigned
+        | OpCode::SimdExtF32x4Eq
+        | OpCode::SimdExtF32x4Ne
+        | OpCode::SimdExtF32x4Lt
+        | OpCode::SimdExtF32x4Gt
+        | OpCode::SimdExtF32x4Le
+        | OpCode::SimdExtF32x4Ge
+        | OpCode::SimdExtF64x2Eq
+        | OpCode::SimdExtF64x2Ne
+        | OpCode::SimdExtF64x2Lt
+        | OpCode::SimdExtF64x2Gt
+        | OpCode::SimdExtF64x2Le
+        | OpCode::SimdExtF64x2Ge
+        | OpCode::SimdExtV128Not
+        | OpCode::SimdExtV128And
+        | OpCode::SimdExtV128AndNot
+        | OpCode::SimdExtV128Or
+        | OpCode::SimdExtV128Xor
+        | OpCode::SimdExtV128Bitselect
+        | OpCode::SimdExtV128AnyTrue
+        | OpCode::SimdExtI8x16Abs
+        | OpCode::SimdExtI8x16Neg
+        | OpCode::SimdExtI8x16Popcnt
+        | OpCode::SimdExtI8x16AllTrue
+        | OpCode::SimdExtI8x16Bitmask
+        | OpCode::SimdExtI8x16NarrowI16x8Signed
+        | OpCode::SimdExtI8x16NarrowI16x8Unsigned
+        | OpCode::SimdExtI8x16Shl
+        | OpCode::SimdExtI8x16ShrSigned
+        | OpCode::SimdExtI8x16ShrUnsigned
+        | OpCode::SimdExtI8x16Add
+        | OpCode::SimdExtI8x16AddSatSigned
+        | OpCode::SimdExtI8x16AddSatUnsigned
+        | OpCode::SimdExtI8x16Sub
+        | OpCode::SimdExtI8x16SubSatSigned
+        | OpCode::SimdExtI8x16SubSatUnsigned
+        | OpCode::SimdExtI8x16MinSigned
+        | OpCode::SimdExtI8x16MinUnsigned
+        | OpCode::SimdExtI8x16MaxSigned
+        | OpCode::SimdExtI8x16MaxUnsigned
+        | OpCode::SimdExtI8x16AvgrUnsigned
+        | OpCode::SimdExtI16x8ExtAddPairwiseI8x16Signed
+        | OpCode::SimdExtI16x8ExtAddPairwiseI8x16Unsigned
+        | OpCode::SimdExtI16x8Abs
+        | OpCode::SimdExtI16x8Neg
+        | OpCode::SimdExtI16x8Q15MulrSatSigned
+        | OpCode::SimdExtI16x8AllTrue
+        | OpCode::SimdExtI16x8Bitmask
+        | OpCode::SimdExtI16x8NarrowI32x4Signed
+        | OpCode::SimdExtI16x8NarrowI32x4Unsigned
+        | OpCode::SimdExtI16x8ExtendLowI8x16Signed
+        | OpCode::SimdExtI16x8ExtendHighI8x16Signed
+        | OpCode::SimdExtI16x8ExtendLowI8x16Unsigned
+        | OpCode::SimdExtI16x8ExtendHighI8x16Unsigned
+        | OpCode::SimdExtI16x8Shl
+        | OpCode::SimdExtI16x8ShrSigned
+        | OpCode::SimdExtI16x8ShrUnsigned
+        | OpCode::SimdExtI16x8Add
+        | OpCode::SimdExtI16x8AddSatSigned
+        | OpCode::SimdExtI16x8AddSatUnsigned
+        | OpCode::SimdExtI16x8Sub
+        | OpCode::SimdExtI16x8SubSatSigned
+        | OpCode::SimdExtI16x8SubSatUnsigned
+        | OpCode::SimdExtI16x8Mul
+        | OpCode::SimdExtI16x8MinSigned
+        | OpCode::SimdExtI16x8MinUnsigned
+        | OpCode::SimdExtI16x8MaxSigned
+        | OpCode::SimdExtI16x8MaxUnsigned
+        | OpCode::SimdExtI16x8AvgrUnsigned
+        | OpCode::SimdExtI16x8ExtMulLowI8x16Signed
+        | OpCode::SimdExtI16x8ExtMulHighI8x16Signed
+        | OpCode::SimdExtI16x8ExtMulLowI8x16Unsigned
+        | OpCode::SimdExtI16x8ExtMulHighI8x16Unsigned
+        | OpCode::SimdExtI32x4ExtAddPairwiseI16x8Signed
+        | OpCode::SimdExtI32x4ExtAddPairwiseI16x8Unsigned
+        | OpCode::SimdExtI32x4Abs
+        | OpCode::SimdExtI32x4Neg
+        | OpCode::SimdExtI32x4AllTrue
+        | OpCode::SimdExtI32x4Bitmask
+        | OpCode::SimdExtI32x4ExtendLowI16x8Signed
+        | OpCode::SimdExtI32x4ExtendHighI16x8Signed
+        | OpCode::SimdExtI32x4ExtendLowI16x8Unsigned
+        | OpCode::SimdExtI32x4ExtendHighI16x8Unsigned
+        | OpCode::SimdExtI32x4Shl
+        | OpCode::SimdExtI32x4ShrSigned
+        | OpCode::SimdExtI32x4ShrUnsigned
+        | OpCode::SimdExtI32x4Add
+        | OpCode::SimdExtI32x4Sub
+        | OpCode::SimdExtI32x4Mul
+        | OpCode::SimdExtI32x4MinSigned
+        | OpCode::SimdExtI32x4MinUnsigned
+        | OpCode::SimdExtI32x4MaxSigned
+        | OpCode::SimdExtI32x4MaxUnsigned
+        | OpCode::SimdExtI32x4DotI16x8Signed
+        | OpCode::SimdExtI32x4ExtMulLowI16x8Signed
+        | OpCode::SimdExtI32x4ExtMulHighI16x8Signed
+        | OpCode::SimdExtI32x4ExtMulLowI16x8Unsigned
+        | OpCode::SimdExtI32x4ExtMulHighI16x8Unsigned
+        | OpCode::SimdExtI64x2Abs
+        | OpCode::SimdExtI64x2Neg
+        | OpCode::SimdExtI64x2AllTrue
+        | OpCode::SimdExtI64x2Bitmask
+        | OpCode::SimdExtI64x2ExtendLowI32x4Signed
+        | OpCode::SimdExtI64x2ExtendHighI32x4Signed
+        | OpCode::SimdExtI64x2ExtendLowI32x4Unsigned
+        | OpCode::SimdExtI64x2ExtendHighI32x4Unsigned
+        | OpCode::SimdExtI64x2Shl
+        | OpCode::SimdExtI64x2ShrSigned
+        | OpCode::SimdExtI64x2ShrUnsigned
+        | OpCode::SimdExtI64x2Add
+        | OpCode::SimdExtI64x2Sub
+        | OpCode::SimdExtI64x2Mul
+        | OpCode::SimdExtI64x2ExtMulLowI32x4Signed
+        | OpCode::SimdExtI64x2ExtMulHighI32x4Signed
+        | OpCode::SimdExtI64x2ExtMulLowI32x4Unsigned
+        | OpCode::SimdExtI64x2ExtMulHighI32x4Unsigned
+        | OpCode::SimdExtF32x4Abs
+        | OpCode::SimdExtF32x4Neg
+        | OpCode::SimdExtF32x4Pmin
+        | OpCode::SimdExtF32x4Pmax
+        | OpCode::SimdExtF64x2Abs
+        | OpCode::SimdExtF64x2Neg
+        | OpCode::SimdExtF64x2Pmin
+        | OpCode::SimdExtF64x2Pmax
+        | OpCode::SimdExtI32x4TruncSatF32x4Signed
+        | OpCode::SimdExtI32x4TruncSatF32x4Unsigned
+        | OpCode::SimdExtF32x4ConvertI32x4Signed
+        | OpCode::SimdExtF32x4ConvertI32x4Unsigned
+        | OpCode::SimdExtI32x4TruncSatF64x2SignedZero
+        | OpCode::SimdExtI32x4TruncSatF64x2UnsignedZero
+        | OpCode::SimdExtF64x2ConvertLowI32x4Signed
+        | OpCode::SimdExtF64x2ConvertLowI32x4Unsigned
+        | OpCode::ReferenceTypesExtTypedSelect
+        | OpCode::ReferenceTypesExtRefNull
+        | OpCode::ReferenceTypesExtRefIsNull
+        | OpCode::ReferenceTypesExtRefFunc
+        | OpCode::ReferenceTypesExtTableFill
+        | OpCode::ReferenceTypesExtTableGet
+        | OpCode::ReferenceTypesExtTableSet
+        | OpCode::ReferenceTypesExtTableGrow
+        | OpCode::ReferenceTypesExtTableSize
+        | OpCode::TailCallExtReturnCall
+        | OpCode::TailCallExtReturnCallIndirect
+        | OpCode::TailCallExtReturnCallRef
+        | OpCode::GcExtRefEq
+        | OpCode::GcExtStructNew
+        | OpCode::GcExtStructNewDefault
+        | OpCode::GcExtStructGet
+        | OpCode::GcExtStructGetSigned
+        | OpCode::GcExtStructGetUnsigned
+        | OpCode::GcExtStructSet
+        | OpCode::GcExtArrayNew
+        | OpCode::GcExtArrayNewDefault
+        | OpCode::GcExtArrayNewFixed
+        | OpCode::GcExtArrayNewData
+        | OpCode::GcExtArrayNewElem
+        | OpCode::GcExtArrayGet
+        | OpCode::GcExtArrayGetSigned
+        | OpCode::GcExtArrayGetUnsigned
+        | OpCode::GcExtArraySet
+        | OpCode::GcExtArrayLen
+        | OpCode::GcExtArrayFill
+        | OpCode::GcExtArrayCopy
+        | OpCode::GcExtArrayInitData
+        | OpCode::GcExtArrayInitElem
+        | OpCode::GcExtRefTestNonNull
+        | OpCode::GcExtRefTestNullable
+        | OpCode::GcExtRefCastNonNull
+        | OpCode::GcExtRefCastNullable
+        | OpCode::GcExtAnyConvertExtern
+        | OpCode::GcExtExternConvertAny
+        | OpCode::GcExtRefI31
+        | OpCode::GcExtI31GetSigned
+        | OpCode::GcExtI31GetUnsigned
+        | OpCode::GcExtBrOnCast
+        | OpCode::GcExtBrOnCastFail
+        | OpCode::FunctionReferencesExtCallRef
+        | OpCode::FunctionReferencesExtRefAsNonNull
+        | OpCode::FunctionReferencesExtBrOnNull
+        | OpCode::FunctionReferencesExtBrOnNonNull => None,
+    }
+}
+
+fn declaration_violation(violation: Violation) -> ProfileViolation {
+    ProfileViolation {
+        function_index: None,
+        operator_index: None,
+        violation,
+    }
+}
+
+/// Checks a module against the deterministic-execution profile, reporting every violation in
+/// section order.
+pub fn check_determinism(program: &ProgramModule) -> Result<Vec<ProfileViolation>> {
+    let mut violations: Vec<ProfileViolation> = Vec::new();
+    let mut memories: Vec<&MemoryType> = Vec::new();
+    let mut tables: Vec<&TableType> = Vec::new();
+    let mut globals: Vec<&GlobalType> = Vec::new();
+    let imports = program
+        .import_section
+        .iter()
+        .flat_map(|section| &section.imports);
+    for import in imports {
+        match &import.ty {
+            Some(import::Ty::Table(table)) => tables.push(table),
+            Some(import::Ty::Memory(memory)) => memories.push(memory),
+            Some(import::Ty::Global(global)) => globals.push(global),
+            Some(import::Ty::FunctionType(_) | import::Ty::Tag(_)) => {}
+            None => return Err(Error::Missing("Import type")),
+        }
+    }
+    if let Some(section) = &program.table_section {
+        tables.extend(section.types.iter());
+    }
+    if let Some(section) = &program.memory_section {
+        memories.extend(section.memory_types.iter());
+    }
+    if let Some(section) = &program.global_section {
+        for global in &section.globals {
+            globals.push(
+                global
+                    .r#type
+                    .as_ref()
+                    .ok_or(Error::Missing("Global type"))?,
+            );
+        }
+    }
+
+    for (index, memory) in memories.iter().enumerate() {
+        if memory.shared.unwrap_or(false) {
+            violations.push(declaration_violation(Violation::SharedMemory {
+                memory: index as u32,
+            }));
+        }
+    }
+    for (index, table) in tables.iter().enumerate() {
+        if table.shared.unwrap_or(false) {
+            violations.push(declaration_violation(Violation::SharedTable {
+                table: index as u32,
+            }));
+        }
+    }
+    for (index, global) in globals.iter().enumerate() {
+        if global.shared.unwrap_or(false) {
+            violations.push(declaration_violation(Violation::SharedGlobal {
+                global: index as u32,
+            }));
+        }
+    }
+
+    let entries = program
+        .code_section
+        .iter()
+        .flat_map(|section| &section.code_section_entry);
+    let imported_functions = imported_count(program, IndexSpace::Function);
+    for (function_index, entry) in (imported_functions..).zip(entries) {
+        for (operator_index, operator) in entry.body.iter().enumerate() {
+            let opcode = OpCode::try_from(operator.opcode.ok_or(Error::Missing("Opcode"))?)?;
+            let violation = if opcode == OpCode::MemoryGrow {
+                let Some(operator::Operator::Mem(memory)) = operator.operator else {
+                    return Err(Error::UnexpectedOperand {
+                        expected: "Mem",
+                        operator: "MemoryGrow",
+                    });
+                };
+                let memory_type = memories
+                    .get(memory as usize)
+                    .ok_or_else(|| Error::Invalid(format!("Memory {} is out of bounds", memory)))?;
+                memory_type
+                    .maximum
+                    .is_none()
+                    .then_some(Violation::UnboundedMemoryGrow { memory })
+            } else {
+                opcode_violation(opcode)
+            };
+            if let Some(violation) = violation {
+                violations.push(ProfileViolation {
+                    function_index: Some(function_index),
+                    operator_index: Some(operator_index as u32),
+                    violation,
+                });
+            }
+        }
+    }
+    Ok(violations)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_encoder::{
+        CodeSection, EntityType, Function, FunctionSection, ImportSection, Instruction, MemArg,
+        MemorySection, Module, TypeSection,
+    };
+
+    fn memory(shared: bool, maximum: Option<u64>) -> wasm_encoder::MemoryType {
+        wasm_encoder::MemoryType {
+            minimum: 1,
+            maximum,
+            memory64: false,
+            shared,
+            page_size_log2: None,
+        }
+    }
+
+    fn create_module(memories: &[wasm_encoder::MemoryType], bodies: &[&[Instruction]]) -> Vec<u8> {
+        let mut module = Module::new();
+        let mut types = TypeSection::new();
+        types.ty().function([], []);
+        module.section(&types);
+        let mut imports = ImportSection::new();
+        imports.import("env", "f", EntityType::Function(0));
+        module.section(&imports);
+        let mut functions = FunctionSection::new();
+        for _ in bodies {
+            functions.function(0);
+        }
+        module.section(&functions);
+        let mut memory_section = MemorySection::new();
+        for memory_type in memories {
+            memory_section.memory(*memory_type);
+        }
+        module.section(&memory_section);
+        let mut code = CodeSection::new();
+        for body in bodies {
+            let mut function = Function::new(vec![]);
+            for instruction in *body {
+                function.instruction(instruction);
+            }
+            function.instruction(&Instruction::End);
+            code.function(&function);
+        }
+        module.section(&code);
+        module.finish()
+    }
+
+    fn check(bytes: &[u8]) -> Vec<ProfileViolation> {
+        check_determinism(&crate::from_wasm(bytes).unwrap()).unwrap()
+    }
+
+    #[test]
+    fn test_deterministic_module() {
+        let bytes = create_module(
+            &[memory(false, Some(2))],
+            &[&[
+                Instruction::I32Const(1),
+                Instruction::MemoryGrow(0),
+                Instruction::Drop,
+                Instruction::F32Const(1.0.into()),
+                Instruction::F32Abs,
+                Instruction::F32Neg,
+                Instruction::Drop,
+            ]],
+        );
+        assert_eq!(check(&bytes), vec![]);
+    }
+
+    #[test]
+    fn test_reports_every_violation() {
+        let memarg = MemArg {
+            offset: 0,
+            align: 2,
+            memory_index: 0,
+        };
+        let bytes = create_module(
+            &[memory(true, Some(1)), memory(false, None)],
+            &[
+                &[
+                    Instruction::F32Const(1.0.into()),
+                    Instruction::F32Const(2.0.into()),
+                    Instruction::F32Add,
+                    Instruction::Drop,
+                ],
+                &[
+                    Instruction::I32Const(0),
+                    Instruction::I32Const(1),
+                    Instruction::I32AtomicRmwAdd(memarg),
+                    Instruction::Drop,
+                    Instruction::I32Const(1),
+                    Instruction::MemoryGrow(1),
+                    Instruction::Drop,
+                    Instruction::I32Const(1),
+                    Instruction::MemoryGrow(0),
+                    Instruction::Drop,
+                    Instruction::V128Const(0),
+                    Instruction::V128Const(0),
+                    Instruction::V128Const(0),
+                    Instruction::F32x4RelaxedMadd,
+                    Instruction::Drop,
+                ],
+            ],
+        );
+        let at = |function_index, operator_index, violation| ProfileViolation {
+            function_index: Some(function_index),
+            operator_index: Some(operator_index),
+            violation,
+        };
+        assert_eq!(
+            check(&bytes),
+            vec![
+                declaration_violation(Violation::SharedMemory { memory: 0 }),
+                at(1, 2, Violation::NanProducingFloat(OpCode::F32Add)),
+                at(2, 2, Violation::Threads(OpCode::ThreadsExtI32AtomicRmwAdd)),
+                at(2, 5, Violation::UnboundedMemoryGrow { memory: 1 }),
+                at(
+                    2,
+                    13,
+                    Violation::RelaxedSimd(OpCode::RelaxedSimdExtF32x4RelaxedMadd)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_simd_float_arithmetic() {
+        let bytes = create_module(
+            &[],
+            &[&[
+                Instruction::V128Const(0),
+                Instruction::F64x2Sqrt,
+                Instruction::F64x2Abs,
+                Instruction::Drop,
+            ]],
+        );
+        assert_eq!(
+            check(&bytes),
+            vec![ProfileViolation {
+                function_index: Some(1),
+                operator_index: Some(1),
+                violation: Violation::NanProducingFloat(OpCode::SimdExtF64x2Sqrt),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_shared_global_and_table() {
+        let program = ProgramModule {
+            table_section: Some(TableSection {
+                types: vec![TableType {
+                    shared: Some(true),
+                    ..Default::default()
+                }],
+            }),
+            global_section: Some(GlobalSection {
+                globals: vec![
+                    Global {
+                        r#type: Some(GlobalType::default()),
+                        init_expr: None,
+                    },
+                    Global {
+                        r#type: Some(GlobalType {
+                            shared: Some(true),
+                            ..Default::default()
+                        }),
+                        init_expr: None,
+                    },
+                ],
+            }),
+            ..Default::default()
+        };
+        assert_eq!(
+            check_determinism(&program).unwrap(),
+            vec![
+                declaration_violation(Violation::SharedTable { table: 0 }),
+                declaration_violation(Violation::SharedGlobal { global: 1 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_memory_grow_out_of_bounds() {
+        let bytes = create_module(
+            &[],
+            &[&[
+                Instruction::I32Const(1),
+                Instruction::MemoryGrow(0),
+                Instruction::Drop,
+            ]],
+        );
+        let program = crate::from_wasm(&bytes).unwrap();
+        assert!(matches!(
+            check_determinism(&program),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_violation_display() {
+        let violation = ProfileViolation {
+            function_index: Some(3),
+            operator_index: Some(7),
+            violation: Violation::UnboundedMemoryGrow { memory: 0 },
+        };
+        assert_eq!(
+            violation.to_string(),
+            "function 3, operator 7: memory.grow on memory 0 without a maximum"
+        );
+        assert_eq!(
+            declaration_violation(Violation::SharedMemory { memory: 1 }).to_string(),
+            "shared memory 1"
+        );
+    }
+}
@@ -0,0 +1,34 @@
+use thiserror::Error;
+
+/// An error converting between WebAssembly binaries and their proto representation.
+#[derive(Debug, Error)]
+pub enum Error {
+    /// The input could not be decoded by `wasmparser`.
+    #[error(transparent)]
+    Parse(#[from] wasmparser::BinaryReaderError),
+
+    /// A proto enum field holds a value that is not defined in the schema.
+    #[error(transparent)]
+    UnknownEnumValue(#[from] prost::UnknownEnumValue),
+
+    /// A required proto field is not set.
+    #[error("{0} not found")]
+    Missing(&'static str),
+
+    /// An operator carries a payload that doesn't match its opcode.
+    #[error("Expected {expected} for {operator} operator")]
+    UnexpectedOperand {
+        expected: &'static str,
+        operator: &'static str,
+    },
+
+    /// The input uses a WebAssembly feature the converter can't represent.
+    #[error("{0}")]
+    Unsupported(String),
+
+    /// The input is structurally invalid.
+    #[error("{0}")]
+    Invalid(String),
+}
+
+pub type Result<T> = std::result::Result<T, Error>;
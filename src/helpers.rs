@@ -1,41 +1,319 @@
-use anyhow::{Ok, Result, anyhow, bail};
-
+use crate::error::{Error, Result};
 use crate::libernet_wasm::*;
 
+impl TryFrom<wasmparser::AbstractHeapType> for RefType {
+    type Error = Error;
+
+    fn try_from(heap_type: wasmparser::AbstractHeapType) -> Result<Self> {
+        use wasmparser::AbstractHeapType;
+        match heap_type {
+            AbstractHeapType::Func => Ok(RefType::RefFunc),
+            AbstractHeapType::Extern => Ok(RefType::ExternRef),
+            AbstractHeapType::Any => Ok(RefType::AnyRef),
+            AbstractHeapType::None => Ok(RefType::NullRef),
+            AbstractHeapType::NoExtern => Ok(RefType::NullExternRef),
+            AbstractHeapType::NoFunc => Ok(RefType::NullFuncRef),
+            AbstractHeapType::Eq => Ok(RefType::EqRef),
+            AbstractHeapType::Struct => Ok(RefType::StructRef),
+            AbstractHeapType::Array => Ok(RefType::ArrayRef),
+            AbstractHeapType::I31 => Ok(RefType::I31Ref),
+            AbstractHeapType::Exn => Ok(RefType::ExnRef),
+            AbstractHeapType::NoExn => Ok(RefType::NullExnRef),
+            AbstractHeapType::Cont | AbstractHeapType::NoCont => Err(Error::Unsupported(format!(
+                "Heap type {:?} is not supported",
+                heap_type
+            ))),
+        }
+    }
+}
+
+impl From<RefType> for wasm_encoder::AbstractHeapType {
+    fn from(ref_type: RefType) -> Self {
+        use wasm_encoder::AbstractHeapType;
+        match ref_type {
+            RefType::RefFunc => AbstractHeapType::Func,
+            RefType::ExternRef => AbstractHeapType::Extern,
+            RefType::AnyRef => AbstractHeapType::Any,
+            RefType::NullRef => AbstractHeapType::None,
+            RefType::NullExternRef => AbstractHeapType::NoExtern,
+            RefType::NullFuncRef => AbstractHeapType::NoFunc,
+            RefType::EqRef => AbstractHeapType::Eq,
+            RefType::StructRef => AbstractHeapType::Struct,
+            RefType::ArrayRef => AbstractHeapType::Array,
+            RefType::I31Ref => AbstractHeapType::I31,
+            RefType::ExnRef => AbstractHeapType::Exn,
+            RefType::NullExnRef => AbstractHeapType::NoExn,
+        }
+    }
+}
+
 impl TryFrom<wasmparser::RefType> for RefType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(ref_type: wasmparser::RefType) -> Result<Self> {
-        use wasmparser::{AbstractHeapType, HeapType};
         match ref_type.heap_type() {
-            HeapType::Abstract {
-                shared: false,
-                ty: AbstractHeapType::Func,
-            } => Ok(RefType::RefFunc),
-            HeapType::Abstract {
-                shared: false,
-                ty: AbstractHeapType::Extern,
-            } => Ok(RefType::ExternRef),
-            _ => {
-                bail!("Only reffunc elements are supported");
+            wasmparser::HeapType::Abstract { shared: false, ty } if ref_type.is_nullable() => {
+                RefType::try_from(ty)
             }
+            _ => Err(Error::Unsupported(format!(
+                "Only nullable abstract reference types are supported, got {:?}",
+                ref_type
+            ))),
         }
     }
 }
 
 impl TryFrom<RefType> for wasm_encoder::RefType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(ref_type: RefType) -> Result<Self> {
-        match ref_type {
-            RefType::RefFunc => Ok(wasm_encoder::RefType::FUNCREF),
-            RefType::ExternRef => Ok(wasm_encoder::RefType::EXTERNREF),
+        Ok(wasm_encoder::RefType {
+            nullable: true,
+            heap_type: wasm_encoder::HeapType::Abstract {
+                shared: false,
+                ty: ref_type.into(),
+            },
+        })
+    }
+}
+
+impl TryFrom<&wasmparser::FuncType> for FuncType {
+    type Error = Error;
+
+    fn try_from(func_type: &wasmparser::FuncType) -> Result<Self> {
+        let mut params: Vec<ValueType> = Vec::new();
+        for param in func_type.params() {
+            params.push(ValueType::try_from(*param)?);
+        }
+        let mut results: Vec<ValueType> = Vec::new();
+        for result in func_type.results() {
+            results.push(ValueType::try_from(*result)?);
+        }
+        Ok(FuncType { params, results })
+    }
+}
+
+impl TryFrom<&FuncType> for wasm_encoder::FuncType {
+    type Error = Error;
+
+    fn try_from(func_type: &FuncType) -> Result<Self> {
+        let mut params: Vec<wasm_encoder::ValType> = Vec::new();
+        for param in &func_type.params {
+            params.push((*param).try_into()?);
+        }
+        let mut results: Vec<wasm_encoder::ValType> = Vec::new();
+        for result in &func_type.results {
+            results.push((*result).try_into()?);
+        }
+        Ok(wasm_encoder::FuncType::new(params, results))
+    }
+}
+
+impl TryFrom<wasmparser::FieldType> for FieldType {
+    type Error = Error;
+
+    fn try_from(field_type: wasmparser::FieldType) -> Result<Self> {
+        use wasmparser::StorageType;
+        let storage_type = match field_type.element_type {
+            StorageType::I8 => field_type::StorageType::PackedType(PackedType::I8 as i32),
+            StorageType::I16 => field_type::StorageType::PackedType(PackedType::I16 as i32),
+            StorageType::Val(value_type) => {
+                field_type::StorageType::ValueType(ValueType::try_from(value_type)?)
+            }
+        };
+        Ok(FieldType {
+            storage_type: Some(storage_type),
+            mutable: Some(field_type.mutable),
+        })
+    }
+}
+
+impl TryFrom<&FieldType> for wasm_encoder::FieldType {
+    type Error = Error;
+
+    fn try_from(field_type: &FieldType) -> Result<Self> {
+        use wasm_encoder::StorageType;
+        let element_type = match field_type
+            .storage_type
+            .ok_or(Error::Missing("Field storage type"))?
+        {
+            field_type::StorageType::PackedType(packed_type) => {
+                match PackedType::try_from(packed_type)? {
+                    PackedType::I8 => StorageType::I8,
+                    PackedType::I16 => StorageType::I16,
+                }
+            }
+            field_type::StorageType::ValueType(value_type) => {
+                StorageType::Val(value_type.try_into()?)
+            }
+        };
+        Ok(wasm_encoder::FieldType {
+            element_type,
+            mutable: field_type.mutable.ok_or(Error::Missing("Field mutable"))?,
+        })
+    }
+}
+
+impl TryFrom<&wasmparser::SubType> for SubType {
+    type Error = Error;
+
+    fn try_from(sub_type: &wasmparser::SubType) -> Result<Self> {
+        use wasmparser::CompositeInnerType;
+        let composite_type = &sub_type.composite_type;
+        if composite_type.shared {
+            return Err(Error::Unsupported(
+                "Shared composite types are not supported".to_string(),
+            ));
+        }
+        if composite_type.descriptor_idx.is_some() || composite_type.describes_idx.is_some() {
+            return Err(Error::Unsupported(
+                "Type descriptors are not supported".to_string(),
+            ));
         }
+        let kind = match &composite_type.inner {
+            CompositeInnerType::Func(ft) => sub_type::Kind::Func(ft.try_into()?),
+            CompositeInnerType::Struct(st) => {
+                let mut fields: Vec<FieldType> = Vec::new();
+                for field in st.fields.iter() {
+                    fields.push(FieldType::try_from(*field)?);
+                }
+                sub_type::Kind::StructType(StructType { fields })
+            }
+            CompositeInnerType::Array(at) => sub_type::Kind::ArrayType(ArrayType {
+                element_type: Some(FieldType::try_from(at.0)?),
+            }),
+            inner_type => {
+                return Err(Error::Unsupported(format!(
+                    "Type is not supported {:?}",
+                    inner_type
+                )));
+            }
+        };
+        let supertype_index = match sub_type.supertype_idx {
+            Some(index) => Some(index.as_module_index().ok_or_else(|| {
+                Error::Invalid(format!("Unexpected supertype index {:?}", index))
+            })?),
+            None => None,
+        };
+        Ok(SubType {
+            kind: Some(kind),
+            is_final: Some(sub_type.is_final),
+            supertype_index,
+        })
+    }
+}
+
+impl TryFrom<&SubType> for wasm_encoder::SubType {
+    type Error = Error;
+
+    fn try_from(sub_type: &SubType) -> Result<Self> {
+        use wasm_encoder::CompositeInnerType;
+        let inner = match sub_type
+            .kind
+            .as_ref()
+            .ok_or(Error::Missing("Sub type kind"))?
+        {
+            sub_type::Kind::Func(ft) => CompositeInnerType::Func(ft.try_into()?),
+            sub_type::Kind::StructType(st) => {
+                let mut fields: Vec<wasm_encoder::FieldType> = Vec::new();
+                for field in &st.fields {
+                    fields.push(field.try_into()?);
+                }
+                CompositeInnerType::Struct(wasm_encoder::StructType {
+                    fields: fields.into(),
+                })
+            }
+            sub_type::Kind::ArrayType(at) => CompositeInnerType::Array(wasm_encoder::ArrayType(
+                at.element_type
+                    .as_ref()
+                    .ok_or(Error::Missing("Array element type"))?
+                    .try_into()?,
+            )),
+        };
+        Ok(wasm_encoder::SubType {
+            // types written before GC support carry neither flag and are final
+            is_final: sub_type.is_final.unwrap_or(true),
+            supertype_idx: sub_type.supertype_index,
+            composite_type: wasm_encoder::CompositeType {
+                inner,
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        })
+    }
+}
+
+impl TryFrom<wasmparser::HeapType> for HeapType {
+    type Error = Error;
+
+    fn try_from(heap_type: wasmparser::HeapType) -> Result<Self> {
+        match heap_type {
+            wasmparser::HeapType::Abstract { shared, ty } => Ok(HeapType {
+                heap_type: Some(heap_type::HeapType::AbstractType(
+                    RefType::try_from(ty)? as i32
+                )),
+                shared: Some(shared),
+            }),
+            wasmparser::HeapType::Concrete(index) => Ok(HeapType {
+                heap_type: Some(heap_type::HeapType::ConcreteType(
+                    index.as_module_index().ok_or_else(|| {
+                        Error::Invalid(format!("Unexpected heap type index {:?}", index))
+                    })?,
+                )),
+                shared: None,
+            }),
+            wasmparser::HeapType::Exact(_) => Err(Error::Unsupported(format!(
+                "Unsupported heap type: {:?}",
+                heap_type
+            ))),
+        }
+    }
+}
+
+impl TryFrom<HeapType> for wasm_encoder::HeapType {
+    type Error = Error;
+
+    fn try_from(heap_type: HeapType) -> Result<Self> {
+        match heap_type.heap_type.ok_or(Error::Missing("Heap type"))? {
+            heap_type::HeapType::AbstractType(ref_type) => Ok(wasm_encoder::HeapType::Abstract {
+                shared: heap_type.shared.unwrap_or(false),
+                ty: RefType::try_from(ref_type)?.into(),
+            }),
+            heap_type::HeapType::ConcreteType(index) => Ok(wasm_encoder::HeapType::Concrete(index)),
+        }
+    }
+}
+
+impl TryFrom<wasmparser::RefType> for ReferenceType {
+    type Error = Error;
+
+    fn try_from(ref_type: wasmparser::RefType) -> Result<Self> {
+        Ok(ReferenceType {
+            nullable: Some(ref_type.is_nullable()),
+            heap_type: Some(HeapType::try_from(ref_type.heap_type())?),
+        })
+    }
+}
+
+impl TryFrom<ReferenceType> for wasm_encoder::RefType {
+    type Error = Error;
+
+    fn try_from(ref_type: ReferenceType) -> Result<Self> {
+        Ok(wasm_encoder::RefType {
+            nullable: ref_type
+                .nullable
+                .ok_or(Error::Missing("Reference nullable"))?,
+            heap_type: ref_type
+                .heap_type
+                .ok_or(Error::Missing("Reference heap type"))?
+                .try_into()?,
+        })
     }
 }
 
 impl TryFrom<wasmparser::ValType> for ValueType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(value_type: wasmparser::ValType) -> Result<Self> {
         use wasmparser::ValType;
@@ -43,38 +321,57 @@ impl TryFrom<wasmparser::ValType> for ValueType {
             ValType::I32 => Ok(ValueType {
                 value_type: Some(PlainType::ValueTypeI32 as i32),
                 reference_type: None,
+                reference: None,
             }),
             ValType::I64 => Ok(ValueType {
                 value_type: Some(PlainType::ValueTypeI64 as i32),
                 reference_type: None,
+                reference: None,
             }),
             ValType::F32 => Ok(ValueType {
                 value_type: Some(PlainType::ValueTypeF32 as i32),
                 reference_type: None,
+                reference: None,
             }),
             ValType::F64 => Ok(ValueType {
                 value_type: Some(PlainType::ValueTypeF64 as i32),
                 reference_type: None,
+                reference: None,
             }),
             ValType::V128 => Ok(ValueType {
                 value_type: Some(PlainType::ValueTypeV128 as i32),
                 reference_type: None,
+                reference: None,
             }),
             ValType::Ref(ref_type) => Ok(ValueType {
                 value_type: Some(PlainType::ValueTypeRef as i32),
-                reference_type: Some(RefType::try_from(ref_type)? as i32),
+                reference_type: None,
+                reference: Some(ReferenceType::try_from(ref_type)?),
             }),
         }
     }
 }
 
+/// Encodes a reference type, falling back to the nullable abstract `RefType` that protos written
+/// before `ReferenceType` carry.
+pub(crate) fn encode_reference(
+    reference: Option<ReferenceType>,
+    reference_type: Option<i32>,
+) -> Result<wasm_encoder::RefType> {
+    match (reference, reference_type) {
+        (Some(reference), _) => reference.try_into(),
+        (None, Some(reference_type)) => RefType::try_from(reference_type)?.try_into(),
+        (None, None) => Err(Error::Missing("Ref type")),
+    }
+}
+
 impl TryFrom<ValueType> for wasm_encoder::ValType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(value_type: ValueType) -> Result<Self> {
         let ty: PlainType = value_type
             .value_type
-            .ok_or(anyhow!("Value type not found"))?
+            .ok_or(Error::Missing("Value type"))?
             .try_into()?;
         match ty {
             PlainType::ValueTypeI32 => Ok(wasm_encoder::ValType::I32),
@@ -82,20 +379,161 @@ impl TryFrom<ValueType> for wasm_encoder::ValType {
             PlainType::ValueTypeF32 => Ok(wasm_encoder::ValType::F32),
             PlainType::ValueTypeF64 => Ok(wasm_encoder::ValType::F64),
             PlainType::ValueTypeV128 => Ok(wasm_encoder::ValType::V128),
-            PlainType::ValueTypeRef => Ok(wasm_encoder::ValType::Ref(
-                RefType::try_from(
-                    value_type
-                        .reference_type
-                        .ok_or(anyhow!("Ref type not found"))?,
-                )?
+            PlainType::ValueTypeRef => Ok(wasm_encoder::ValType::Ref(encode_reference(
+                value_type.reference,
+                value_type.reference_type,
+            )?)),
+        }
+    }
+}
+
+impl TryFrom<wasmparser::TableType> for TableType {
+    type Error = Error;
+
+    fn try_from(table_type: wasmparser::TableType) -> Result<Self> {
+        Ok(TableType {
+            reference_type: None,
+            table64: Some(table_type.table64),
+            initial: Some(table_type.initial),
+            maximum: table_type.maximum,
+            shared: Some(table_type.shared),
+            init_expr: None,
+            reference: Some(ReferenceType::try_from(table_type.element_type)?),
+        })
+    }
+}
+
+impl TryFrom<&TableType> for wasm_encoder::TableType {
+    type Error = Error;
+
+    fn try_from(table_type: &TableType) -> Result<Self> {
+        Ok(wasm_encoder::TableType {
+            element_type: encode_reference(table_type.reference, table_type.reference_type)?,
+            table64: table_type.table64.ok_or(Error::Missing("Table64"))?,
+            minimum: table_type.initial.ok_or(Error::Missing("Initial"))?,
+            maximum: table_type.maximum,
+            shared: table_type.shared.ok_or(Error::Missing("Shared"))?,
+        })
+    }
+}
+
+impl From<wasmparser::MemoryType> for MemoryType {
+    fn from(memory_type: wasmparser::MemoryType) -> Self {
+        MemoryType {
+            memory64: Some(memory_type.memory64),
+            shared: Some(memory_type.shared),
+            initial: Some(memory_type.initial),
+            maximum: memory_type.maximum,
+            page_size_log2: memory_type.page_size_log2,
+        }
+    }
+}
+
+impl TryFrom<&MemoryType> for wasm_encoder::MemoryType {
+    type Error = Error;
+
+    fn try_from(memory_type: &MemoryType) -> Result<Self> {
+        Ok(wasm_encoder::MemoryType {
+            memory64: memory_type.memory64.ok_or(Error::Missing("Memory64"))?,
+            shared: memory_type.shared.ok_or(Error::Missing("Shared"))?,
+            minimum: memory_type.initial.ok_or(Error::Missing("Initial"))?,
+            maximum: memory_type.maximum,
+            page_size_log2: memory_type.page_size_log2,
+        })
+    }
+}
+
+impl TryFrom<wasmparser::GlobalType> for GlobalType {
+    type Error = Error;
+
+    fn try_from(global_type: wasmparser::GlobalType) -> Result<Self> {
+        Ok(GlobalType {
+            content_type: Some(ValueType::try_from(global_type.content_type)?),
+            mutable: Some(global_type.mutable),
+            shared: Some(global_type.shared),
+        })
+    }
+}
+
+impl TryFrom<&GlobalType> for wasm_encoder::GlobalType {
+    type Error = Error;
+
+    fn try_from(global_type: &GlobalType) -> Result<Self> {
+        Ok(wasm_encoder::GlobalType {
+            val_type: global_type
+                .content_type
+                .ok_or(Error::Missing("Content type"))?
                 .try_into()?,
+            mutable: global_type.mutable.ok_or(Error::Missing("Mutable"))?,
+            shared: global_type.shared.ok_or(Error::Missing("Shared"))?,
+        })
+    }
+}
+
+impl TryFrom<wasmparser::TagType> for TagType {
+    type Error = Error;
+
+    fn try_from(tag_type: wasmparser::TagType) -> Result<Self> {
+        if tag_type.kind != wasmparser::TagKind::Exception {
+            return Err(Error::Unsupported(
+                "Only Exception tags are supported".to_string(),
+            ));
+        }
+        Ok(TagType {
+            kind: Some(TagKind::Exception as i32),
+            function_type_idx: Some(tag_type.func_type_idx),
+        })
+    }
+}
+
+impl TryFrom<&TagType> for wasm_encoder::TagType {
+    type Error = Error;
+
+    fn try_from(tag_type: &TagType) -> Result<Self> {
+        Ok(wasm_encoder::TagType {
+            kind: wasm_encoder::TagKind::Exception,
+            func_type_idx: tag_type
+                .function_type_idx
+                .ok_or(Error::Missing("Func type index"))?,
+        })
+    }
+}
+
+impl TryFrom<wasmparser::TypeRef> for import::Ty {
+    type Error = Error;
+
+    fn try_from(type_ref: wasmparser::TypeRef) -> Result<Self> {
+        use wasmparser::TypeRef;
+        match type_ref {
+            TypeRef::Func(ftype) => Ok(import::Ty::FunctionType(ftype)),
+            TypeRef::Table(table) => Ok(import::Ty::Table(table.try_into()?)),
+            TypeRef::Memory(memory) => Ok(import::Ty::Memory(memory.into())),
+            TypeRef::Global(global) => Ok(import::Ty::Global(global.try_into()?)),
+            TypeRef::Tag(tag) => Ok(import::Ty::Tag(tag.try_into()?)),
+            TypeRef::FuncExact(_) => Err(Error::Unsupported(
+                "ImportSection: exact function imports are not supported".to_string(),
             )),
         }
     }
 }
 
+impl TryFrom<&import::Ty> for wasm_encoder::EntityType {
+    type Error = Error;
+
+    fn try_from(ty: &import::Ty) -> Result<Self> {
+        use wasm_encoder::EntityType;
+        match ty {
+            import::Ty::FunctionType(ftype) => Ok(EntityType::Function(*ftype)),
+            import::Ty::Table(table) => Ok(EntityType::Table(table.try_into()?)),
+            import::Ty::Memory(memory) => Ok(EntityType::Memory(memory.try_into()?)),
+            import::Ty::Global(global) => Ok(EntityType::Global(global.try_into()?)),
+            import::Ty::Tag(tag) => Ok(EntityType::Tag(tag.try_into()?)),
+        }
+    }
+}
+
 impl TryFrom<wasmparser::ConstExpr<'_>> for Expression {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(expr: wasmparser::ConstExpr<'_>) -> Result<Self> {
         let reader = expr.get_operators_reader();
@@ -108,7 +546,7 @@ impl TryFrom<wasmparser::ConstExpr<'_>> for Expression {
 }
 
 impl TryFrom<Expression> for wasm_encoder::ConstExpr {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(expression: Expression) -> Result<Self> {
         use wasm_encoder::ConstExpr;
@@ -127,7 +565,7 @@ impl TryFrom<Expression> for wasm_encoder::ConstExpr {
 }
 
 impl TryFrom<wasmparser::ExternalKind> for ExternalKind {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(external_kind: wasmparser::ExternalKind) -> Result<Self> {
         match external_kind {
@@ -142,7 +580,7 @@ impl TryFrom<wasmparser::ExternalKind> for ExternalKind {
 }
 
 impl TryFrom<ExternalKind> for wasm_encoder::ExportKind {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(external_kind: ExternalKind) -> Result<wasm_encoder::ExportKind> {
         match external_kind {
@@ -151,13 +589,16 @@ impl TryFrom<ExternalKind> for wasm_encoder::ExportKind {
             ExternalKind::ExtMemory => Ok(wasm_encoder::ExportKind::Memory),
             ExternalKind::ExtGlobal => Ok(wasm_encoder::ExportKind::Global),
             ExternalKind::ExtTag => Ok(wasm_encoder::ExportKind::Tag),
-            _ => Err(anyhow!("ExternalKind is not supported")),
+            _ => Err(Error::Unsupported(format!(
+                "ExternalKind {:?} is not supported",
+                external_kind
+            ))),
         }
     }
 }
 
 impl TryFrom<wasmparser::ElementKind<'_>> for ElementKind {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(element_kind: wasmparser::ElementKind) -> Result<Self> {
         match element_kind {
@@ -184,7 +625,7 @@ impl TryFrom<wasmparser::ElementKind<'_>> for ElementKind {
 }
 
 impl TryFrom<wasmparser::DataKind<'_>> for DataKind {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(element_kind: wasmparser::DataKind) -> Result<Self> {
         match element_kind {
@@ -217,44 +658,142 @@ mod tests {
             ValueType::try_from(ValType::I32).unwrap(),
             ValueType {
                 value_type: Some(1),
-                reference_type: None
+                reference_type: None,
+                reference: None
             }
         ));
         assert!(matches!(
             ValueType::try_from(ValType::I64).unwrap(),
             ValueType {
                 value_type: Some(2),
-                reference_type: None
+                reference_type: None,
+                reference: None
             }
         ));
         assert!(matches!(
             ValueType::try_from(ValType::F32).unwrap(),
             ValueType {
                 value_type: Some(3),
-                reference_type: None
+                reference_type: None,
+                reference: None
             }
         ));
         assert!(matches!(
             ValueType::try_from(ValType::F64).unwrap(),
             ValueType {
                 value_type: Some(4),
-                reference_type: None
+                reference_type: None,
+                reference: None
             }
         ));
         assert!(matches!(
             ValueType::try_from(ValType::V128).unwrap(),
             ValueType {
                 value_type: Some(5),
-                reference_type: None
+                reference_type: None,
+                reference: None
             }
         ));
-        assert!(matches!(
+        assert_eq!(
             ValueType::try_from(ValType::Ref(wasmparser::RefType::FUNCREF)).unwrap(),
             ValueType {
                 value_type: Some(6),
-                reference_type: Some(1)
+                reference_type: None,
+                reference: Some(ReferenceType {
+                    nullable: Some(true),
+                    heap_type: Some(HeapType {
+                        heap_type: Some(heap_type::HeapType::AbstractType(1)),
+                        shared: Some(false),
+                    }),
+                }),
             }
-        ));
+        );
+    }
+
+    #[test]
+    fn test_valtype_concrete_non_null_roundtrip() {
+        let ref_type = wasmparser::RefType::concrete(
+            false,
+            wasmparser::PackedIndex::from_module_index(2).unwrap(),
+        );
+        let value_type = ValueType::try_from(wasmparser::ValType::Ref(ref_type)).unwrap();
+        assert_eq!(
+            value_type.reference,
+            Some(ReferenceType {
+                nullable: Some(false),
+                heap_type: Some(HeapType {
+                    heap_type: Some(heap_type::HeapType::ConcreteType(2)),
+                    shared: None,
+                }),
+            })
+        );
+        assert_eq!(
+            wasm_encoder::ValType::try_from(value_type).unwrap(),
+            wasm_encoder::ValType::Ref(wasm_encoder::RefType {
+                nullable: false,
+                heap_type: wasm_encoder::HeapType::Concrete(2),
+            })
+        );
+    }
+
+    #[test]
+    fn test_valtype_abstract_non_null_roundtrip() {
+        let value_type = ValueType::try_from(wasmparser::ValType::Ref(
+            wasmparser::RefType::FUNCREF.as_non_null(),
+        ))
+        .unwrap();
+        assert_eq!(
+            value_type
+                .reference
+                .and_then(|reference| reference.nullable),
+            Some(false)
+        );
+        assert_eq!(
+            wasm_encoder::ValType::try_from(value_type).unwrap(),
+            wasm_encoder::ValType::Ref(wasm_encoder::RefType {
+                nullable: false,
+                heap_type: wasm_encoder::HeapType::FUNC,
+            })
+        );
+    }
+
+    #[test]
+    fn test_valtype_shared_abstract_roundtrip() {
+        let ref_type = wasmparser::RefType::new(
+            true,
+            wasmparser::HeapType::Abstract {
+                shared: true,
+                ty: wasmparser::AbstractHeapType::Any,
+            },
+        )
+        .unwrap();
+        let value_type = ValueType::try_from(wasmparser::ValType::Ref(ref_type)).unwrap();
+        assert_eq!(
+            wasm_encoder::ValType::try_from(value_type).unwrap(),
+            wasm_encoder::ValType::Ref(wasm_encoder::RefType {
+                nullable: true,
+                heap_type: wasm_encoder::HeapType::Abstract {
+                    shared: true,
+                    ty: wasm_encoder::AbstractHeapType::Any,
+                },
+            })
+        );
+    }
+
+    #[test]
+    fn test_valtype_to_wasm_encoder_missing_nullable() {
+        let result = wasm_encoder::ValType::try_from(ValueType {
+            value_type: Some(PlainType::ValueTypeRef as i32),
+            reference_type: None,
+            reference: Some(ReferenceType {
+                nullable: None,
+                heap_type: Some(HeapType {
+                    heap_type: Some(heap_type::HeapType::ConcreteType(0)),
+                    shared: None,
+                }),
+            }),
+        });
+        assert!(matches!(result, Err(Error::Missing("Reference nullable"))));
     }
 
     #[test]
@@ -262,7 +801,8 @@ mod tests {
         assert!(matches!(
             wasm_encoder::ValType::try_from(ValueType {
                 value_type: Some(PlainType::ValueTypeI32 as i32),
-                reference_type: None
+                reference_type: None,
+                reference: None
             })
             .unwrap(),
             wasm_encoder::ValType::I32
@@ -270,7 +810,8 @@ mod tests {
         assert!(matches!(
             wasm_encoder::ValType::try_from(ValueType {
                 value_type: Some(PlainType::ValueTypeI64 as i32),
-                reference_type: None
+                reference_type: None,
+                reference: None
             })
             .unwrap(),
             wasm_encoder::ValType::I64
@@ -278,7 +819,8 @@ mod tests {
         assert!(matches!(
             wasm_encoder::ValType::try_from(ValueType {
                 value_type: Some(PlainType::ValueTypeF32 as i32),
-                reference_type: None
+                reference_type: None,
+                reference: None
             })
             .unwrap(),
             wasm_encoder::ValType::F32
@@ -286,7 +828,8 @@ mod tests {
         assert!(matches!(
             wasm_encoder::ValType::try_from(ValueType {
                 value_type: Some(PlainType::ValueTypeF64 as i32),
-                reference_type: None
+                reference_type: None,
+                reference: None
             })
             .unwrap(),
             wasm_encoder::ValType::F64
@@ -294,7 +837,8 @@ mod tests {
         assert!(matches!(
             wasm_encoder::ValType::try_from(ValueType {
                 value_type: Some(PlainType::ValueTypeV128 as i32),
-                reference_type: None
+                reference_type: None,
+                reference: None
             })
             .unwrap(),
             wasm_encoder::ValType::V128
@@ -302,7 +846,8 @@ mod tests {
         assert!(matches!(
             wasm_encoder::ValType::try_from(ValueType {
                 value_type: Some(PlainType::ValueTypeRef as i32),
-                reference_type: Some(RefType::RefFunc as i32)
+                reference_type: Some(RefType::RefFunc as i32),
+                reference: None
             })
             .unwrap(),
             wasm_encoder::ValType::Ref(wasm_encoder::RefType::FUNCREF)
@@ -386,6 +931,88 @@ mod tests {
         assert_eq!(result, wasm_encoder::RefType::EXTERNREF);
     }
 
+    #[test]
+    fn test_heap_type_roundtrip() {
+        for ref_type in [wasmparser::RefType::FUNCREF, wasmparser::RefType::EXTERNREF] {
+            let heap_type = HeapType::try_from(ref_type.heap_type()).unwrap();
+            let back = wasm_encoder::HeapType::try_from(heap_type).unwrap();
+            assert_eq!(
+                wasm_encoder::RefType {
+                    nullable: true,
+                    heap_type: back,
+                },
+                wasm_encoder::RefType::try_from(RefType::try_from(ref_type).unwrap()).unwrap()
+            );
+        }
+    }
+
+    #[test]
+    fn test_reftype_from_wasmparser_gc_abstract() {
+        assert_eq!(
+            RefType::try_from(wasmparser::RefType::I31REF).unwrap(),
+            RefType::I31Ref
+        );
+        assert_eq!(
+            wasm_encoder::RefType::try_from(RefType::NullFuncRef).unwrap(),
+            wasm_encoder::RefType {
+                nullable: true,
+                heap_type: wasm_encoder::HeapType::Abstract {
+                    shared: false,
+                    ty: wasm_encoder::AbstractHeapType::NoFunc,
+                },
+            }
+        );
+    }
+
+    #[test]
+    fn test_reftype_from_wasmparser_non_nullable() {
+        let ref_type = wasmparser::RefType::EQREF.as_non_null();
+        assert!(matches!(
+            RefType::try_from(ref_type),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_heap_type_concrete() {
+        let heap_type = HeapType::try_from(wasmparser::HeapType::Concrete(
+            wasmparser::UnpackedIndex::Module(3),
+        ))
+        .unwrap();
+        assert_eq!(
+            heap_type.heap_type,
+            Some(heap_type::HeapType::ConcreteType(3))
+        );
+        assert_eq!(
+            wasm_encoder::HeapType::try_from(heap_type).unwrap(),
+            wasm_encoder::HeapType::Concrete(3)
+        );
+    }
+
+    #[test]
+    fn test_reference_type_roundtrip() {
+        let ref_type = ReferenceType::try_from(wasmparser::RefType::ANYREF.as_non_null()).unwrap();
+        assert_eq!(ref_type.nullable, Some(false));
+        assert_eq!(
+            wasm_encoder::RefType::try_from(ref_type).unwrap(),
+            wasm_encoder::RefType {
+                nullable: false,
+                heap_type: wasm_encoder::HeapType::ANY,
+            }
+        );
+    }
+
+    #[test]
+    fn test_heap_type_missing() {
+        assert!(matches!(
+            wasm_encoder::HeapType::try_from(HeapType {
+                heap_type: None,
+                shared: None,
+            }),
+            Err(Error::Missing("Heap type"))
+        ));
+    }
+
     #[test]
     fn test_expression_from_wasmparser_constexpr() {
         use wasm_encoder::{ConstExpr, Instruction, Module};
@@ -629,6 +1256,7 @@ mod tests {
         let result = wasm_encoder::ValType::try_from(ValueType {
             value_type: None,
             reference_type: None,
+            reference: None,
         });
         assert!(result.is_err());
     }
@@ -638,6 +1266,7 @@ mod tests {
         let result = wasm_encoder::ValType::try_from(ValueType {
             value_type: Some(PlainType::ValueTypeRef as i32),
             reference_type: None,
+            reference: None,
         });
         assert!(result.is_err());
     }
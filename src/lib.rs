@@ -0,0 +1,32 @@
+pub mod libernet_wasm {
+    include!(concat!(env!("OUT_DIR"), "/libernet.wasm.rs"));
+}
+
+pub mod component_modules;
+mod components;
+pub mod dead_code;
+pub mod determinism;
+mod error;
+mod helpers;
+pub mod metering;
+mod names;
+mod operators;
+pub mod program_component;
+pub mod program_module;
+pub mod remap;
+mod sections;
+pub mod stack_limit;
+#[cfg(test)]
+mod test_support;
+pub mod type_dedup;
+
+pub use component_modules::extract_modules;
+pub use dead_code::eliminate_dead_code;
+pub use determinism::{ProfileViolation, Violation, check_determinism};
+pub use error::{Error, Result};
+pub use metering::{FuelMechanism, MeteringConfig, meter_fuel};
+pub use program_component::{from_wasm_component, render_wasm_component};
+pub use program_module::{from_wasm, render_wasm};
+pub use remap::{IndexSpace, Remapping};
+pub use stack_limit::limit_stack_height;
+pub use type_dedup::deduplicate_types;
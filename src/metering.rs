@@ -0,0 +1,524 @@
+use std::collections::HashMap;
+
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+use crate::operators::{simple, with_operand};
+use crate::remap::{IndexSpace, Remapping, append_global, imported_count};
+
+/// How metered code pays for the fuel it uses.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum FuelMechanism {
+    /// Calls an injected `(func (param i64))` import with the cost of each block.
+    Import { module: String, name: String },
+    /// Subtracts the cost of each block from an injected mutable i64 global, exported under
+    /// `export_name` so the host can refill it, trapping when the remaining fuel doesn't cover it.
+    /// The global starts with `initial_fuel`, which pays for the start function.
+    Global {
+        export_name: String,
+        initial_fuel: u64,
+    },
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MeteringConfig {
+    pub mechanism: FuelMechanism,
+    /// Cost of the operators missing from `costs`.
+    pub default_cost: u64,
+    pub costs: HashMap<OpCode, u64>,
+}
+
+impl Default for MeteringConfig {
+    fn default() -> Self {
+        MeteringConfig {
+            mechanism: FuelMechanism::Import {
+                module: "env".to_string(),
+                name: "charge_fuel".to_string(),
+            },
+            default_cost: 1,
+            costs: HashMap::new(),
+        }
+    }
+}
+
+impl MeteringConfig {
+    fn cost(&self, opcode: OpCode) -> u64 {
+        self.costs
+            .get(&opcode)
+            .copied()
+            .unwrap_or(self.default_cost)
+    }
+}
+
+/// Whether the operator closes a metered block: control may enter or leave right after it.
+fn ends_block(opcode: OpCode) -> bool {
+    matches!(
+        opcode,
+        OpCode::Block
+            | OpCode::Loop
+            | OpCode::If
+            | OpCode::Else
+            | OpCode::End
+            | OpCode::Br
+            | OpCode::BrIf
+            | OpCode::BrTable
+            | OpCode::Return
+            | OpCode::Unreachable
+            | OpCode::ExceptionsExtTryTable
+            | OpCode::ExceptionsExtThrow
+            | OpCode::ExceptionsExtThrowRef
+            | OpCode::LegacyExceptionsExtTry
+            | OpCode::LegacyExceptionsExtCatch
+            | OpCode::LegacyExceptionsExtCatchAll
+            | OpCode::LegacyExceptionsExtDelegate
+            | OpCode::LegacyExceptionsExtRethrow
+            | OpCode::TailCallExtReturnCall
+            | OpCode::TailCallExtReturnCallIndirect
+            | OpCode::TailCallExtReturnCallRef
+            | OpCode::GcExtBrOnCast
+            | OpCode::GcExtBrOnCastFail
+            | OpCode::FunctionReferencesExtBrOnNull
+            | OpCode::FunctionReferencesExtBrOnNonNull
+    )
+}
+
+/// The injected code charging `cost` units of fuel.
+enum Charge {
+    Call(u32),
+    Global(u32),
+}
+
+impl Charge {
+    fn operators(&self, cost: u64) -> Vec<Operator> {
+        // the cost is unsigned, the host and the unsigned comparison read the same bits
+        let cost = operator::Operator::I64Value(cost as i64);
+        match *self {
+            Charge::Call(function_index) => vec![
+                with_operand(OpCode::I64Constant, cost),
+                with_operand(
+                    OpCode::Call,
+                    operator::Operator::FunctionIndex(function_index),
+                ),
+            ],
+            Charge::Global(global_index) => {
+                let global = operator::Operator::GlobalIndex(global_index);
+                vec![
+                    with_operand(OpCode::GlobalGet, global.clone()),
+                    with_operand(OpCode::I64Constant, cost.clone()),
+                    simple(OpCode::I64LtUnsigned),
+                    with_operand(
+                        OpCode::If,
+                        operator::Operator::BlockType(BlockType {
+                            block_type: Some(block_type::BlockType::Empty(0)),
+                        }),
+                    ),
+                    simple(OpCode::Unreachable),
+                    simple(OpCode::End),
+                    with_operand(OpCode::GlobalGet, global.clone()),
+                    with_operand(OpCode::I64Constant, cost),
+                    simple(OpCode::I64Sub),
+                    with_operand(OpCode::GlobalSet, global),
+                ]
+            }
+        }
+    }
+}
+
+/// Splits the body into basic blocks and charges each one's cost on entry.
+fn meter_body(
+    body: Vec<Operator>,
+    config: &MeteringConfig,
+    charge: &Charge,
+) -> Result<Vec<Operator>> {
+    let mut metered: Vec<Operator> = Vec::with_capacity(body.len());
+    let mut block: Vec<Operator> = Vec::new();
+    let mut cost: u64 = 0;
+    for operator in body {
+        let opcode = OpCode::try_from(operator.opcode.ok_or(Error::Missing("Opcode"))?)?;
+        cost = cost.saturating_add(config.cost(opcode));
+        block.push(operator);
+        if ends_block(opcode) {
+            if cost > 0 {
+                metered.extend(charge.operators(cost));
+            }
+            metered.append(&mut block);
+            cost = 0;
+        }
+    }
+    if cost > 0 {
+        metered.extend(charge.operators(cost));
+    }
+    metered.append(&mut block);
+    Ok(metered)
+}
+
+fn charge_function_type() -> SubType {
+    SubType {
+        kind: Some(sub_type::Kind::Func(FuncType {
+            params: vec![ValueType {
+                value_type: Some(PlainType::ValueTypeI64 as i32),
+                ..Default::default()
+            }],
+            results: vec![],
+        })),
+        is_final: Some(true),
+        supertype_index: None,
+    }
+}
+
+/// Injects the charge function import, shifting every defined function up by one.
+fn inject_import(program: &mut ProgramModule, module: &str, name: &str) -> Charge {
+    let type_section = program.type_section.get_or_insert_with(Default::default);
+    let type_index = type_section.types.len() as u32;
+    type_section.types.push(charge_function_type());
+    if !type_section.rec_groups.is_empty() {
+        type_section.rec_groups.push(RecGroup {
+            type_count: Some(1),
+            explicit: Some(false),
+        });
+    }
+
+    let function_index = imported_count(program, IndexSpace::Function);
+    let import_section = program.import_section.get_or_insert_with(Default::default);
+    import_section.imports.push(Import {
+        module: Some(module.to_string()),
+        name: Some(name.to_string()),
+        ty: Some(import::Ty::FunctionType(type_index)),
+    });
+
+    Remapping::new()
+        .with(IndexSpace::Function, |index| {
+            if index >= function_index {
+                index + 1
+            } else {
+                index
+            }
+        })
+        .apply(program);
+    Charge::Call(function_index)
+}
+
+/// Injects the fuel global and exports it.
+fn inject_global(program: &mut ProgramModule, export_name: &str, initial_fuel: u64) -> Charge {
+    let fuel_type = GlobalType {
+        content_type: Some(ValueType {
+            value_type: Some(PlainType::ValueTypeI64 as i32),
+            ..Default::default()
+        }),
+        mutable: Some(true),
+        shared: Some(false),
+    };
+    let global_index = append_global(
+        program,
+        fuel_type,
+        with_operand(
+            OpCode::I64Constant,
+            operator::Operator::I64Value(initial_fuel as i64),
+        ),
+    );
+    program
+        .export_section
+        .get_or_insert_with(Default::default)
+        .exports
+        .push(Export {
+            name: Some(export_name.to_string()),
+            kind: Some(ExternalKind::ExtGlobal as i32),
+            index: Some(global_index),
+        });
+    Charge::Global(global_index)
+}
+
+/// Instruments every function body to pay for its basic blocks before running them.
+pub fn meter_fuel(mut program: ProgramModule, config: &MeteringConfig) -> Result<ProgramModule> {
+    let charge = match &config.mechanism {
+        FuelMechanism::Import { module, name } => inject_import(&mut program, module, name),
+        FuelMechanism::Global {
+            export_name,
+            initial_fuel,
+        } => inject_global(&mut program, export_name, *initial_fuel),
+    };
+    let entries = program
+        .code_section
+        .iter_mut()
+        .flat_map(|section| section.code_section_entry.iter_mut());
+    for entry in entries {
+        entry.body = meter_body(std::mem::take(&mut entry.body), config, &charge)?;
+    }
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        base_types, body, code_section, funcref_table, log_import, validate,
+    };
+    use wasm_encoder::{
+        BlockType as EncoderBlockType, ConstExpr, ElementSection, Elements, ExportKind,
+        FunctionSection, GlobalSection, Instruction, Module, StartSection, ValType,
+    };
+
+    /// Creates a module with an imported logger, a function branching on its argument and a
+    /// function looping once, referenced from exports, the start section, a table and a global
+    fn create_module() -> Vec<u8> {
+        let mut module = Module::new();
+        module.section(&base_types());
+        module.section(&log_import(0));
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        functions.function(0);
+        module.section(&functions);
+        module.section(&funcref_table());
+        let mut globals = GlobalSection::new();
+        globals.global(
+            wasm_encoder::GlobalType {
+                val_type: ValType::FUNCREF,
+                mutable: false,
+                shared: false,
+            },
+            &ConstExpr::ref_func(1),
+        );
+        module.section(&globals);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("run", ExportKind::Func, 1);
+        exports.export("tick", ExportKind::Func, 2);
+        module.section(&exports);
+        module.section(&StartSection { function_index: 2 });
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            Elements::Functions([2].as_slice().into()),
+        );
+        module.section(&elements);
+        module.section(&code_section(vec![
+            vec![
+                Instruction::LocalGet(0),
+                Instruction::If(EncoderBlockType::Empty),
+                Instruction::Call(2),
+                Instruction::End,
+                Instruction::Call(0),
+                Instruction::LocalGet(0),
+            ],
+            vec![
+                Instruction::Block(EncoderBlockType::Empty),
+                Instruction::Loop(EncoderBlockType::Empty),
+                Instruction::I32Const(1),
+                Instruction::BrIf(1),
+                Instruction::Br(0),
+                Instruction::End,
+                Instruction::End,
+            ],
+        ]));
+        module.finish()
+    }
+
+    fn call(function_index: u32) -> Operator {
+        with_operand(
+            OpCode::Call,
+            operator::Operator::FunctionIndex(function_index),
+        )
+    }
+
+    fn charge(cost: i64) -> [Operator; 2] {
+        [
+            with_operand(OpCode::I64Constant, operator::Operator::I64Value(cost)),
+            call(1),
+        ]
+    }
+
+    #[test]
+    fn test_meter_fuel_import() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let metered = meter_fuel(program, &MeteringConfig::default()).unwrap();
+        validate(&metered);
+
+        let import = metered
+            .import_section
+            .as_ref()
+            .unwrap()
+            .imports
+            .last()
+            .unwrap();
+        assert_eq!(import.module, Some("env".to_string()));
+        assert_eq!(import.name, Some("charge_fuel".to_string()));
+        assert_eq!(import.ty, Some(import::Ty::FunctionType(2)));
+        assert_eq!(
+            metered.type_section.as_ref().unwrap().types[2],
+            charge_function_type()
+        );
+
+        let local_get = with_operand(OpCode::LocalGet, operator::Operator::LocalIndex(0));
+        let mut expected: Vec<Operator> = Vec::new();
+        expected.extend(charge(2));
+        expected.push(local_get.clone());
+        expected.push(with_operand(
+            OpCode::If,
+            operator::Operator::BlockType(BlockType {
+                block_type: Some(block_type::BlockType::Empty(0)),
+            }),
+        ));
+        expected.extend(charge(2));
+        expected.extend([call(3), simple(OpCode::End)]);
+        expected.extend(charge(3));
+        expected.extend([call(0), local_get, simple(OpCode::End)]);
+        assert_eq!(body(&metered, 0), expected.as_slice());
+    }
+
+    #[test]
+    fn test_meter_fuel_remaps_functions() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let metered = meter_fuel(program, &MeteringConfig::default()).unwrap();
+        let exports = &metered.export_section.as_ref().unwrap().exports;
+        assert_eq!(exports[0].index, Some(2));
+        assert_eq!(exports[1].index, Some(3));
+        assert_eq!(
+            metered.start_section.as_ref().unwrap().function_index,
+            Some(3)
+        );
+        assert_eq!(
+            metered.element_section.as_ref().unwrap().elements[0].items,
+            Some(element::Items::Functions(ElementFunctions {
+                functions: vec![3]
+            }))
+        );
+        let init_expr = metered.global_section.as_ref().unwrap().globals[0]
+            .init_expr
+            .as_ref()
+            .unwrap();
+        assert_eq!(
+            init_expr.operators[0].operator,
+            Some(operator::Operator::FunctionIndex(2))
+        );
+    }
+
+    #[test]
+    fn test_meter_fuel_global() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let config = MeteringConfig {
+            mechanism: FuelMechanism::Global {
+                export_name: "fuel".to_string(),
+                initial_fuel: 0,
+            },
+            ..Default::default()
+        };
+        let metered = meter_fuel(program, &config).unwrap();
+        validate(&metered);
+
+        assert_eq!(metered.global_section.as_ref().unwrap().globals.len(), 2);
+        let export = metered
+            .export_section
+            .as_ref()
+            .unwrap()
+            .exports
+            .last()
+            .unwrap();
+        assert_eq!(export.name, Some("fuel".to_string()));
+        assert_eq!(export.kind, Some(ExternalKind::ExtGlobal as i32));
+        assert_eq!(export.index, Some(1));
+        // no import was injected, so function indices are unchanged
+        assert_eq!(
+            metered.export_section.as_ref().unwrap().exports[0].index,
+            Some(1)
+        );
+
+        // each iteration of the loop pays for itself
+        let tick = body(&metered, 1);
+        let loop_position = tick
+            .iter()
+            .position(|operator| operator.opcode == Some(OpCode::Loop as i32))
+            .unwrap();
+        assert_eq!(
+            &tick[loop_position + 1..loop_position + 11],
+            Charge::Global(1).operators(2).as_slice()
+        );
+    }
+
+    fn instantiate(
+        bytes: &[u8],
+    ) -> std::result::Result<(wasmi::Store<()>, wasmi::Instance), wasmi::Error> {
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, bytes)?;
+        let mut store = wasmi::Store::new(&engine, ());
+        let mut linker = wasmi::Linker::new(&engine);
+        linker.func_wrap("env", "log", || {})?;
+        let instance = linker.instantiate_and_start(&mut store, &module)?;
+        Ok((store, instance))
+    }
+
+    fn global_config(initial_fuel: u64) -> MeteringConfig {
+        MeteringConfig {
+            mechanism: FuelMechanism::Global {
+                export_name: "fuel".to_string(),
+                initial_fuel,
+            },
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_meter_fuel_global_start_function() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let metered = meter_fuel(program, &global_config(100)).unwrap();
+        let bytes = crate::render_wasm(metered).unwrap();
+        let (mut store, instance) = instantiate(&bytes).unwrap();
+        let fuel = instance.get_global(&store, "fuel").unwrap();
+        // block, loop, the branch out of both and the end of the function
+        assert_eq!(fuel.get(&store).i64(), Some(95));
+
+        let tick = instance.get_typed_func::<(), ()>(&store, "tick").unwrap();
+        tick.call(&mut store, ()).unwrap();
+        assert_eq!(fuel.get(&store).i64(), Some(90));
+    }
+
+    #[test]
+    fn test_meter_fuel_global_out_of_fuel() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let metered = meter_fuel(program, &global_config(4)).unwrap();
+        let bytes = crate::render_wasm(metered).unwrap();
+        assert!(instantiate(&bytes).is_err());
+    }
+
+    #[test]
+    fn test_meter_fuel_custom_costs() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let config = MeteringConfig {
+            costs: HashMap::from([
+                (OpCode::Block, 0),
+                (OpCode::Loop, 0),
+                (OpCode::End, 0),
+                (OpCode::BrIf, 10),
+            ]),
+            ..Default::default()
+        };
+        let metered = meter_fuel(program, &config).unwrap();
+        validate(&metered);
+        let charges: Vec<&Operator> = body(&metered, 1)
+            .iter()
+            .filter(|operator| operator.opcode == Some(OpCode::I64Constant as i32))
+            .collect();
+        assert_eq!(
+            charges,
+            vec![
+                &with_operand(OpCode::I64Constant, operator::Operator::I64Value(11)),
+                &with_operand(OpCode::I64Constant, operator::Operator::I64Value(1)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_meter_fuel_missing_opcode() {
+        let program = ProgramModule {
+            code_section: Some(crate::libernet_wasm::CodeSection {
+                code_section_entry: vec![CodeSectionEntry {
+                    locals: vec![],
+                    body: vec![Operator::default()],
+                }],
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(
+            meter_fuel(program, &MeteringConfig::default()),
+            Err(Error::Missing(_))
+        ));
+    }
+}
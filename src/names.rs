@@ -0,0 +1,378 @@
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+
+impl TryFrom<wasmparser::NameMap<'_>> for NameMap {
+    type Error = Error;
+
+    fn try_from(map: wasmparser::NameMap<'_>) -> Result<Self> {
+        let mut names: Vec<Naming> = Vec::new();
+        for naming in map {
+            let naming = naming?;
+            names.push(Naming {
+                index: Some(naming.index),
+                name: Some(naming.name.to_string()),
+            });
+        }
+        Ok(NameMap { names })
+    }
+}
+
+impl TryFrom<&NameMap> for wasm_encoder::NameMap {
+    type Error = Error;
+
+    fn try_from(map: &NameMap) -> Result<Self> {
+        let mut names = wasm_encoder::NameMap::new();
+        for naming in &map.names {
+            names.append(
+                naming.index.ok_or(Error::Missing("Naming index"))?,
+                naming
+                    .name
+                    .as_deref()
+                    .ok_or(Error::Missing("Naming name"))?,
+            );
+        }
+        Ok(names)
+    }
+}
+
+impl TryFrom<wasmparser::IndirectNameMap<'_>> for IndirectNameMap {
+    type Error = Error;
+
+    fn try_from(map: wasmparser::IndirectNameMap<'_>) -> Result<Self> {
+        let mut names: Vec<IndirectNaming> = Vec::new();
+        for naming in map {
+            let naming = naming?;
+            names.push(IndirectNaming {
+                index: Some(naming.index),
+                names: Some(NameMap::try_from(naming.names)?),
+            });
+        }
+        Ok(IndirectNameMap { names })
+    }
+}
+
+impl TryFrom<&IndirectNameMap> for wasm_encoder::IndirectNameMap {
+    type Error = Error;
+
+    fn try_from(map: &IndirectNameMap) -> Result<Self> {
+        let mut names = wasm_encoder::IndirectNameMap::new();
+        for naming in &map.names {
+            names.append(
+                naming
+                    .index
+                    .ok_or(Error::Missing("Indirect naming index"))?,
+                &wasm_encoder::NameMap::try_from(
+                    naming
+                        .names
+                        .as_ref()
+                        .ok_or(Error::Missing("Indirect naming names"))?,
+                )?,
+            );
+        }
+        Ok(names)
+    }
+}
+
+impl NameMap {
+    pub fn get(&self, index: u32) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|naming| naming.index == Some(index))
+            .and_then(|naming| naming.name.as_deref())
+    }
+}
+
+impl IndirectNameMap {
+    pub fn get(&self, outer: u32, inner: u32) -> Option<&str> {
+        self.names
+            .iter()
+            .find(|naming| naming.index == Some(outer))
+            .and_then(|naming| naming.names.as_ref())
+            .and_then(|names| names.get(inner))
+    }
+}
+
+fn subsection_id(name: &wasmparser::Name<'_>) -> u8 {
+    use wasmparser::Name;
+    match name {
+        Name::Module { .. } => 0,
+        Name::Function(_) => 1,
+        Name::Local(_) => 2,
+        Name::Label(_) => 3,
+        Name::Type(_) => 4,
+        Name::Table(_) => 5,
+        Name::Memory(_) => 6,
+        Name::Global(_) => 7,
+        Name::Element(_) => 8,
+        Name::Data(_) => 9,
+        Name::Field(_) => 10,
+        Name::Tag(_) => 11,
+        Name::Unknown { ty, .. } => *ty,
+    }
+}
+
+impl NameSection {
+    /// Decodes a name section whose subsections are in increasing id order, which is the only
+    /// order `render_wasm` can reproduce.
+    pub fn from_wasmparser(reader: wasmparser::NameSectionReader<'_>) -> Result<NameSection> {
+        use wasmparser::Name;
+        let mut section = NameSection::default();
+        let mut last_id: Option<u8> = None;
+        for name in reader {
+            let name = name?;
+            let id = subsection_id(&name);
+            if last_id.is_some_and(|last_id| id <= last_id) {
+                return Err(Error::Invalid(format!(
+                    "Name subsection {} is repeated or out of order",
+                    id
+                )));
+            }
+            last_id = Some(id);
+            match name {
+                Name::Module { name, .. } => section.module_name = Some(name.to_string()),
+                Name::Function(map) => section.function_names = Some(map.try_into()?),
+                Name::Local(map) => section.local_names = Some(map.try_into()?),
+                Name::Label(map) => section.label_names = Some(map.try_into()?),
+                Name::Type(map) => section.type_names = Some(map.try_into()?),
+                Name::Table(map) => section.table_names = Some(map.try_into()?),
+                Name::Memory(map) => section.memory_names = Some(map.try_into()?),
+                Name::Global(map) => section.global_names = Some(map.try_into()?),
+                Name::Element(map) => section.element_names = Some(map.try_into()?),
+                Name::Data(map) => section.data_names = Some(map.try_into()?),
+                Name::Field(map) => section.field_names = Some(map.try_into()?),
+                Name::Tag(map) => section.tag_names = Some(map.try_into()?),
+                Name::Unknown { ty, data, .. } => {
+                    section.unknown_subsections.push(NameSubsection {
+                        id: Some(ty as u32),
+                        data: Some(data.to_vec()),
+                    });
+                }
+            }
+        }
+        Ok(section)
+    }
+
+    pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
+        let mut names = wasm_encoder::NameSection::new();
+        // subsections have to be emitted in increasing id order
+        if let Some(name) = &self.module_name {
+            names.module(name);
+        }
+        if let Some(map) = &self.function_names {
+            names.functions(&map.try_into()?);
+        }
+        if let Some(map) = &self.local_names {
+            names.locals(&map.try_into()?);
+        }
+        if let Some(map) = &self.label_names {
+            names.labels(&map.try_into()?);
+        }
+        if let Some(map) = &self.type_names {
+            names.types(&map.try_into()?);
+        }
+        if let Some(map) = &self.table_names {
+            names.tables(&map.try_into()?);
+        }
+        if let Some(map) = &self.memory_names {
+            names.memories(&map.try_into()?);
+        }
+        if let Some(map) = &self.global_names {
+            names.globals(&map.try_into()?);
+        }
+        if let Some(map) = &self.element_names {
+            names.elements(&map.try_into()?);
+        }
+        if let Some(map) = &self.data_names {
+            names.data(&map.try_into()?);
+        }
+        if let Some(map) = &self.field_names {
+            names.fields(&map.try_into()?);
+        }
+        if let Some(map) = &self.tag_names {
+            names.tags(&map.try_into()?);
+        }
+        for subsection in &self.unknown_subsections {
+            let id = subsection.id.ok_or(Error::Missing("Name subsection id"))?;
+            names.raw(
+                u8::try_from(id).map_err(|_| {
+                    Error::Invalid(format!("Name subsection id {} is out of range", id))
+                })?,
+                subsection
+                    .data
+                    .as_deref()
+                    .ok_or(Error::Missing("Name subsection data"))?,
+            );
+        }
+        module.section(&names);
+        Ok(())
+    }
+
+    pub fn function_name(&self, function_index: u32) -> Option<&str> {
+        self.function_names.as_ref()?.get(function_index)
+    }
+
+    pub fn local_name(&self, function_index: u32, local_index: u32) -> Option<&str> {
+        self.local_names.as_ref()?.get(function_index, local_index)
+    }
+
+    pub fn label_name(&self, function_index: u32, label_index: u32) -> Option<&str> {
+        self.label_names.as_ref()?.get(function_index, label_index)
+    }
+
+    pub fn global_name(&self, global_index: u32) -> Option<&str> {
+        self.global_names.as_ref()?.get(global_index)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasmparser::{KnownCustom, Parser, Payload};
+
+    fn create_name_section() -> Vec<u8> {
+        let mut names = wasm_encoder::NameSection::new();
+        names.module("test_module");
+
+        let mut functions = wasm_encoder::NameMap::new();
+        functions.append(0, "main");
+        functions.append(1, "helper");
+        names.functions(&functions);
+
+        let mut main_locals = wasm_encoder::NameMap::new();
+        main_locals.append(0, "argc");
+        main_locals.append(1, "argv");
+        let mut locals = wasm_encoder::IndirectNameMap::new();
+        locals.append(0, &main_locals);
+        names.locals(&locals);
+
+        let mut loop_labels = wasm_encoder::NameMap::new();
+        loop_labels.append(0, "outer");
+        let mut labels = wasm_encoder::IndirectNameMap::new();
+        labels.append(1, &loop_labels);
+        names.labels(&labels);
+
+        let mut globals = wasm_encoder::NameMap::new();
+        globals.append(0, "__stack_pointer");
+        names.globals(&globals);
+
+        names.raw(42, &[1, 2, 3]);
+
+        let mut module = wasm_encoder::Module::new();
+        module.section(&names);
+        module.finish()
+    }
+
+    fn parse_name_section(wasm_bytes: &[u8]) -> NameSection {
+        for payload in Parser::new(0).parse_all(wasm_bytes) {
+            if let Payload::CustomSection(reader) = payload.unwrap()
+                && let KnownCustom::Name(reader) = reader.as_known()
+            {
+                return NameSection::from_wasmparser(reader).unwrap();
+            }
+        }
+        panic!("Name section not found");
+    }
+
+    #[test]
+    fn test_name_section_from_wasmparser() {
+        let section = parse_name_section(&create_name_section());
+
+        assert_eq!(section.module_name.as_deref(), Some("test_module"));
+        assert_eq!(section.function_name(0), Some("main"));
+        assert_eq!(section.function_name(1), Some("helper"));
+        assert_eq!(section.function_name(2), None);
+        assert_eq!(section.local_name(0, 1), Some("argv"));
+        assert_eq!(section.local_name(1, 0), None);
+        assert_eq!(section.label_name(1, 0), Some("outer"));
+        assert_eq!(section.global_name(0), Some("__stack_pointer"));
+        assert!(section.type_names.is_none());
+        assert_eq!(
+            section.unknown_subsections,
+            vec![NameSubsection {
+                id: Some(42),
+                data: Some(vec![1, 2, 3]),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_name_section_round_trip() {
+        let original = create_name_section();
+        let section = parse_name_section(&original);
+
+        let mut module = wasm_encoder::Module::new();
+        section.render_wasm(&mut module).unwrap();
+
+        assert_eq!(module.finish(), original);
+    }
+
+    #[test]
+    fn test_name_section_rejects_repeated_subsection() {
+        let mut names = wasm_encoder::NameSection::new();
+        let mut functions = wasm_encoder::NameMap::new();
+        functions.append(0, "main");
+        names.functions(&functions);
+        names.functions(&functions);
+        let mut module = wasm_encoder::Module::new();
+        module.section(&names);
+
+        for payload in Parser::new(0).parse_all(&module.finish()) {
+            if let Payload::CustomSection(reader) = payload.unwrap()
+                && let KnownCustom::Name(reader) = reader.as_known()
+            {
+                assert!(matches!(
+                    NameSection::from_wasmparser(reader),
+                    Err(Error::Invalid(_))
+                ));
+            }
+        }
+    }
+
+    #[test]
+    fn test_name_section_round_trip_unknown_subsection_in_the_middle() {
+        let mut names = wasm_encoder::NameSection::new();
+        let mut functions = wasm_encoder::NameMap::new();
+        functions.append(0, "main");
+        names.functions(&functions);
+        names.raw(42, &[1, 2, 3]);
+        let mut globals = wasm_encoder::NameMap::new();
+        globals.append(0, "__stack_pointer");
+        names.globals(&globals);
+        let mut module = wasm_encoder::Module::new();
+        module.section(&names);
+        let original = module.finish();
+
+        let custom_section = Parser::new(0)
+            .parse_all(&original)
+            .find_map(|payload| match payload.unwrap() {
+                Payload::CustomSection(reader) => {
+                    Some(CustomSection::from_wasmparser(&reader, 0).unwrap())
+                }
+                _ => None,
+            })
+            .unwrap();
+        assert!(custom_section.name_section.is_none());
+
+        let mut module = wasm_encoder::Module::new();
+        custom_section.render_wasm(&mut module).unwrap();
+        assert_eq!(module.finish(), original);
+    }
+
+    #[test]
+    fn test_name_section_render_missing_name() {
+        let section = NameSection {
+            function_names: Some(NameMap {
+                names: vec![Naming {
+                    index: Some(0),
+                    name: None,
+                }],
+            }),
+            ..Default::default()
+        };
+        let mut module = wasm_encoder::Module::new();
+        assert!(matches!(
+            section.render_wasm(&mut module),
+            Err(Error::Missing("Naming name"))
+        ));
+    }
+}
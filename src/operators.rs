@@ -1,8 +1,8 @@
+use crate::error::{Error, Result};
 use crate::libernet_wasm::*;
-use anyhow::{Ok, Result, anyhow};
 
 impl TryFrom<wasmparser::BlockType> for BlockType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(blockty: wasmparser::BlockType) -> Result<Self> {
         match blockty {
@@ -22,13 +22,10 @@ impl TryFrom<wasmparser::BlockType> for BlockType {
 }
 
 impl TryFrom<BlockType> for wasm_encoder::BlockType {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(blocktype: BlockType) -> Result<Self> {
-        match blocktype
-            .block_type
-            .ok_or(anyhow!("Block type not found"))?
-        {
+        match blocktype.block_type.ok_or(Error::Missing("Block type"))? {
             block_type::BlockType::Empty(0) => Ok(wasm_encoder::BlockType::Empty),
             block_type::BlockType::ValueType(valtype) => {
                 Ok(wasm_encoder::BlockType::Result(valtype.try_into()?))
@@ -36,13 +33,16 @@ impl TryFrom<BlockType> for wasm_encoder::BlockType {
             block_type::BlockType::TypeIndex(typeidx) => {
                 Ok(wasm_encoder::BlockType::FunctionType(typeidx))
             }
-            _ => Err(anyhow!("Unsupported block type: {:?}", blocktype)),
+            _ => Err(Error::Invalid(format!(
+                "Unsupported block type: {:?}",
+                blocktype
+            ))),
         }
     }
 }
 
 impl TryFrom<wasmparser::MemArg> for MemArg {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(memarg: wasmparser::MemArg) -> Result<Self> {
         Ok(MemArg {
@@ -55,7 +55,7 @@ impl TryFrom<wasmparser::MemArg> for MemArg {
 }
 
 impl TryFrom<MemArg> for wasm_encoder::MemArg {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(memarg: MemArg) -> Result<Self> {
         match memarg {
@@ -69,13 +69,60 @@ impl TryFrom<MemArg> for wasm_encoder::MemArg {
                 offset,
                 memory_index: memory,
             }),
-            _ => Err(anyhow!("Unsupported memarg: {:?}", memarg)),
+            _ => Err(Error::Invalid(format!("Unsupported memarg: {:?}", memarg))),
         }
     }
 }
 
+/// An operator without operand, built by instrumentation passes.
+pub(crate) fn simple(opcode: OpCode) -> Operator {
+    Operator {
+        opcode: Some(opcode as i32),
+        ..Operator::default()
+    }
+}
+
+pub(crate) fn with_operand(opcode: OpCode, operand: operator::Operator) -> Operator {
+    Operator {
+        opcode: Some(opcode as i32),
+        operator: Some(operand),
+    }
+}
+
+fn lane_index(lane: u32, lane_count: u8) -> Result<u8> {
+    u8::try_from(lane)
+        .ok()
+        .filter(|&lane| lane < lane_count)
+        .ok_or_else(|| {
+            Error::Invalid(format!(
+                "Lane index {} is out of range for {} lanes",
+                lane, lane_count
+            ))
+        })
+}
+
+fn v128_value(bytes: &[u8]) -> Result<i128> {
+    let bytes: [u8; 16] = bytes
+        .try_into()
+        .map_err(|_| Error::Invalid(format!("V128 constant has {} bytes", bytes.len())))?;
+    Ok(i128::from_le_bytes(bytes))
+}
+
+fn shuffle_lanes(lanes: &[u8]) -> Result<[u8; 16]> {
+    // the lanes select from the 32 bytes of both operands
+    if let Some(lane) = lanes.iter().find(|&&lane| lane >= 32) {
+        return Err(Error::Invalid(format!(
+            "Shuffle lane {} is out of range for 32 lanes",
+            lane
+        )));
+    }
+    lanes
+        .try_into()
+        .map_err(|_| Error::Invalid(format!("Shuffle has {} lanes", lanes.len())))
+}
+
 impl TryFrom<wasmparser::Catch> for CatchElement {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(catch: wasmparser::Catch) -> Result<Self> {
         match catch {
@@ -105,8 +152,39 @@ impl TryFrom<wasmparser::Catch> for CatchElement {
     }
 }
 
+impl TryFrom<CatchElement> for wasm_encoder::Catch {
+    type Error = Error;
+
+    fn try_from(catch: CatchElement) -> Result<Self> {
+        match catch.catch_element.ok_or(Error::Missing("Catch element"))? {
+            catch_element::CatchElement::One(CatchOne { tag, label }) => {
+                Ok(wasm_encoder::Catch::One {
+                    tag: tag.ok_or(Error::Missing("Catch tag"))?,
+                    label: label.ok_or(Error::Missing("Catch label"))?,
+                })
+            }
+            catch_element::CatchElement::OneRef(CatchOneRef { tag, label }) => {
+                Ok(wasm_encoder::Catch::OneRef {
+                    tag: tag.ok_or(Error::Missing("Catch tag"))?,
+                    label: label.ok_or(Error::Missing("Catch label"))?,
+                })
+            }
+            catch_element::CatchElement::All(CatchAllElements { label }) => {
+                Ok(wasm_encoder::Catch::All {
+                    label: label.ok_or(Error::Missing("Catch label"))?,
+                })
+            }
+            catch_element::CatchElement::AllRef(CatchAllRef { label }) => {
+                Ok(wasm_encoder::Catch::AllRef {
+                    label: label.ok_or(Error::Missing("Catch label"))?,
+                })
+            }
+        }
+    }
+}
+
 impl TryFrom<wasmparser::Operator<'_>> for Operator {
-    type Error = anyhow::Error;
+    type Error = Error;
 
     fn try_from(operator: wasmparser::Operator<'_>) -> Result<Self> {
         match operator {
@@ -960,779 +1038,5295 @@ impl TryFrom<wasmparser::Operator<'_>> for Operator {
                 opcode: Some(OpCode::LegacyExceptionsExtCatchAll as i32),
                 ..Operator::default()
             }),
-            _ => Err(anyhow!("Got unsupported operator: {:?}", operator)),
-        }
-    }
-}
-
-impl TryFrom<Operator> for wasm_encoder::Instruction<'_> {
-    type Error = anyhow::Error;
-
-    fn try_from(operator: Operator) -> Result<Self> {
-        let opcode = operator.opcode.ok_or(anyhow!("Opcode not found"))?;
-        let opcode = OpCode::try_from(opcode)?;
-        match opcode {
-            OpCode::Unreachable => Ok(wasm_encoder::Instruction::Unreachable),
-            OpCode::Nop => Ok(wasm_encoder::Instruction::Nop),
-            OpCode::Block => {
-                let blockty = match operator
-                    .operator
-                    .ok_or(anyhow!("Block operator not found"))?
-                {
-                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
-                    _ => return Err(anyhow!("Expected BlockType for Block operator")),
-                };
-                Ok(wasm_encoder::Instruction::Block(blockty))
-            }
-            OpCode::Loop => {
-                let blockty = match operator
-                    .operator
-                    .ok_or(anyhow!("Loop operator not found"))?
-                {
-                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
-                    _ => return Err(anyhow!("Expected BlockType for Loop operator")),
-                };
-                Ok(wasm_encoder::Instruction::Loop(blockty))
-            }
-            OpCode::If => {
-                let blockty = match operator.operator.ok_or(anyhow!("If operator not found"))? {
-                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
-                    _ => return Err(anyhow!("Expected BlockType for If operator")),
-                };
-                Ok(wasm_encoder::Instruction::If(blockty))
-            }
-            OpCode::Else => Ok(wasm_encoder::Instruction::Else),
-            OpCode::End => Ok(wasm_encoder::Instruction::End),
-            OpCode::Br => {
-                let relative_depth =
-                    match operator.operator.ok_or(anyhow!("Br operator not found"))? {
-                        operator::Operator::RelativeDepth(depth) => depth,
-                        _ => return Err(anyhow!("Expected RelativeDepth for Br operator")),
-                    };
-                Ok(wasm_encoder::Instruction::Br(relative_depth))
-            }
-            OpCode::BrIf => {
-                let relative_depth = match operator
-                    .operator
-                    .ok_or(anyhow!("BrIf operator not found"))?
-                {
-                    operator::Operator::RelativeDepth(depth) => depth,
-                    _ => return Err(anyhow!("Expected RelativeDepth for BrIf operator")),
-                };
-                Ok(wasm_encoder::Instruction::BrIf(relative_depth))
-            }
-            OpCode::BrTable => {
-                let (targets_vec, default) = match operator
-                    .operator
-                    .ok_or(anyhow!("BrTable operator not found"))?
-                {
-                    operator::Operator::Targets(targets) => {
-                        let default = targets
-                            .default
-                            .ok_or(anyhow!("BrTable default target not found"))?;
-                        (targets.targets, default)
-                    }
-                    _ => return Err(anyhow!("Expected Targets for BrTable operator")),
-                };
-                Ok(wasm_encoder::Instruction::BrTable(
-                    targets_vec.into(),
-                    default,
-                ))
-            }
-            OpCode::Return => Ok(wasm_encoder::Instruction::Return),
-            OpCode::Call => {
-                let function_index = match operator
-                    .operator
-                    .ok_or(anyhow!("Call operator not found"))?
-                {
-                    operator::Operator::FunctionIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected FunctionIndex for Call operator")),
-                };
-                Ok(wasm_encoder::Instruction::Call(function_index))
-            }
-            OpCode::CallIndirect => {
-                let (type_index, table_index) = match operator
-                    .operator
-                    .ok_or(anyhow!("CallIndirect operator not found"))?
-                {
-                    operator::Operator::CallIndirect(ci) => {
-                        let type_index = ci
-                            .type_index
-                            .ok_or(anyhow!("CallIndirect type_index not found"))?;
-                        let table_index = ci
-                            .table_index
-                            .ok_or(anyhow!("CallIndirect table_index not found"))?;
-                        (type_index, table_index)
-                    }
-                    _ => return Err(anyhow!("Expected CallIndirectOp for CallIndirect operator")),
-                };
-                Ok(wasm_encoder::Instruction::CallIndirect {
-                    type_index,
-                    table_index,
-                })
-            }
-            OpCode::Drop => Ok(wasm_encoder::Instruction::Drop),
-            OpCode::Select => Ok(wasm_encoder::Instruction::Select),
-            OpCode::LocalGet => {
-                let local_index = match operator
-                    .operator
-                    .ok_or(anyhow!("LocalGet operator not found"))?
-                {
-                    operator::Operator::LocalIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected LocalIndex for LocalGet operator")),
-                };
-                Ok(wasm_encoder::Instruction::LocalGet(local_index))
-            }
-            OpCode::LocalSet => {
-                let local_index = match operator
-                    .operator
-                    .ok_or(anyhow!("LocalSet operator not found"))?
-                {
-                    operator::Operator::LocalIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected LocalIndex for LocalSet operator")),
-                };
-                Ok(wasm_encoder::Instruction::LocalSet(local_index))
-            }
-            OpCode::LocalTee => {
-                let local_index = match operator
-                    .operator
-                    .ok_or(anyhow!("LocalTee operator not found"))?
-                {
-                    operator::Operator::LocalIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected LocalIndex for LocalTee operator")),
-                };
-                Ok(wasm_encoder::Instruction::LocalTee(local_index))
-            }
-            OpCode::GlobalGet => {
-                let global_index = match operator
-                    .operator
-                    .ok_or(anyhow!("GlobalGet operator not found"))?
-                {
-                    operator::Operator::GlobalIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected GlobalIndex for GlobalGet operator")),
-                };
-                Ok(wasm_encoder::Instruction::GlobalGet(global_index))
-            }
-            OpCode::GlobalSet => {
-                let global_index = match operator
-                    .operator
-                    .ok_or(anyhow!("GlobalSet operator not found"))?
-                {
-                    operator::Operator::GlobalIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected GlobalIndex for GlobalSet operator")),
-                };
-                Ok(wasm_encoder::Instruction::GlobalSet(global_index))
-            }
-            OpCode::I32Load => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I32Load operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Load operator")),
-                };
-                Ok(wasm_encoder::Instruction::I32Load(memarg))
-            }
-            OpCode::I64Load => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I64Load operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load operator")),
-                };
-                Ok(wasm_encoder::Instruction::I64Load(memarg))
-            }
-            OpCode::F32Load => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("F32Load operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for F32Load operator")),
-                };
-                Ok(wasm_encoder::Instruction::F32Load(memarg))
-            }
-            OpCode::F64Load => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("F64Load operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for F64Load operator")),
-                };
-                Ok(wasm_encoder::Instruction::F64Load(memarg))
-            }
-            OpCode::I32Load8Signed => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I32Load8S operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Load8S operator")),
-                };
-                Ok(wasm_encoder::Instruction::I32Load8S(memarg))
-            }
-            OpCode::I32Load8Unsigned => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I32Load8U operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Load8U operator")),
-                };
-                Ok(wasm_encoder::Instruction::I32Load8U(memarg))
-            }
-            OpCode::I32Load16Signed => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I32Load16S operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Load16S operator")),
-                };
-                Ok(wasm_encoder::Instruction::I32Load16S(memarg))
-            }
-            OpCode::I32Load16Unsigned => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I32Load16U operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Load16U operator")),
-                };
-                Ok(wasm_encoder::Instruction::I32Load16U(memarg))
-            }
-            OpCode::I64Load8Signed => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I64Load8S operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load8S operator")),
-                };
-                Ok(wasm_encoder::Instruction::I64Load8S(memarg))
-            }
-            OpCode::I64Load8Unsigned => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I64Load8U operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load8U operator")),
-                };
-                Ok(wasm_encoder::Instruction::I64Load8U(memarg))
-            }
-            OpCode::I64Load16Signed => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I64Load16S operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load16S operator")),
-                };
-                Ok(wasm_encoder::Instruction::I64Load16S(memarg))
-            }
-            OpCode::I64Load16Unsigned => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I64Load16U operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load16U operator")),
-                };
-                Ok(wasm_encoder::Instruction::I64Load16U(memarg))
-            }
-            OpCode::I64Load32Signed => {
-                let memarg = match operator
-                    .operator
-                    .ok_or(anyhow!("I64Load32S operator not found"))?
-                {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load32S operator")),
+            // @simd
+            wasmparser::Operator::V128Load { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load8x8S { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load8x8Signed as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load8x8U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load8x8Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load16x4S { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load16x4Signed as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load16x4U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load16x4Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load32x2S { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load32x2Signed as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load32x2U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load32x2Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load8Splat { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load8Splat as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load16Splat { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load16Splat as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load32Splat { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load32Splat as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load64Splat { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load64Splat as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load32Zero { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load32Zero as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load64Zero { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load64Zero as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Store { memarg } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Store as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::V128Load8Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load8Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Load16Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load16Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Load32Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load32Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Load64Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Load64Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Store8Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Store8Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Store16Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Store16Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Store32Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Store32Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Store64Lane { memarg, lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Store64Lane as i32),
+                operator: Some(operator::Operator::MemargLane(MemArgLane {
+                    memarg: Some(MemArg::try_from(memarg)?),
+                    lane: Some(lane as u32),
+                })),
+            }),
+            wasmparser::Operator::V128Const { value } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Const as i32),
+                operator: Some(operator::Operator::V128Value(value.bytes().to_vec())),
+            }),
+            wasmparser::Operator::I8x16Shuffle { lanes } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Shuffle as i32),
+                operator: Some(operator::Operator::Lanes(lanes.to_vec())),
+            }),
+            wasmparser::Operator::I8x16ExtractLaneS { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16ExtractLaneSigned as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I8x16ExtractLaneU { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16ExtractLaneUnsigned as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I8x16ReplaceLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16ReplaceLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I16x8ExtractLaneS { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtractLaneSigned as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I16x8ExtractLaneU { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtractLaneUnsigned as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I16x8ReplaceLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ReplaceLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I32x4ExtractLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtractLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I32x4ReplaceLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ReplaceLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I64x2ExtractLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtractLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I64x2ReplaceLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ReplaceLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::F32x4ExtractLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4ExtractLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::F32x4ReplaceLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4ReplaceLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::F64x2ExtractLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2ExtractLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::F64x2ReplaceLane { lane } => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2ReplaceLane as i32),
+                operator: Some(operator::Operator::Lane(lane as u32)),
+            }),
+            wasmparser::Operator::I8x16Swizzle => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Swizzle as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Splat => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Splat as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Splat => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Splat as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Splat => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Splat as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Splat => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Splat as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Splat => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Splat as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Splat => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Splat as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Eq => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Eq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Ne => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Ne as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16LtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16LtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16LtU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16LtUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16GtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16GtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16GtU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16GtUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16LeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16LeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16LeU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16LeUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16GeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16GeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16GeU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16GeUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Eq => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Eq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Ne => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Ne as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8LtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8LtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8LtU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8LtUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8GtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8GtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8GtU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8GtUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8LeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8LeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8LeU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8LeUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8GeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8GeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8GeU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8GeUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Eq => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Eq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Ne => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Ne as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4LtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4LtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4LtU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4LtUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4GtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4GtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4GtU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4GtUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4LeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4LeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4LeU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4LeUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4GeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4GeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4GeU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4GeUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Eq => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Eq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Ne => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Ne as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2LtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2LtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2GtS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2GtSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2LeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2LeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2GeS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2GeSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Eq => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Eq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Ne => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Ne as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Lt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Lt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Gt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Gt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Le => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Le as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Ge => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Ge as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Eq => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Eq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Ne => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Ne as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Lt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Lt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Gt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Gt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Le => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Le as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Ge => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Ge as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128Not => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Not as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128And => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128And as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128AndNot => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128AndNot as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128Or => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Or as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128Xor => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Xor as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128Bitselect => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128Bitselect as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::V128AnyTrue => Ok(Operator {
+                opcode: Some(OpCode::SimdExtV128AnyTrue as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Abs => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Abs as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Neg => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Neg as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Popcnt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Popcnt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16AllTrue => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16AllTrue as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Bitmask => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Bitmask as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16NarrowI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16NarrowI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16NarrowI16x8U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16NarrowI16x8Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Shl => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Shl as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16ShrS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16ShrSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16ShrU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16ShrUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Add => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Add as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16AddSatS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16AddSatSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16AddSatU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16AddSatUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16Sub => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16Sub as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16SubSatS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16SubSatSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16SubSatU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16SubSatUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16MinS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16MinSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16MinU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16MinUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16MaxS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16MaxSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16MaxU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16MaxUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16AvgrU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI8x16AvgrUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtAddPairwiseI8x16S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtAddPairwiseI8x16Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtAddPairwiseI8x16U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtAddPairwiseI8x16Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Abs => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Abs as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Neg => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Neg as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Q15MulrSatS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Q15MulrSatSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8AllTrue => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8AllTrue as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Bitmask => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Bitmask as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8NarrowI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8NarrowI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8NarrowI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8NarrowI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtendLowI8x16S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtendLowI8x16Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtendHighI8x16S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtendHighI8x16Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtendLowI8x16U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtendLowI8x16Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtendHighI8x16U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtendHighI8x16Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Shl => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Shl as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ShrS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ShrSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ShrU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ShrUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Add => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Add as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8AddSatS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8AddSatSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8AddSatU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8AddSatUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Sub => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Sub as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8SubSatS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8SubSatSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8SubSatU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8SubSatUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8Mul => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8Mul as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8MinS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8MinSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8MinU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8MinUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8MaxS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8MaxSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8MaxU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8MaxUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8AvgrU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8AvgrUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtMulLowI8x16S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtMulLowI8x16Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtMulHighI8x16S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtMulHighI8x16Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtMulLowI8x16U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtMulLowI8x16Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8ExtMulHighI8x16U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI16x8ExtMulHighI8x16Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtAddPairwiseI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtAddPairwiseI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtAddPairwiseI16x8U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtAddPairwiseI16x8Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Abs => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Abs as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Neg => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Neg as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4AllTrue => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4AllTrue as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Bitmask => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Bitmask as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtendLowI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtendLowI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtendHighI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtendHighI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtendLowI16x8U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtendLowI16x8Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtendHighI16x8U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtendHighI16x8Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Shl => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Shl as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ShrS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ShrSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ShrU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ShrUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Add => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Add as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Sub => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Sub as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4Mul => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4Mul as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4MinS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4MinSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4MinU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4MinUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4MaxS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4MaxSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4MaxU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4MaxUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4DotI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4DotI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtMulLowI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtMulLowI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtMulHighI16x8S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtMulHighI16x8Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtMulLowI16x8U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtMulLowI16x8Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4ExtMulHighI16x8U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4ExtMulHighI16x8Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Abs => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Abs as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Neg => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Neg as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2AllTrue => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2AllTrue as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Bitmask => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Bitmask as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtendLowI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtendLowI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtendHighI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtendHighI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtendLowI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtendLowI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtendHighI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtendHighI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Shl => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Shl as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ShrS => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ShrSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ShrU => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ShrUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Add => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Add as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Sub => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Sub as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2Mul => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2Mul as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtMulLowI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtMulLowI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtMulHighI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtMulHighI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtMulLowI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtMulLowI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2ExtMulHighI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI64x2ExtMulHighI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Ceil => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Ceil as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Floor => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Floor as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Trunc => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Trunc as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Nearest => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Nearest as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Abs => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Abs as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Neg => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Neg as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Sqrt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Sqrt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Add => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Add as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Sub => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Sub as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Mul => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Mul as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Div => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Div as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Min => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Min as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4Max => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Max as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4PMin => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Pmin as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4PMax => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4Pmax as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Ceil => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Ceil as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Floor => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Floor as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Trunc => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Trunc as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Nearest => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Nearest as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Abs => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Abs as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Neg => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Neg as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Sqrt => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Sqrt as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Add => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Add as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Sub => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Sub as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Mul => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Mul as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Div => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Div as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Min => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Min as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2Max => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Max as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2PMin => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Pmin as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2PMax => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2Pmax as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4TruncSatF32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4TruncSatF32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4TruncSatF32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4TruncSatF32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4ConvertI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4ConvertI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4ConvertI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4ConvertI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4TruncSatF64x2SZero => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4TruncSatF64x2SignedZero as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4TruncSatF64x2UZero => Ok(Operator {
+                opcode: Some(OpCode::SimdExtI32x4TruncSatF64x2UnsignedZero as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2ConvertLowI32x4S => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2ConvertLowI32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2ConvertLowI32x4U => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2ConvertLowI32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4DemoteF64x2Zero => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF32x4DemoteF64x2Zero as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2PromoteLowF32x4 => Ok(Operator {
+                opcode: Some(OpCode::SimdExtF64x2PromoteLowF32x4 as i32),
+                ..Operator::default()
+            }),
+            // @relaxed_simd
+            wasmparser::Operator::I8x16RelaxedSwizzle => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI8x16RelaxedSwizzle as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4RelaxedTruncF32x4S => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI32x4RelaxedTruncF32x4Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4RelaxedTruncF32x4U => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI32x4RelaxedTruncF32x4Unsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4RelaxedTruncF64x2SZero => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI32x4RelaxedTruncF64x2SignedZero as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4RelaxedTruncF64x2UZero => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI32x4RelaxedTruncF64x2UnsignedZero as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4RelaxedMadd => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF32x4RelaxedMadd as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4RelaxedNmadd => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF32x4RelaxedNmadd as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2RelaxedMadd => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF64x2RelaxedMadd as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2RelaxedNmadd => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF64x2RelaxedNmadd as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I8x16RelaxedLaneselect => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI8x16RelaxedLaneselect as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8RelaxedLaneselect => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI16x8RelaxedLaneselect as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4RelaxedLaneselect => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI32x4RelaxedLaneselect as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I64x2RelaxedLaneselect => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI64x2RelaxedLaneselect as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4RelaxedMin => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF32x4RelaxedMin as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F32x4RelaxedMax => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF32x4RelaxedMax as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2RelaxedMin => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF64x2RelaxedMin as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::F64x2RelaxedMax => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtF64x2RelaxedMax as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8RelaxedQ15mulrS => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI16x8RelaxedQ15mulrSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I16x8RelaxedDotI8x16I7x16S => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI16x8RelaxedDotI8x16I7x16Signed as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32x4RelaxedDotI8x16I7x16AddS => Ok(Operator {
+                opcode: Some(OpCode::RelaxedSimdExtI32x4RelaxedDotI8x16I7x16AddSigned as i32),
+                ..Operator::default()
+            }),
+            // @threads
+            wasmparser::Operator::MemoryAtomicNotify { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtMemoryAtomicNotify as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::MemoryAtomicWait32 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtMemoryAtomicWait32 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::MemoryAtomicWait64 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtMemoryAtomicWait64 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::AtomicFence => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtAtomicFence as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I32AtomicLoad { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicLoad as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicLoad { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicLoad as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicLoad8U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicLoad8Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicLoad16U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicLoad16Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicLoad8U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicLoad8Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicLoad16U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicLoad16Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicLoad32U { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicLoad32Unsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicStore { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicStore as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicStore { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicStore as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicStore8 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicStore8 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicStore16 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicStore16 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicStore8 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicStore8 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicStore16 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicStore16 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicStore32 { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicStore32 as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwAdd { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwAdd as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwAdd { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwAdd as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8AddU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8AddUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16AddU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16AddUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8AddU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8AddUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16AddU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16AddUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32AddU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32AddUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwSub { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwSub as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwSub { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwSub as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8SubU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8SubUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16SubU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16SubUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8SubU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8SubUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16SubU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16SubUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32SubU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32SubUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwAnd { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwAnd as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwAnd { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwAnd as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8AndU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8AndUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16AndU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16AndUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8AndU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8AndUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16AndU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16AndUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32AndU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32AndUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwOr { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwOr as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwOr { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwOr as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8OrU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8OrUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16OrU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16OrUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8OrU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8OrUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16OrU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16OrUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32OrU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32OrUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwXor { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwXor as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwXor { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwXor as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8XorU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8XorUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16XorU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16XorUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8XorU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8XorUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16XorU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16XorUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32XorU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32XorUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwXchg { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwXchg as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwXchg { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwXchg as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8XchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8XchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16XchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16XchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8XchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8XchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16XchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16XchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32XchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32XchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmwCmpxchg { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmwCmpxchg as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmwCmpxchg { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmwCmpxchg as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw8CmpxchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw8CmpxchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I32AtomicRmw16CmpxchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI32AtomicRmw16CmpxchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw8CmpxchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw8CmpxchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw16CmpxchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw16CmpxchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            wasmparser::Operator::I64AtomicRmw32CmpxchgU { memarg } => Ok(Operator {
+                opcode: Some(OpCode::ThreadsExtI64AtomicRmw32CmpxchgUnsigned as i32),
+                operator: Some(operator::Operator::Memarg(MemArg::try_from(memarg)?)),
+            }),
+            // @reference_types
+            wasmparser::Operator::TypedSelect { ty } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtTypedSelect as i32),
+                operator: Some(operator::Operator::SelectType(ValueType::try_from(ty)?)),
+            }),
+            wasmparser::Operator::RefNull { hty } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtRefNull as i32),
+                operator: Some(operator::Operator::HeapType(HeapType::try_from(hty)?)),
+            }),
+            wasmparser::Operator::RefIsNull => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtRefIsNull as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::RefFunc { function_index } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtRefFunc as i32),
+                operator: Some(operator::Operator::FunctionIndex(function_index)),
+            }),
+            wasmparser::Operator::TableFill { table } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtTableFill as i32),
+                operator: Some(operator::Operator::TableIndex(table)),
+            }),
+            wasmparser::Operator::TableGet { table } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtTableGet as i32),
+                operator: Some(operator::Operator::TableIndex(table)),
+            }),
+            wasmparser::Operator::TableSet { table } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtTableSet as i32),
+                operator: Some(operator::Operator::TableIndex(table)),
+            }),
+            wasmparser::Operator::TableGrow { table } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtTableGrow as i32),
+                operator: Some(operator::Operator::TableIndex(table)),
+            }),
+            wasmparser::Operator::TableSize { table } => Ok(Operator {
+                opcode: Some(OpCode::ReferenceTypesExtTableSize as i32),
+                operator: Some(operator::Operator::TableIndex(table)),
+            }),
+            // @tail_call
+            wasmparser::Operator::ReturnCall { function_index } => Ok(Operator {
+                opcode: Some(OpCode::TailCallExtReturnCall as i32),
+                operator: Some(operator::Operator::FunctionIndex(function_index)),
+            }),
+            wasmparser::Operator::ReturnCallIndirect {
+                type_index,
+                table_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::TailCallExtReturnCallIndirect as i32),
+                operator: Some(operator::Operator::CallIndirect(CallIndirectOp {
+                    type_index: Some(type_index),
+                    table_index: Some(table_index),
+                })),
+            }),
+            wasmparser::Operator::ReturnCallRef { type_index } => Ok(Operator {
+                opcode: Some(OpCode::TailCallExtReturnCallRef as i32),
+                operator: Some(operator::Operator::TypeIndex(type_index)),
+            }),
+            // @gc
+            wasmparser::Operator::RefEq => Ok(Operator {
+                opcode: Some(OpCode::GcExtRefEq as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::StructNew { struct_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtStructNew as i32),
+                operator: Some(operator::Operator::TypeIndex(struct_type_index)),
+            }),
+            wasmparser::Operator::StructNewDefault { struct_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtStructNewDefault as i32),
+                operator: Some(operator::Operator::TypeIndex(struct_type_index)),
+            }),
+            wasmparser::Operator::StructGet {
+                struct_type_index,
+                field_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtStructGet as i32),
+                operator: Some(operator::Operator::StructField(StructFieldOp {
+                    type_index: Some(struct_type_index),
+                    field_index: Some(field_index),
+                })),
+            }),
+            wasmparser::Operator::StructGetS {
+                struct_type_index,
+                field_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtStructGetSigned as i32),
+                operator: Some(operator::Operator::StructField(StructFieldOp {
+                    type_index: Some(struct_type_index),
+                    field_index: Some(field_index),
+                })),
+            }),
+            wasmparser::Operator::StructGetU {
+                struct_type_index,
+                field_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtStructGetUnsigned as i32),
+                operator: Some(operator::Operator::StructField(StructFieldOp {
+                    type_index: Some(struct_type_index),
+                    field_index: Some(field_index),
+                })),
+            }),
+            wasmparser::Operator::StructSet {
+                struct_type_index,
+                field_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtStructSet as i32),
+                operator: Some(operator::Operator::StructField(StructFieldOp {
+                    type_index: Some(struct_type_index),
+                    field_index: Some(field_index),
+                })),
+            }),
+            wasmparser::Operator::ArrayNew { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayNew as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArrayNewDefault { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayNewDefault as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArrayNewFixed {
+                array_type_index,
+                array_size,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayNewFixed as i32),
+                operator: Some(operator::Operator::ArrayNewFixed(ArrayNewFixedOp {
+                    type_index: Some(array_type_index),
+                    size: Some(array_size),
+                })),
+            }),
+            wasmparser::Operator::ArrayNewData {
+                array_type_index,
+                array_data_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayNewData as i32),
+                operator: Some(operator::Operator::ArrayData(ArrayDataOp {
+                    type_index: Some(array_type_index),
+                    data_index: Some(array_data_index),
+                })),
+            }),
+            wasmparser::Operator::ArrayNewElem {
+                array_type_index,
+                array_elem_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayNewElem as i32),
+                operator: Some(operator::Operator::ArrayElem(ArrayElemOp {
+                    type_index: Some(array_type_index),
+                    element_index: Some(array_elem_index),
+                })),
+            }),
+            wasmparser::Operator::ArrayGet { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayGet as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArrayGetS { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayGetSigned as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArrayGetU { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayGetUnsigned as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArraySet { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArraySet as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArrayLen => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayLen as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::ArrayFill { array_type_index } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayFill as i32),
+                operator: Some(operator::Operator::TypeIndex(array_type_index)),
+            }),
+            wasmparser::Operator::ArrayCopy {
+                array_type_index_dst,
+                array_type_index_src,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayCopy as i32),
+                operator: Some(operator::Operator::ArrayCopy(ArrayCopyOp {
+                    dst_type_index: Some(array_type_index_dst),
+                    src_type_index: Some(array_type_index_src),
+                })),
+            }),
+            wasmparser::Operator::ArrayInitData {
+                array_type_index,
+                array_data_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayInitData as i32),
+                operator: Some(operator::Operator::ArrayData(ArrayDataOp {
+                    type_index: Some(array_type_index),
+                    data_index: Some(array_data_index),
+                })),
+            }),
+            wasmparser::Operator::ArrayInitElem {
+                array_type_index,
+                array_elem_index,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtArrayInitElem as i32),
+                operator: Some(operator::Operator::ArrayElem(ArrayElemOp {
+                    type_index: Some(array_type_index),
+                    element_index: Some(array_elem_index),
+                })),
+            }),
+            wasmparser::Operator::RefTestNonNull { hty } => Ok(Operator {
+                opcode: Some(OpCode::GcExtRefTestNonNull as i32),
+                operator: Some(operator::Operator::HeapType(HeapType::try_from(hty)?)),
+            }),
+            wasmparser::Operator::RefTestNullable { hty } => Ok(Operator {
+                opcode: Some(OpCode::GcExtRefTestNullable as i32),
+                operator: Some(operator::Operator::HeapType(HeapType::try_from(hty)?)),
+            }),
+            wasmparser::Operator::RefCastNonNull { hty } => Ok(Operator {
+                opcode: Some(OpCode::GcExtRefCastNonNull as i32),
+                operator: Some(operator::Operator::HeapType(HeapType::try_from(hty)?)),
+            }),
+            wasmparser::Operator::RefCastNullable { hty } => Ok(Operator {
+                opcode: Some(OpCode::GcExtRefCastNullable as i32),
+                operator: Some(operator::Operator::HeapType(HeapType::try_from(hty)?)),
+            }),
+            wasmparser::Operator::AnyConvertExtern => Ok(Operator {
+                opcode: Some(OpCode::GcExtAnyConvertExtern as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::ExternConvertAny => Ok(Operator {
+                opcode: Some(OpCode::GcExtExternConvertAny as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::RefI31 => Ok(Operator {
+                opcode: Some(OpCode::GcExtRefI31 as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I31GetS => Ok(Operator {
+                opcode: Some(OpCode::GcExtI31GetSigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::I31GetU => Ok(Operator {
+                opcode: Some(OpCode::GcExtI31GetUnsigned as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::BrOnCast {
+                relative_depth,
+                from_ref_type,
+                to_ref_type,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtBrOnCast as i32),
+                operator: Some(operator::Operator::BrOnCast(BrOnCastOp {
+                    relative_depth: Some(relative_depth),
+                    from_type: Some(ReferenceType::try_from(from_ref_type)?),
+                    to_type: Some(ReferenceType::try_from(to_ref_type)?),
+                })),
+            }),
+            wasmparser::Operator::BrOnCastFail {
+                relative_depth,
+                from_ref_type,
+                to_ref_type,
+            } => Ok(Operator {
+                opcode: Some(OpCode::GcExtBrOnCastFail as i32),
+                operator: Some(operator::Operator::BrOnCast(BrOnCastOp {
+                    relative_depth: Some(relative_depth),
+                    from_type: Some(ReferenceType::try_from(from_ref_type)?),
+                    to_type: Some(ReferenceType::try_from(to_ref_type)?),
+                })),
+            }),
+            // @function_references
+            wasmparser::Operator::CallRef { type_index } => Ok(Operator {
+                opcode: Some(OpCode::FunctionReferencesExtCallRef as i32),
+                operator: Some(operator::Operator::TypeIndex(type_index)),
+            }),
+            wasmparser::Operator::RefAsNonNull => Ok(Operator {
+                opcode: Some(OpCode::FunctionReferencesExtRefAsNonNull as i32),
+                ..Operator::default()
+            }),
+            wasmparser::Operator::BrOnNull { relative_depth } => Ok(Operator {
+                opcode: Some(OpCode::FunctionReferencesExtBrOnNull as i32),
+                operator: Some(operator::Operator::RelativeDepth(relative_depth)),
+            }),
+            wasmparser::Operator::BrOnNonNull { relative_depth } => Ok(Operator {
+                opcode: Some(OpCode::FunctionReferencesExtBrOnNonNull as i32),
+                operator: Some(operator::Operator::RelativeDepth(relative_depth)),
+            }),
+            _ => Err(Error::Unsupported(format!(
+                "Got unsupported operator: {:?}",
+                operator
+            ))),
+        }
+    }
+}
+
+impl TryFrom<Operator> for wasm_encoder::Instruction<'_> {
+    type Error = Error;
+
+    fn try_from(operator: Operator) -> Result<Self> {
+        let opcode = operator.opcode.ok_or(Error::Missing("Opcode"))?;
+        let opcode = OpCode::try_from(opcode)?;
+        match opcode {
+            OpCode::Unreachable => Ok(wasm_encoder::Instruction::Unreachable),
+            OpCode::Nop => Ok(wasm_encoder::Instruction::Nop),
+            OpCode::Block => {
+                let blockty = match operator.operator.ok_or(Error::Missing("Block operator"))? {
+                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "BlockType",
+                            operator: "Block",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Block(blockty))
+            }
+            OpCode::Loop => {
+                let blockty = match operator.operator.ok_or(Error::Missing("Loop operator"))? {
+                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "BlockType",
+                            operator: "Loop",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Loop(blockty))
+            }
+            OpCode::If => {
+                let blockty = match operator.operator.ok_or(Error::Missing("If operator"))? {
+                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "BlockType",
+                            operator: "If",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::If(blockty))
+            }
+            OpCode::Else => Ok(wasm_encoder::Instruction::Else),
+            OpCode::End => Ok(wasm_encoder::Instruction::End),
+            OpCode::Br => {
+                let relative_depth = match operator.operator.ok_or(Error::Missing("Br operator"))? {
+                    operator::Operator::RelativeDepth(depth) => depth,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "RelativeDepth",
+                            operator: "Br",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Br(relative_depth))
+            }
+            OpCode::BrIf => {
+                let relative_depth =
+                    match operator.operator.ok_or(Error::Missing("BrIf operator"))? {
+                        operator::Operator::RelativeDepth(depth) => depth,
+                        _ => {
+                            return Err(Error::UnexpectedOperand {
+                                expected: "RelativeDepth",
+                                operator: "BrIf",
+                            });
+                        }
+                    };
+                Ok(wasm_encoder::Instruction::BrIf(relative_depth))
+            }
+            OpCode::BrTable => {
+                let (targets_vec, default) = match operator
+                    .operator
+                    .ok_or(Error::Missing("BrTable operator"))?
+                {
+                    operator::Operator::Targets(targets) => {
+                        let default = targets
+                            .default
+                            .ok_or(Error::Missing("BrTable default target"))?;
+                        (targets.targets, default)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Targets",
+                            operator: "BrTable",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::BrTable(
+                    targets_vec.into(),
+                    default,
+                ))
+            }
+            OpCode::Return => Ok(wasm_encoder::Instruction::Return),
+            OpCode::Call => {
+                let function_index =
+                    match operator.operator.ok_or(Error::Missing("Call operator"))? {
+                        operator::Operator::FunctionIndex(idx) => idx,
+                        _ => {
+                            return Err(Error::UnexpectedOperand {
+                                expected: "FunctionIndex",
+                                operator: "Call",
+                            });
+                        }
+                    };
+                Ok(wasm_encoder::Instruction::Call(function_index))
+            }
+            OpCode::CallIndirect => {
+                let (type_index, table_index) = match operator
+                    .operator
+                    .ok_or(Error::Missing("CallIndirect operator"))?
+                {
+                    operator::Operator::CallIndirect(ci) => {
+                        let type_index = ci
+                            .type_index
+                            .ok_or(Error::Missing("CallIndirect type_index"))?;
+                        let table_index = ci
+                            .table_index
+                            .ok_or(Error::Missing("CallIndirect table_index"))?;
+                        (type_index, table_index)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "CallIndirectOp",
+                            operator: "CallIndirect",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::CallIndirect {
+                    type_index,
+                    table_index,
+                })
+            }
+            OpCode::Drop => Ok(wasm_encoder::Instruction::Drop),
+            OpCode::Select => Ok(wasm_encoder::Instruction::Select),
+            OpCode::LocalGet => {
+                let local_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("LocalGet operator"))?
+                {
+                    operator::Operator::LocalIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "LocalIndex",
+                            operator: "LocalGet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::LocalGet(local_index))
+            }
+            OpCode::LocalSet => {
+                let local_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("LocalSet operator"))?
+                {
+                    operator::Operator::LocalIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "LocalIndex",
+                            operator: "LocalSet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::LocalSet(local_index))
+            }
+            OpCode::LocalTee => {
+                let local_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("LocalTee operator"))?
+                {
+                    operator::Operator::LocalIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "LocalIndex",
+                            operator: "LocalTee",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::LocalTee(local_index))
+            }
+            OpCode::GlobalGet => {
+                let global_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("GlobalGet operator"))?
+                {
+                    operator::Operator::GlobalIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "GlobalIndex",
+                            operator: "GlobalGet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::GlobalGet(global_index))
+            }
+            OpCode::GlobalSet => {
+                let global_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("GlobalSet operator"))?
+                {
+                    operator::Operator::GlobalIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "GlobalIndex",
+                            operator: "GlobalSet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::GlobalSet(global_index))
+            }
+            OpCode::I32Load => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Load operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Load",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Load(memarg))
+            }
+            OpCode::I64Load => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load(memarg))
+            }
+            OpCode::F32Load => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("F32Load operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "F32Load",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F32Load(memarg))
+            }
+            OpCode::F64Load => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("F64Load operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "F64Load",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F64Load(memarg))
+            }
+            OpCode::I32Load8Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Load8S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Load8S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Load8S(memarg))
+            }
+            OpCode::I32Load8Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Load8U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Load8U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Load8U(memarg))
+            }
+            OpCode::I32Load16Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Load16S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Load16S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Load16S(memarg))
+            }
+            OpCode::I32Load16Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Load16U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Load16U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Load16U(memarg))
+            }
+            OpCode::I64Load8Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load8S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load8S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load8S(memarg))
+            }
+            OpCode::I64Load8Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load8U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load8U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load8U(memarg))
+            }
+            OpCode::I64Load16Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load16S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load16S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load16S(memarg))
+            }
+            OpCode::I64Load16Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load16U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load16U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load16U(memarg))
+            }
+            OpCode::I64Load32Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load32S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load32S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load32S(memarg))
+            }
+            OpCode::I64Load32Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Load32U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Load32U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Load32U(memarg))
+            }
+            OpCode::I32Store => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Store operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Store",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Store(memarg))
+            }
+            OpCode::I64Store => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Store operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Store",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Store(memarg))
+            }
+            OpCode::F32Store => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("F32Store operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "F32Store",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F32Store(memarg))
+            }
+            OpCode::F64Store => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("F64Store operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "F64Store",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F64Store(memarg))
+            }
+            OpCode::I32Store8 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Store8 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Store8",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Store8(memarg))
+            }
+            OpCode::I32Store16 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Store16 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32Store16",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Store16(memarg))
+            }
+            OpCode::I64Store8 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Store8 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Store8",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Store8(memarg))
+            }
+            OpCode::I64Store16 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Store16 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Store16",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Store16(memarg))
+            }
+            OpCode::I64Store32 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Store32 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64Store32",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Store32(memarg))
+            }
+            OpCode::MemorySize => {
+                let mem = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemorySize operator"))?
+                {
+                    operator::Operator::Mem(m) => m,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Mem",
+                            operator: "MemorySize",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemorySize(mem))
+            }
+            OpCode::MemoryGrow => {
+                let mem = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryGrow operator"))?
+                {
+                    operator::Operator::Mem(m) => m,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Mem",
+                            operator: "MemoryGrow",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryGrow(mem))
+            }
+            OpCode::I32Constant => {
+                let value = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32Const operator"))?
+                {
+                    operator::Operator::I32Value(v) => v,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "I32value",
+                            operator: "I32Const",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32Const(value))
+            }
+            OpCode::I64Constant => {
+                let value = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64Const operator"))?
+                {
+                    operator::Operator::I64Value(v) => v,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "I64value",
+                            operator: "I64Const",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64Const(value))
+            }
+            OpCode::F32Constant => {
+                let value = match operator
+                    .operator
+                    .ok_or(Error::Missing("F32Const operator"))?
+                {
+                    operator::Operator::F32Value(bits) => wasm_encoder::Ieee32::new(bits),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "F32value",
+                            operator: "F32Const",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F32Const(value))
+            }
+            OpCode::F64Constant => {
+                let value = match operator
+                    .operator
+                    .ok_or(Error::Missing("F64Const operator"))?
+                {
+                    operator::Operator::F64Value(bits) => wasm_encoder::Ieee64::new(bits),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "F64value",
+                            operator: "F64Const",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F64Const(value))
+            }
+            OpCode::I32Eqz => Ok(wasm_encoder::Instruction::I32Eqz),
+            OpCode::I32Eq => Ok(wasm_encoder::Instruction::I32Eq),
+            OpCode::I32Ne => Ok(wasm_encoder::Instruction::I32Ne),
+            OpCode::I32LtSigned => Ok(wasm_encoder::Instruction::I32LtS),
+            OpCode::I32LtUnsigned => Ok(wasm_encoder::Instruction::I32LtU),
+            OpCode::I32GtSigned => Ok(wasm_encoder::Instruction::I32GtS),
+            OpCode::I32GtUnsigned => Ok(wasm_encoder::Instruction::I32GtU),
+            OpCode::I32LeSigned => Ok(wasm_encoder::Instruction::I32LeS),
+            OpCode::I32LeUnsigned => Ok(wasm_encoder::Instruction::I32LeU),
+            OpCode::I32GeSigned => Ok(wasm_encoder::Instruction::I32GeS),
+            OpCode::I32GeUnsigned => Ok(wasm_encoder::Instruction::I32GeU),
+            OpCode::I64Eqz => Ok(wasm_encoder::Instruction::I64Eqz),
+            OpCode::I64Eq => Ok(wasm_encoder::Instruction::I64Eq),
+            OpCode::I64Ne => Ok(wasm_encoder::Instruction::I64Ne),
+            OpCode::I64LtSigned => Ok(wasm_encoder::Instruction::I64LtS),
+            OpCode::I64LtUnsigned => Ok(wasm_encoder::Instruction::I64LtU),
+            OpCode::I64GtSigned => Ok(wasm_encoder::Instruction::I64GtS),
+            OpCode::I64GtUnsigned => Ok(wasm_encoder::Instruction::I64GtU),
+            OpCode::I64LeSigned => Ok(wasm_encoder::Instruction::I64LeS),
+            OpCode::I64LeUnsigned => Ok(wasm_encoder::Instruction::I64LeU),
+            OpCode::I64GeSigned => Ok(wasm_encoder::Instruction::I64GeS),
+            OpCode::I64GeUnsigned => Ok(wasm_encoder::Instruction::I64GeU),
+            OpCode::F32Eq => Ok(wasm_encoder::Instruction::F32Eq),
+            OpCode::F32Ne => Ok(wasm_encoder::Instruction::F32Ne),
+            OpCode::F32Lt => Ok(wasm_encoder::Instruction::F32Lt),
+            OpCode::F32Gt => Ok(wasm_encoder::Instruction::F32Gt),
+            OpCode::F32Le => Ok(wasm_encoder::Instruction::F32Le),
+            OpCode::F32Ge => Ok(wasm_encoder::Instruction::F32Ge),
+            OpCode::F64Eq => Ok(wasm_encoder::Instruction::F64Eq),
+            OpCode::F64Ne => Ok(wasm_encoder::Instruction::F64Ne),
+            OpCode::F64Lt => Ok(wasm_encoder::Instruction::F64Lt),
+            OpCode::F64Gt => Ok(wasm_encoder::Instruction::F64Gt),
+            OpCode::F64Le => Ok(wasm_encoder::Instruction::F64Le),
+            OpCode::F64Ge => Ok(wasm_encoder::Instruction::F64Ge),
+            OpCode::I32Clz => Ok(wasm_encoder::Instruction::I32Clz),
+            OpCode::I32Ctz => Ok(wasm_encoder::Instruction::I32Ctz),
+            OpCode::I32Popcnt => Ok(wasm_encoder::Instruction::I32Popcnt),
+            OpCode::I32Add => Ok(wasm_encoder::Instruction::I32Add),
+            OpCode::I32Sub => Ok(wasm_encoder::Instruction::I32Sub),
+            OpCode::I32Mul => Ok(wasm_encoder::Instruction::I32Mul),
+            OpCode::I32DivSigned => Ok(wasm_encoder::Instruction::I32DivS),
+            OpCode::I32DivUnsigned => Ok(wasm_encoder::Instruction::I32DivU),
+            OpCode::I32RemSigned => Ok(wasm_encoder::Instruction::I32RemS),
+            OpCode::I32RemUnsigned => Ok(wasm_encoder::Instruction::I32RemU),
+            OpCode::I32And => Ok(wasm_encoder::Instruction::I32And),
+            OpCode::I32Or => Ok(wasm_encoder::Instruction::I32Or),
+            OpCode::I32Xor => Ok(wasm_encoder::Instruction::I32Xor),
+            OpCode::I32Shl => Ok(wasm_encoder::Instruction::I32Shl),
+            OpCode::I32ShrSigned => Ok(wasm_encoder::Instruction::I32ShrS),
+            OpCode::I32ShrUnsigned => Ok(wasm_encoder::Instruction::I32ShrU),
+            OpCode::I32Rotl => Ok(wasm_encoder::Instruction::I32Rotl),
+            OpCode::I32Rotr => Ok(wasm_encoder::Instruction::I32Rotr),
+            OpCode::I64Clz => Ok(wasm_encoder::Instruction::I64Clz),
+            OpCode::I64Ctz => Ok(wasm_encoder::Instruction::I64Ctz),
+            OpCode::I64Popcnt => Ok(wasm_encoder::Instruction::I64Popcnt),
+            OpCode::I64Add => Ok(wasm_encoder::Instruction::I64Add),
+            OpCode::I64Sub => Ok(wasm_encoder::Instruction::I64Sub),
+            OpCode::I64Mul => Ok(wasm_encoder::Instruction::I64Mul),
+            OpCode::I64DivSigned => Ok(wasm_encoder::Instruction::I64DivS),
+            OpCode::I64DivUnsigned => Ok(wasm_encoder::Instruction::I64DivU),
+            OpCode::I64RemSigned => Ok(wasm_encoder::Instruction::I64RemS),
+            OpCode::I64RemUnsigned => Ok(wasm_encoder::Instruction::I64RemU),
+            OpCode::I64And => Ok(wasm_encoder::Instruction::I64And),
+            OpCode::I64Or => Ok(wasm_encoder::Instruction::I64Or),
+            OpCode::I64Xor => Ok(wasm_encoder::Instruction::I64Xor),
+            OpCode::I64Shl => Ok(wasm_encoder::Instruction::I64Shl),
+            OpCode::I64ShrSigned => Ok(wasm_encoder::Instruction::I64ShrS),
+            OpCode::I64ShrUnsigned => Ok(wasm_encoder::Instruction::I64ShrU),
+            OpCode::I64Rotl => Ok(wasm_encoder::Instruction::I64Rotl),
+            OpCode::I64Rotr => Ok(wasm_encoder::Instruction::I64Rotr),
+            OpCode::F32Abs => Ok(wasm_encoder::Instruction::F32Abs),
+            OpCode::F32Neg => Ok(wasm_encoder::Instruction::F32Neg),
+            OpCode::F32Ceil => Ok(wasm_encoder::Instruction::F32Ceil),
+            OpCode::F32Floor => Ok(wasm_encoder::Instruction::F32Floor),
+            OpCode::F32Trunc => Ok(wasm_encoder::Instruction::F32Trunc),
+            OpCode::F32Nearest => Ok(wasm_encoder::Instruction::F32Nearest),
+            OpCode::F32Sqrt => Ok(wasm_encoder::Instruction::F32Sqrt),
+            OpCode::F32Add => Ok(wasm_encoder::Instruction::F32Add),
+            OpCode::F32Sub => Ok(wasm_encoder::Instruction::F32Sub),
+            OpCode::F32Mul => Ok(wasm_encoder::Instruction::F32Mul),
+            OpCode::F32Div => Ok(wasm_encoder::Instruction::F32Div),
+            OpCode::F32Min => Ok(wasm_encoder::Instruction::F32Min),
+            OpCode::F32Max => Ok(wasm_encoder::Instruction::F32Max),
+            OpCode::F32Copysign => Ok(wasm_encoder::Instruction::F32Copysign),
+            OpCode::F64Abs => Ok(wasm_encoder::Instruction::F64Abs),
+            OpCode::F64Neg => Ok(wasm_encoder::Instruction::F64Neg),
+            OpCode::F64Ceil => Ok(wasm_encoder::Instruction::F64Ceil),
+            OpCode::F64Floor => Ok(wasm_encoder::Instruction::F64Floor),
+            OpCode::F64Trunc => Ok(wasm_encoder::Instruction::F64Trunc),
+            OpCode::F64Nearest => Ok(wasm_encoder::Instruction::F64Nearest),
+            OpCode::F64Sqrt => Ok(wasm_encoder::Instruction::F64Sqrt),
+            OpCode::F64Add => Ok(wasm_encoder::Instruction::F64Add),
+            OpCode::F64Sub => Ok(wasm_encoder::Instruction::F64Sub),
+            OpCode::F64Mul => Ok(wasm_encoder::Instruction::F64Mul),
+            OpCode::F64Div => Ok(wasm_encoder::Instruction::F64Div),
+            OpCode::F64Min => Ok(wasm_encoder::Instruction::F64Min),
+            OpCode::F64Max => Ok(wasm_encoder::Instruction::F64Max),
+            OpCode::F64Copysign => Ok(wasm_encoder::Instruction::F64Copysign),
+            OpCode::I32WrapI64 => Ok(wasm_encoder::Instruction::I32WrapI64),
+            OpCode::I32TruncF32Signed => Ok(wasm_encoder::Instruction::I32TruncF32S),
+            OpCode::I32TruncF32Unsigned => Ok(wasm_encoder::Instruction::I32TruncF32U),
+            OpCode::I32TruncF64Signed => Ok(wasm_encoder::Instruction::I32TruncF64S),
+            OpCode::I32TruncF64Unsigned => Ok(wasm_encoder::Instruction::I32TruncF64U),
+            OpCode::I64ExtendI32Signed => Ok(wasm_encoder::Instruction::I64ExtendI32S),
+            OpCode::I64ExtendI32Unsigned => Ok(wasm_encoder::Instruction::I64ExtendI32U),
+            OpCode::I64TruncF32Signed => Ok(wasm_encoder::Instruction::I64TruncF32S),
+            OpCode::I64TruncF32Unsigned => Ok(wasm_encoder::Instruction::I64TruncF32U),
+            OpCode::I64TruncF64Signed => Ok(wasm_encoder::Instruction::I64TruncF64S),
+            OpCode::I64TruncF64Unsigned => Ok(wasm_encoder::Instruction::I64TruncF64U),
+            OpCode::F32ConvertI32Signed => Ok(wasm_encoder::Instruction::F32ConvertI32S),
+            OpCode::F32ConvertI32Unsigned => Ok(wasm_encoder::Instruction::F32ConvertI32U),
+            OpCode::F32ConvertI64Signed => Ok(wasm_encoder::Instruction::F32ConvertI64S),
+            OpCode::F32ConvertI64Unsigned => Ok(wasm_encoder::Instruction::F32ConvertI64U),
+            OpCode::F32DemoteF64 => Ok(wasm_encoder::Instruction::F32DemoteF64),
+            OpCode::F64ConvertI32Signed => Ok(wasm_encoder::Instruction::F64ConvertI32S),
+            OpCode::F64ConvertI32Unsigned => Ok(wasm_encoder::Instruction::F64ConvertI32U),
+            OpCode::F64ConvertI64Signed => Ok(wasm_encoder::Instruction::F64ConvertI64S),
+            OpCode::F64ConvertI64Unsigned => Ok(wasm_encoder::Instruction::F64ConvertI64U),
+            OpCode::F64PromoteF32 => Ok(wasm_encoder::Instruction::F64PromoteF32),
+            OpCode::I32ReinterpretF32 => Ok(wasm_encoder::Instruction::I32ReinterpretF32),
+            OpCode::I64ReinterpretF64 => Ok(wasm_encoder::Instruction::I64ReinterpretF64),
+            OpCode::F32ReinterpretI32 => Ok(wasm_encoder::Instruction::F32ReinterpretI32),
+            OpCode::F64ReinterpretI64 => Ok(wasm_encoder::Instruction::F64ReinterpretI64),
+            OpCode::SignExtI32Extend8Signed => Ok(wasm_encoder::Instruction::I32Extend8S),
+            OpCode::SignExtI32Extend16Signed => Ok(wasm_encoder::Instruction::I32Extend16S),
+            OpCode::SignExtI64Extend8Signed => Ok(wasm_encoder::Instruction::I64Extend8S),
+            OpCode::SignExtI64Extend16Signed => Ok(wasm_encoder::Instruction::I64Extend16S),
+            OpCode::SignExtI64Extend32Signed => Ok(wasm_encoder::Instruction::I64Extend32S),
+            OpCode::SaturatingFloatToIntExtI32TruncSatF32Signed => {
+                Ok(wasm_encoder::Instruction::I32TruncSatF32S)
+            }
+            OpCode::SaturatingFloatToIntExtI32TruncSatF32Unsigned => {
+                Ok(wasm_encoder::Instruction::I32TruncSatF32U)
+            }
+            OpCode::SaturatingFloatToIntExtI32TruncSatF64Signed => {
+                Ok(wasm_encoder::Instruction::I32TruncSatF64S)
+            }
+            OpCode::SaturatingFloatToIntExtI32TruncSatF64Unsigned => {
+                Ok(wasm_encoder::Instruction::I32TruncSatF64U)
+            }
+            OpCode::SaturatingFloatToIntExtI64TruncSatF32Signed => {
+                Ok(wasm_encoder::Instruction::I64TruncSatF32S)
+            }
+            OpCode::SaturatingFloatToIntExtI64TruncSatF32Unsigned => {
+                Ok(wasm_encoder::Instruction::I64TruncSatF32U)
+            }
+            OpCode::SaturatingFloatToIntExtI64TruncSatF64Signed => {
+                Ok(wasm_encoder::Instruction::I64TruncSatF64S)
+            }
+            OpCode::SaturatingFloatToIntExtI64TruncSatF64Unsigned => {
+                Ok(wasm_encoder::Instruction::I64TruncSatF64U)
+            }
+            OpCode::BulkMemoryExtMemoryInit => {
+                let (data_index, mem) = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryInit operator"))?
+                {
+                    operator::Operator::MemoryInit(mi) => {
+                        let data_index = mi
+                            .data_index
+                            .ok_or(Error::Missing("MemoryInit data_index"))?;
+                        let mem = mi.address.ok_or(Error::Missing("MemoryInit address"))?;
+                        (data_index, mem)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemoryInitOp",
+                            operator: "MemoryInit",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryInit { mem, data_index })
+            }
+            OpCode::BulkMemoryExtDataDrop => {
+                let data_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("DataDrop operator"))?
+                {
+                    operator::Operator::DataIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "DataIndex",
+                            operator: "DataDrop",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::DataDrop(data_index))
+            }
+            OpCode::BulkMemoryExtMemoryCopy => {
+                let (dst_mem, src_mem) = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryCopy operator"))?
+                {
+                    operator::Operator::MemoryCopy(mc) => {
+                        let dst_mem = mc
+                            .destination_address
+                            .ok_or(Error::Missing("MemoryCopy destination_address"))?;
+                        let src_mem = mc
+                            .source_address
+                            .ok_or(Error::Missing("MemoryCopy source_address"))?;
+                        (dst_mem, src_mem)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemoryCopyOp",
+                            operator: "MemoryCopy",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryCopy { dst_mem, src_mem })
+            }
+            OpCode::BulkMemoryExtMemoryFill => {
+                let mem = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryFill operator"))?
+                {
+                    operator::Operator::Mem(m) => m,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Mem",
+                            operator: "MemoryFill",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryFill(mem))
+            }
+            OpCode::BulkMemoryExtTableInit => {
+                let (elem_index, table) = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableInit operator"))?
+                {
+                    operator::Operator::TableInit(ti) => {
+                        let elem_index = ti
+                            .element_index
+                            .ok_or(Error::Missing("TableInit elem_index"))?;
+                        let table = ti.table.ok_or(Error::Missing("TableInit table"))?;
+                        (elem_index, table)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableInitOp",
+                            operator: "TableInit",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableInit { table, elem_index })
+            }
+            OpCode::BulkMemoryExtElemDrop => {
+                let elem_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("ElemDrop operator"))?
+                {
+                    operator::Operator::ElementIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ElemIndex",
+                            operator: "ElemDrop",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::ElemDrop(elem_index))
+            }
+            OpCode::BulkMemoryExtTableCopy => {
+                let (dst_table, src_table) = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableCopy operator"))?
+                {
+                    operator::Operator::TableCopy(tc) => {
+                        let dst_table =
+                            tc.dst_table.ok_or(Error::Missing("TableCopy dst_table"))?;
+                        let src_table =
+                            tc.src_table.ok_or(Error::Missing("TableCopy src_table"))?;
+                        (dst_table, src_table)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableCopyOp",
+                            operator: "TableCopy",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableCopy {
+                    dst_table,
+                    src_table,
+                })
+            }
+            OpCode::ExceptionsExtTryTable => {
+                let (ty, catch_elements) = match operator
+                    .operator
+                    .ok_or(Error::Missing("TryTable operator"))?
+                {
+                    operator::Operator::TryTable(tt) => (
+                        tt.r#type.ok_or(Error::Missing("TryTable type"))?,
+                        tt.catches,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TryTableOp",
+                            operator: "TryTable",
+                        });
+                    }
+                };
+                let mut catches: Vec<wasm_encoder::Catch> = Vec::new();
+                for catch in catch_elements {
+                    catches.push(wasm_encoder::Catch::try_from(catch)?);
+                }
+                Ok(wasm_encoder::Instruction::TryTable(
+                    wasm_encoder::BlockType::try_from(ty)?,
+                    catches.into(),
+                ))
+            }
+            OpCode::ExceptionsExtThrow => {
+                let tag_index = match operator.operator.ok_or(Error::Missing("Throw operator"))? {
+                    operator::Operator::ThrowOp(to) => {
+                        to.tag_index.ok_or(Error::Missing("Throw tag_index"))?
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ThrowOp",
+                            operator: "Throw",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Throw(tag_index))
+            }
+            OpCode::ExceptionsExtThrowRef => Ok(wasm_encoder::Instruction::ThrowRef),
+            OpCode::LegacyExceptionsExtTry => {
+                let ty = match operator.operator.ok_or(Error::Missing("Try operator"))? {
+                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "BlockType",
+                            operator: "Try",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Try(ty))
+            }
+            OpCode::LegacyExceptionsExtCatch => {
+                let tag_index = match operator.operator.ok_or(Error::Missing("Catch operator"))? {
+                    operator::Operator::TagIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TagIndex",
+                            operator: "Catch",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Catch(tag_index))
+            }
+            OpCode::LegacyExceptionsExtRethrow => {
+                let relative_depth = match operator
+                    .operator
+                    .ok_or(Error::Missing("Rethrow operator"))?
+                {
+                    operator::Operator::RelativeDepth(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "RelativeDepth",
+                            operator: "Rethrow",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Rethrow(relative_depth))
+            }
+            OpCode::LegacyExceptionsExtDelegate => {
+                let relative_depth = match operator
+                    .operator
+                    .ok_or(Error::Missing("Delegate operator"))?
+                {
+                    operator::Operator::RelativeDepth(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "RelativeDepth",
+                            operator: "Delegate",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::Delegate(relative_depth))
+            }
+            OpCode::LegacyExceptionsExtCatchAll => Ok(wasm_encoder::Instruction::CatchAll),
+            OpCode::SimdExtV128Load => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load(memarg))
+            }
+            OpCode::SimdExtV128Load8x8Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load8x8S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load8x8S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load8x8S(memarg))
+            }
+            OpCode::SimdExtV128Load8x8Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load8x8U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load8x8U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load8x8U(memarg))
+            }
+            OpCode::SimdExtV128Load16x4Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load16x4S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load16x4S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load16x4S(memarg))
+            }
+            OpCode::SimdExtV128Load16x4Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load16x4U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load16x4U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load16x4U(memarg))
+            }
+            OpCode::SimdExtV128Load32x2Signed => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load32x2S operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load32x2S",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load32x2S(memarg))
+            }
+            OpCode::SimdExtV128Load32x2Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load32x2U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load32x2U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load32x2U(memarg))
+            }
+            OpCode::SimdExtV128Load8Splat => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load8Splat operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load8Splat",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load8Splat(memarg))
+            }
+            OpCode::SimdExtV128Load16Splat => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load16Splat operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load16Splat",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load16Splat(memarg))
+            }
+            OpCode::SimdExtV128Load32Splat => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load32Splat operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load32Splat",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load32Splat(memarg))
+            }
+            OpCode::SimdExtV128Load64Splat => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load64Splat operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load64Splat",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load64Splat(memarg))
+            }
+            OpCode::SimdExtV128Load32Zero => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load32Zero operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load32Zero",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load32Zero(memarg))
+            }
+            OpCode::SimdExtV128Load64Zero => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load64Zero operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Load64Zero",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load64Zero(memarg))
+            }
+            OpCode::SimdExtV128Store => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Store operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "V128Store",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Store(memarg))
+            }
+            OpCode::SimdExtV128Load8Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load8Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 16)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Load8Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load8Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Load16Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load16Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 8)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Load16Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load16Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Load32Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load32Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 4)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Load32Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load32Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Load64Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Load64Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 2)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Load64Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Load64Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Store8Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Store8Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 16)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Store8Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Store8Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Store16Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Store16Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 8)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Store16Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Store16Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Store32Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Store32Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 4)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Store32Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Store32Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Store64Lane => {
+                let (memarg, lane) = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Store64Lane operator"))?
+                {
+                    operator::Operator::MemargLane(MemArgLane {
+                        memarg: Some(memarg),
+                        lane: Some(lane),
+                    }) => (
+                        wasm_encoder::MemArg::try_from(memarg)?,
+                        lane_index(lane, 2)?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArgLane",
+                            operator: "V128Store64Lane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Store64Lane { memarg, lane })
+            }
+            OpCode::SimdExtV128Const => {
+                let value = match operator
+                    .operator
+                    .ok_or(Error::Missing("V128Const operator"))?
+                {
+                    operator::Operator::V128Value(bytes) => v128_value(&bytes)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "V128Value",
+                            operator: "V128Const",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::V128Const(value))
+            }
+            OpCode::SimdExtI8x16Shuffle => {
+                let lanes = match operator
+                    .operator
+                    .ok_or(Error::Missing("I8x16Shuffle operator"))?
+                {
+                    operator::Operator::Lanes(lanes) => shuffle_lanes(&lanes)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lanes",
+                            operator: "I8x16Shuffle",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I8x16Shuffle(lanes))
+            }
+            OpCode::SimdExtI8x16ExtractLaneSigned => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I8x16ExtractLaneS operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 16)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I8x16ExtractLaneS",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I8x16ExtractLaneS(lane))
+            }
+            OpCode::SimdExtI8x16ExtractLaneUnsigned => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I8x16ExtractLaneU operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 16)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I8x16ExtractLaneU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I8x16ExtractLaneU(lane))
+            }
+            OpCode::SimdExtI8x16ReplaceLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I8x16ReplaceLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 16)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I8x16ReplaceLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I8x16ReplaceLane(lane))
+            }
+            OpCode::SimdExtI16x8ExtractLaneSigned => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I16x8ExtractLaneS operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 8)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I16x8ExtractLaneS",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I16x8ExtractLaneS(lane))
+            }
+            OpCode::SimdExtI16x8ExtractLaneUnsigned => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I16x8ExtractLaneU operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 8)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I16x8ExtractLaneU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I16x8ExtractLaneU(lane))
+            }
+            OpCode::SimdExtI16x8ReplaceLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I16x8ReplaceLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 8)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I16x8ReplaceLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I16x8ReplaceLane(lane))
+            }
+            OpCode::SimdExtI32x4ExtractLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32x4ExtractLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 4)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I32x4ExtractLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32x4ExtractLane(lane))
+            }
+            OpCode::SimdExtI32x4ReplaceLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32x4ReplaceLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 4)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I32x4ReplaceLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32x4ReplaceLane(lane))
+            }
+            OpCode::SimdExtI64x2ExtractLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64x2ExtractLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 2)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I64x2ExtractLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64x2ExtractLane(lane))
+            }
+            OpCode::SimdExtI64x2ReplaceLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64x2ReplaceLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 2)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "I64x2ReplaceLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64x2ReplaceLane(lane))
+            }
+            OpCode::SimdExtF32x4ExtractLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("F32x4ExtractLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 4)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "F32x4ExtractLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F32x4ExtractLane(lane))
+            }
+            OpCode::SimdExtF32x4ReplaceLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("F32x4ReplaceLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 4)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "F32x4ReplaceLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F32x4ReplaceLane(lane))
+            }
+            OpCode::SimdExtF64x2ExtractLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("F64x2ExtractLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 2)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "F64x2ExtractLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F64x2ExtractLane(lane))
+            }
+            OpCode::SimdExtF64x2ReplaceLane => {
+                let lane = match operator
+                    .operator
+                    .ok_or(Error::Missing("F64x2ReplaceLane operator"))?
+                {
+                    operator::Operator::Lane(lane) => lane_index(lane, 2)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "Lane",
+                            operator: "F64x2ReplaceLane",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::F64x2ReplaceLane(lane))
+            }
+            OpCode::SimdExtI8x16Swizzle => Ok(wasm_encoder::Instruction::I8x16Swizzle),
+            OpCode::SimdExtI8x16Splat => Ok(wasm_encoder::Instruction::I8x16Splat),
+            OpCode::SimdExtI16x8Splat => Ok(wasm_encoder::Instruction::I16x8Splat),
+            OpCode::SimdExtI32x4Splat => Ok(wasm_encoder::Instruction::I32x4Splat),
+            OpCode::SimdExtI64x2Splat => Ok(wasm_encoder::Instruction::I64x2Splat),
+            OpCode::SimdExtF32x4Splat => Ok(wasm_encoder::Instruction::F32x4Splat),
+            OpCode::SimdExtF64x2Splat => Ok(wasm_encoder::Instruction::F64x2Splat),
+            OpCode::SimdExtI8x16Eq => Ok(wasm_encoder::Instruction::I8x16Eq),
+            OpCode::SimdExtI8x16Ne => Ok(wasm_encoder::Instruction::I8x16Ne),
+            OpCode::SimdExtI8x16LtSigned => Ok(wasm_encoder::Instruction::I8x16LtS),
+            OpCode::SimdExtI8x16LtUnsigned => Ok(wasm_encoder::Instruction::I8x16LtU),
+            OpCode::SimdExtI8x16GtSigned => Ok(wasm_encoder::Instruction::I8x16GtS),
+            OpCode::SimdExtI8x16GtUnsigned => Ok(wasm_encoder::Instruction::I8x16GtU),
+            OpCode::SimdExtI8x16LeSigned => Ok(wasm_encoder::Instruction::I8x16LeS),
+            OpCode::SimdExtI8x16LeUnsigned => Ok(wasm_encoder::Instruction::I8x16LeU),
+            OpCode::SimdExtI8x16GeSigned => Ok(wasm_encoder::Instruction::I8x16GeS),
+            OpCode::SimdExtI8x16GeUnsigned => Ok(wasm_encoder::Instruction::I8x16GeU),
+            OpCode::SimdExtI16x8Eq => Ok(wasm_encoder::Instruction::I16x8Eq),
+            OpCode::SimdExtI16x8Ne => Ok(wasm_encoder::Instruction::I16x8Ne),
+            OpCode::SimdExtI16x8LtSigned => Ok(wasm_encoder::Instruction::I16x8LtS),
+            OpCode::SimdExtI16x8LtUnsigned => Ok(wasm_encoder::Instruction::I16x8LtU),
+            OpCode::SimdExtI16x8GtSigned => Ok(wasm_encoder::Instruction::I16x8GtS),
+            OpCode::SimdExtI16x8GtUnsigned => Ok(wasm_encoder::Instruction::I16x8GtU),
+            OpCode::SimdExtI16x8LeSigned => Ok(wasm_encoder::Instruction::I16x8LeS),
+            OpCode::SimdExtI16x8LeUnsigned => Ok(wasm_encoder::Instruction::I16x8LeU),
+            OpCode::SimdExtI16x8GeSigned => Ok(wasm_encoder::Instruction::I16x8GeS),
+            OpCode::SimdExtI16x8GeUnsigned => Ok(wasm_encoder::Instruction::I16x8GeU),
+            OpCode::SimdExtI32x4Eq => Ok(wasm_encoder::Instruction::I32x4Eq),
+            OpCode::SimdExtI32x4Ne => Ok(wasm_encoder::Instruction::I32x4Ne),
+            OpCode::SimdExtI32x4LtSigned => Ok(wasm_encoder::Instruction::I32x4LtS),
+            OpCode::SimdExtI32x4LtUnsigned => Ok(wasm_encoder::Instruction::I32x4LtU),
+            OpCode::SimdExtI32x4GtSigned => Ok(wasm_encoder::Instruction::I32x4GtS),
+            OpCode::SimdExtI32x4GtUnsigned => Ok(wasm_encoder::Instruction::I32x4GtU),
+            OpCode::SimdExtI32x4LeSigned => Ok(wasm_encoder::Instruction::I32x4LeS),
+            OpCode::SimdExtI32x4LeUnsigned => Ok(wasm_encoder::Instruction::I32x4LeU),
+            OpCode::SimdExtI32x4GeSigned => Ok(wasm_encoder::Instruction::I32x4GeS),
+            OpCode::SimdExtI32x4GeUnsigned => Ok(wasm_encoder::Instruction::I32x4GeU),
+            OpCode::SimdExtI64x2Eq => Ok(wasm_encoder::Instruction::I64x2Eq),
+            OpCode::SimdExtI64x2Ne => Ok(wasm_encoder::Instruction::I64x2Ne),
+            OpCode::SimdExtI64x2LtSigned => Ok(wasm_encoder::Instruction::I64x2LtS),
+            OpCode::SimdExtI64x2GtSigned => Ok(wasm_encoder::Instruction::I64x2GtS),
+            OpCode::SimdExtI64x2LeSigned => Ok(wasm_encoder::Instruction::I64x2LeS),
+            OpCode::SimdExtI64x2GeSigned => Ok(wasm_encoder::Instruction::I64x2GeS),
+            OpCode::SimdExtF32x4Eq => Ok(wasm_encoder::Instruction::F32x4Eq),
+            OpCode::SimdExtF32x4Ne => Ok(wasm_encoder::Instruction::F32x4Ne),
+            OpCode::SimdExtF32x4Lt => Ok(wasm_encoder::Instruction::F32x4Lt),
+            OpCode::SimdExtF32x4Gt => Ok(wasm_encoder::Instruction::F32x4Gt),
+            OpCode::SimdExtF32x4Le => Ok(wasm_encoder::Instruction::F32x4Le),
+            OpCode::SimdExtF32x4Ge => Ok(wasm_encoder::Instruction::F32x4Ge),
+            OpCode::SimdExtF64x2Eq => Ok(wasm_encoder::Instruction::F64x2Eq),
+            OpCode::SimdExtF64x2Ne => Ok(wasm_encoder::Instruction::F64x2Ne),
+            OpCode::SimdExtF64x2Lt => Ok(wasm_encoder::Instruction::F64x2Lt),
+            OpCode::SimdExtF64x2Gt => Ok(wasm_encoder::Instruction::F64x2Gt),
+            OpCode::SimdExtF64x2Le => Ok(wasm_encoder::Instruction::F64x2Le),
+            OpCode::SimdExtF64x2Ge => Ok(wasm_encoder::Instruction::F64x2Ge),
+            OpCode::SimdExtV128Not => Ok(wasm_encoder::Instruction::V128Not),
+            OpCode::SimdExtV128And => Ok(wasm_encoder::Instruction::V128And),
+            OpCode::SimdExtV128AndNot => Ok(wasm_encoder::Instruction::V128AndNot),
+            OpCode::SimdExtV128Or => Ok(wasm_encoder::Instruction::V128Or),
+            OpCode::SimdExtV128Xor => Ok(wasm_encoder::Instruction::V128Xor),
+            OpCode::SimdExtV128Bitselect => Ok(wasm_encoder::Instruction::V128Bitselect),
+            OpCode::SimdExtV128AnyTrue => Ok(wasm_encoder::Instruction::V128AnyTrue),
+            OpCode::SimdExtI8x16Abs => Ok(wasm_encoder::Instruction::I8x16Abs),
+            OpCode::SimdExtI8x16Neg => Ok(wasm_encoder::Instruction::I8x16Neg),
+            OpCode::SimdExtI8x16Popcnt => Ok(wasm_encoder::Instruction::I8x16Popcnt),
+            OpCode::SimdExtI8x16AllTrue => Ok(wasm_encoder::Instruction::I8x16AllTrue),
+            OpCode::SimdExtI8x16Bitmask => Ok(wasm_encoder::Instruction::I8x16Bitmask),
+            OpCode::SimdExtI8x16NarrowI16x8Signed => {
+                Ok(wasm_encoder::Instruction::I8x16NarrowI16x8S)
+            }
+            OpCode::SimdExtI8x16NarrowI16x8Unsigned => {
+                Ok(wasm_encoder::Instruction::I8x16NarrowI16x8U)
+            }
+            OpCode::SimdExtI8x16Shl => Ok(wasm_encoder::Instruction::I8x16Shl),
+            OpCode::SimdExtI8x16ShrSigned => Ok(wasm_encoder::Instruction::I8x16ShrS),
+            OpCode::SimdExtI8x16ShrUnsigned => Ok(wasm_encoder::Instruction::I8x16ShrU),
+            OpCode::SimdExtI8x16Add => Ok(wasm_encoder::Instruction::I8x16Add),
+            OpCode::SimdExtI8x16AddSatSigned => Ok(wasm_encoder::Instruction::I8x16AddSatS),
+            OpCode::SimdExtI8x16AddSatUnsigned => Ok(wasm_encoder::Instruction::I8x16AddSatU),
+            OpCode::SimdExtI8x16Sub => Ok(wasm_encoder::Instruction::I8x16Sub),
+            OpCode::SimdExtI8x16SubSatSigned => Ok(wasm_encoder::Instruction::I8x16SubSatS),
+            OpCode::SimdExtI8x16SubSatUnsigned => Ok(wasm_encoder::Instruction::I8x16SubSatU),
+            OpCode::SimdExtI8x16MinSigned => Ok(wasm_encoder::Instruction::I8x16MinS),
+            OpCode::SimdExtI8x16MinUnsigned => Ok(wasm_encoder::Instruction::I8x16MinU),
+            OpCode::SimdExtI8x16MaxSigned => Ok(wasm_encoder::Instruction::I8x16MaxS),
+            OpCode::SimdExtI8x16MaxUnsigned => Ok(wasm_encoder::Instruction::I8x16MaxU),
+            OpCode::SimdExtI8x16AvgrUnsigned => Ok(wasm_encoder::Instruction::I8x16AvgrU),
+            OpCode::SimdExtI16x8ExtAddPairwiseI8x16Signed => {
+                Ok(wasm_encoder::Instruction::I16x8ExtAddPairwiseI8x16S)
+            }
+            OpCode::SimdExtI16x8ExtAddPairwiseI8x16Unsigned => {
+                Ok(wasm_encoder::Instruction::I16x8ExtAddPairwiseI8x16U)
+            }
+            OpCode::SimdExtI16x8Abs => Ok(wasm_encoder::Instruction::I16x8Abs),
+            OpCode::SimdExtI16x8Neg => Ok(wasm_encoder::Instruction::I16x8Neg),
+            OpCode::SimdExtI16x8Q15MulrSatSigned => Ok(wasm_encoder::Instruction::I16x8Q15MulrSatS),
+            OpCode::SimdExtI16x8AllTrue => Ok(wasm_encoder::Instruction::I16x8AllTrue),
+            OpCode::SimdExtI16x8Bitmask => Ok(wasm_encoder::Instruction::I16x8Bitmask),
+            OpCode::SimdExtI16x8NarrowI32x4Signed => {
+                Ok(wasm_encoder::Instruction::I16x8NarrowI32x4S)
+            }
+            OpCode::SimdExtI16x8NarrowI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I16x8NarrowI32x4U)
+            }
+            OpCode::SimdExtI16x8ExtendLowI8x16Signed => {
+                Ok(wasm_encoder::Instruction::I16x8ExtendLowI8x16S)
+            }
+            OpCode::SimdExtI16x8ExtendHighI8x16Signed => {
+                Ok(wasm_encoder::Instruction::I16x8ExtendHighI8x16S)
+            }
+            OpCode::SimdExtI16x8ExtendLowI8x16Unsigned => {
+                Ok(wasm_encoder::Instruction::I16x8ExtendLowI8x16U)
+            }
+            OpCode::SimdExtI16x8ExtendHighI8x16Unsigned => {
+                Ok(wasm_encoder::Instruction::I16x8ExtendHighI8x16U)
+            }
+            OpCode::SimdExtI16x8Shl => Ok(wasm_encoder::Instruction::I16x8Shl),
+            OpCode::SimdExtI16x8ShrSigned => Ok(wasm_encoder::Instruction::I16x8ShrS),
+            OpCode::SimdExtI16x8ShrUnsigned => Ok(wasm_encoder::Instruction::I16x8ShrU),
+            OpCode::SimdExtI16x8Add => Ok(wasm_encoder::Instruction::I16x8Add),
+            OpCode::SimdExtI16x8AddSatSigned => Ok(wasm_encoder::Instruction::I16x8AddSatS),
+            OpCode::SimdExtI16x8AddSatUnsigned => Ok(wasm_encoder::Instruction::I16x8AddSatU),
+            OpCode::SimdExtI16x8Sub => Ok(wasm_encoder::Instruction::I16x8Sub),
+            OpCode::SimdExtI16x8SubSatSigned => Ok(wasm_encoder::Instruction::I16x8SubSatS),
+            OpCode::SimdExtI16x8SubSatUnsigned => Ok(wasm_encoder::Instruction::I16x8SubSatU),
+            OpCode::SimdExtI16x8Mul => Ok(wasm_encoder::Instruction::I16x8Mul),
+            OpCode::SimdExtI16x8MinSigned => Ok(wasm_encoder::Instruction::I16x8MinS),
+            OpCode::SimdExtI16x8MinUnsigned => Ok(wasm_encoder::Instruction::I16x8MinU),
+            OpCode::SimdExtI16x8MaxSigned => Ok(wasm_encoder::Instruction::I16x8MaxS),
+            OpCode::SimdExtI16x8MaxUnsigned => Ok(wasm_encoder::Instruction::I16x8MaxU),
+            OpCode::SimdExtI16x8AvgrUnsigned => Ok(wasm_encoder::Instruction::I16x8AvgrU),
+            OpCode::SimdExtI16x8ExtMulLowI8x16Signed => {
+                Ok(wasm_encoder::Instruction::I16x8ExtMulLowI8x16S)
+            }
+            OpCode::SimdExtI16x8ExtMulHighI8x16Signed => {
+                Ok(wasm_encoder::Instruction::I16x8ExtMulHighI8x16S)
+            }
+            OpCode::SimdExtI16x8ExtMulLowI8x16Unsigned => {
+                Ok(wasm_encoder::Instruction::I16x8ExtMulLowI8x16U)
+            }
+            OpCode::SimdExtI16x8ExtMulHighI8x16Unsigned => {
+                Ok(wasm_encoder::Instruction::I16x8ExtMulHighI8x16U)
+            }
+            OpCode::SimdExtI32x4ExtAddPairwiseI16x8Signed => {
+                Ok(wasm_encoder::Instruction::I32x4ExtAddPairwiseI16x8S)
+            }
+            OpCode::SimdExtI32x4ExtAddPairwiseI16x8Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4ExtAddPairwiseI16x8U)
+            }
+            OpCode::SimdExtI32x4Abs => Ok(wasm_encoder::Instruction::I32x4Abs),
+            OpCode::SimdExtI32x4Neg => Ok(wasm_encoder::Instruction::I32x4Neg),
+            OpCode::SimdExtI32x4AllTrue => Ok(wasm_encoder::Instruction::I32x4AllTrue),
+            OpCode::SimdExtI32x4Bitmask => Ok(wasm_encoder::Instruction::I32x4Bitmask),
+            OpCode::SimdExtI32x4ExtendLowI16x8Signed => {
+                Ok(wasm_encoder::Instruction::I32x4ExtendLowI16x8S)
+            }
+            OpCode::SimdExtI32x4ExtendHighI16x8Signed => {
+                Ok(wasm_encoder::Instruction::I32x4ExtendHighI16x8S)
+            }
+            OpCode::SimdExtI32x4ExtendLowI16x8Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4ExtendLowI16x8U)
+            }
+            OpCode::SimdExtI32x4ExtendHighI16x8Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4ExtendHighI16x8U)
+            }
+            OpCode::SimdExtI32x4Shl => Ok(wasm_encoder::Instruction::I32x4Shl),
+            OpCode::SimdExtI32x4ShrSigned => Ok(wasm_encoder::Instruction::I32x4ShrS),
+            OpCode::SimdExtI32x4ShrUnsigned => Ok(wasm_encoder::Instruction::I32x4ShrU),
+            OpCode::SimdExtI32x4Add => Ok(wasm_encoder::Instruction::I32x4Add),
+            OpCode::SimdExtI32x4Sub => Ok(wasm_encoder::Instruction::I32x4Sub),
+            OpCode::SimdExtI32x4Mul => Ok(wasm_encoder::Instruction::I32x4Mul),
+            OpCode::SimdExtI32x4MinSigned => Ok(wasm_encoder::Instruction::I32x4MinS),
+            OpCode::SimdExtI32x4MinUnsigned => Ok(wasm_encoder::Instruction::I32x4MinU),
+            OpCode::SimdExtI32x4MaxSigned => Ok(wasm_encoder::Instruction::I32x4MaxS),
+            OpCode::SimdExtI32x4MaxUnsigned => Ok(wasm_encoder::Instruction::I32x4MaxU),
+            OpCode::SimdExtI32x4DotI16x8Signed => Ok(wasm_encoder::Instruction::I32x4DotI16x8S),
+            OpCode::SimdExtI32x4ExtMulLowI16x8Signed => {
+                Ok(wasm_encoder::Instruction::I32x4ExtMulLowI16x8S)
+            }
+            OpCode::SimdExtI32x4ExtMulHighI16x8Signed => {
+                Ok(wasm_encoder::Instruction::I32x4ExtMulHighI16x8S)
+            }
+            OpCode::SimdExtI32x4ExtMulLowI16x8Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4ExtMulLowI16x8U)
+            }
+            OpCode::SimdExtI32x4ExtMulHighI16x8Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4ExtMulHighI16x8U)
+            }
+            OpCode::SimdExtI64x2Abs => Ok(wasm_encoder::Instruction::I64x2Abs),
+            OpCode::SimdExtI64x2Neg => Ok(wasm_encoder::Instruction::I64x2Neg),
+            OpCode::SimdExtI64x2AllTrue => Ok(wasm_encoder::Instruction::I64x2AllTrue),
+            OpCode::SimdExtI64x2Bitmask => Ok(wasm_encoder::Instruction::I64x2Bitmask),
+            OpCode::SimdExtI64x2ExtendLowI32x4Signed => {
+                Ok(wasm_encoder::Instruction::I64x2ExtendLowI32x4S)
+            }
+            OpCode::SimdExtI64x2ExtendHighI32x4Signed => {
+                Ok(wasm_encoder::Instruction::I64x2ExtendHighI32x4S)
+            }
+            OpCode::SimdExtI64x2ExtendLowI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I64x2ExtendLowI32x4U)
+            }
+            OpCode::SimdExtI64x2ExtendHighI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I64x2ExtendHighI32x4U)
+            }
+            OpCode::SimdExtI64x2Shl => Ok(wasm_encoder::Instruction::I64x2Shl),
+            OpCode::SimdExtI64x2ShrSigned => Ok(wasm_encoder::Instruction::I64x2ShrS),
+            OpCode::SimdExtI64x2ShrUnsigned => Ok(wasm_encoder::Instruction::I64x2ShrU),
+            OpCode::SimdExtI64x2Add => Ok(wasm_encoder::Instruction::I64x2Add),
+            OpCode::SimdExtI64x2Sub => Ok(wasm_encoder::Instruction::I64x2Sub),
+            OpCode::SimdExtI64x2Mul => Ok(wasm_encoder::Instruction::I64x2Mul),
+            OpCode::SimdExtI64x2ExtMulLowI32x4Signed => {
+                Ok(wasm_encoder::Instruction::I64x2ExtMulLowI32x4S)
+            }
+            OpCode::SimdExtI64x2ExtMulHighI32x4Signed => {
+                Ok(wasm_encoder::Instruction::I64x2ExtMulHighI32x4S)
+            }
+            OpCode::SimdExtI64x2ExtMulLowI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I64x2ExtMulLowI32x4U)
+            }
+            OpCode::SimdExtI64x2ExtMulHighI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I64x2ExtMulHighI32x4U)
+            }
+            OpCode::SimdExtF32x4Ceil => Ok(wasm_encoder::Instruction::F32x4Ceil),
+            OpCode::SimdExtF32x4Floor => Ok(wasm_encoder::Instruction::F32x4Floor),
+            OpCode::SimdExtF32x4Trunc => Ok(wasm_encoder::Instruction::F32x4Trunc),
+            OpCode::SimdExtF32x4Nearest => Ok(wasm_encoder::Instruction::F32x4Nearest),
+            OpCode::SimdExtF32x4Abs => Ok(wasm_encoder::Instruction::F32x4Abs),
+            OpCode::SimdExtF32x4Neg => Ok(wasm_encoder::Instruction::F32x4Neg),
+            OpCode::SimdExtF32x4Sqrt => Ok(wasm_encoder::Instruction::F32x4Sqrt),
+            OpCode::SimdExtF32x4Add => Ok(wasm_encoder::Instruction::F32x4Add),
+            OpCode::SimdExtF32x4Sub => Ok(wasm_encoder::Instruction::F32x4Sub),
+            OpCode::SimdExtF32x4Mul => Ok(wasm_encoder::Instruction::F32x4Mul),
+            OpCode::SimdExtF32x4Div => Ok(wasm_encoder::Instruction::F32x4Div),
+            OpCode::SimdExtF32x4Min => Ok(wasm_encoder::Instruction::F32x4Min),
+            OpCode::SimdExtF32x4Max => Ok(wasm_encoder::Instruction::F32x4Max),
+            OpCode::SimdExtF32x4Pmin => Ok(wasm_encoder::Instruction::F32x4PMin),
+            OpCode::SimdExtF32x4Pmax => Ok(wasm_encoder::Instruction::F32x4PMax),
+            OpCode::SimdExtF64x2Ceil => Ok(wasm_encoder::Instruction::F64x2Ceil),
+            OpCode::SimdExtF64x2Floor => Ok(wasm_encoder::Instruction::F64x2Floor),
+            OpCode::SimdExtF64x2Trunc => Ok(wasm_encoder::Instruction::F64x2Trunc),
+            OpCode::SimdExtF64x2Nearest => Ok(wasm_encoder::Instruction::F64x2Nearest),
+            OpCode::SimdExtF64x2Abs => Ok(wasm_encoder::Instruction::F64x2Abs),
+            OpCode::SimdExtF64x2Neg => Ok(wasm_encoder::Instruction::F64x2Neg),
+            OpCode::SimdExtF64x2Sqrt => Ok(wasm_encoder::Instruction::F64x2Sqrt),
+            OpCode::SimdExtF64x2Add => Ok(wasm_encoder::Instruction::F64x2Add),
+            OpCode::SimdExtF64x2Sub => Ok(wasm_encoder::Instruction::F64x2Sub),
+            OpCode::SimdExtF64x2Mul => Ok(wasm_encoder::Instruction::F64x2Mul),
+            OpCode::SimdExtF64x2Div => Ok(wasm_encoder::Instruction::F64x2Div),
+            OpCode::SimdExtF64x2Min => Ok(wasm_encoder::Instruction::F64x2Min),
+            OpCode::SimdExtF64x2Max => Ok(wasm_encoder::Instruction::F64x2Max),
+            OpCode::SimdExtF64x2Pmin => Ok(wasm_encoder::Instruction::F64x2PMin),
+            OpCode::SimdExtF64x2Pmax => Ok(wasm_encoder::Instruction::F64x2PMax),
+            OpCode::SimdExtI32x4TruncSatF32x4Signed => {
+                Ok(wasm_encoder::Instruction::I32x4TruncSatF32x4S)
+            }
+            OpCode::SimdExtI32x4TruncSatF32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4TruncSatF32x4U)
+            }
+            OpCode::SimdExtF32x4ConvertI32x4Signed => {
+                Ok(wasm_encoder::Instruction::F32x4ConvertI32x4S)
+            }
+            OpCode::SimdExtF32x4ConvertI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::F32x4ConvertI32x4U)
+            }
+            OpCode::SimdExtI32x4TruncSatF64x2SignedZero => {
+                Ok(wasm_encoder::Instruction::I32x4TruncSatF64x2SZero)
+            }
+            OpCode::SimdExtI32x4TruncSatF64x2UnsignedZero => {
+                Ok(wasm_encoder::Instruction::I32x4TruncSatF64x2UZero)
+            }
+            OpCode::SimdExtF64x2ConvertLowI32x4Signed => {
+                Ok(wasm_encoder::Instruction::F64x2ConvertLowI32x4S)
+            }
+            OpCode::SimdExtF64x2ConvertLowI32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::F64x2ConvertLowI32x4U)
+            }
+            OpCode::SimdExtF32x4DemoteF64x2Zero => {
+                Ok(wasm_encoder::Instruction::F32x4DemoteF64x2Zero)
+            }
+            OpCode::SimdExtF64x2PromoteLowF32x4 => {
+                Ok(wasm_encoder::Instruction::F64x2PromoteLowF32x4)
+            }
+            OpCode::RelaxedSimdExtI8x16RelaxedSwizzle => {
+                Ok(wasm_encoder::Instruction::I8x16RelaxedSwizzle)
+            }
+            OpCode::RelaxedSimdExtI32x4RelaxedTruncF32x4Signed => {
+                Ok(wasm_encoder::Instruction::I32x4RelaxedTruncF32x4S)
+            }
+            OpCode::RelaxedSimdExtI32x4RelaxedTruncF32x4Unsigned => {
+                Ok(wasm_encoder::Instruction::I32x4RelaxedTruncF32x4U)
+            }
+            OpCode::RelaxedSimdExtI32x4RelaxedTruncF64x2SignedZero => {
+                Ok(wasm_encoder::Instruction::I32x4RelaxedTruncF64x2SZero)
+            }
+            OpCode::RelaxedSimdExtI32x4RelaxedTruncF64x2UnsignedZero => {
+                Ok(wasm_encoder::Instruction::I32x4RelaxedTruncF64x2UZero)
+            }
+            OpCode::RelaxedSimdExtF32x4RelaxedMadd => {
+                Ok(wasm_encoder::Instruction::F32x4RelaxedMadd)
+            }
+            OpCode::RelaxedSimdExtF32x4RelaxedNmadd => {
+                Ok(wasm_encoder::Instruction::F32x4RelaxedNmadd)
+            }
+            OpCode::RelaxedSimdExtF64x2RelaxedMadd => {
+                Ok(wasm_encoder::Instruction::F64x2RelaxedMadd)
+            }
+            OpCode::RelaxedSimdExtF64x2RelaxedNmadd => {
+                Ok(wasm_encoder::Instruction::F64x2RelaxedNmadd)
+            }
+            OpCode::RelaxedSimdExtI8x16RelaxedLaneselect => {
+                Ok(wasm_encoder::Instruction::I8x16RelaxedLaneselect)
+            }
+            OpCode::RelaxedSimdExtI16x8RelaxedLaneselect => {
+                Ok(wasm_encoder::Instruction::I16x8RelaxedLaneselect)
+            }
+            OpCode::RelaxedSimdExtI32x4RelaxedLaneselect => {
+                Ok(wasm_encoder::Instruction::I32x4RelaxedLaneselect)
+            }
+            OpCode::RelaxedSimdExtI64x2RelaxedLaneselect => {
+                Ok(wasm_encoder::Instruction::I64x2RelaxedLaneselect)
+            }
+            OpCode::RelaxedSimdExtF32x4RelaxedMin => Ok(wasm_encoder::Instruction::F32x4RelaxedMin),
+            OpCode::RelaxedSimdExtF32x4RelaxedMax => Ok(wasm_encoder::Instruction::F32x4RelaxedMax),
+            OpCode::RelaxedSimdExtF64x2RelaxedMin => Ok(wasm_encoder::Instruction::F64x2RelaxedMin),
+            OpCode::RelaxedSimdExtF64x2RelaxedMax => Ok(wasm_encoder::Instruction::F64x2RelaxedMax),
+            OpCode::RelaxedSimdExtI16x8RelaxedQ15mulrSigned => {
+                Ok(wasm_encoder::Instruction::I16x8RelaxedQ15mulrS)
+            }
+            OpCode::RelaxedSimdExtI16x8RelaxedDotI8x16I7x16Signed => {
+                Ok(wasm_encoder::Instruction::I16x8RelaxedDotI8x16I7x16S)
+            }
+            OpCode::RelaxedSimdExtI32x4RelaxedDotI8x16I7x16AddSigned => {
+                Ok(wasm_encoder::Instruction::I32x4RelaxedDotI8x16I7x16AddS)
+            }
+            OpCode::ThreadsExtMemoryAtomicNotify => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryAtomicNotify operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "MemoryAtomicNotify",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryAtomicNotify(memarg))
+            }
+            OpCode::ThreadsExtMemoryAtomicWait32 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryAtomicWait32 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "MemoryAtomicWait32",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryAtomicWait32(memarg))
+            }
+            OpCode::ThreadsExtMemoryAtomicWait64 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("MemoryAtomicWait64 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "MemoryAtomicWait64",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::MemoryAtomicWait64(memarg))
+            }
+            OpCode::ThreadsExtAtomicFence => Ok(wasm_encoder::Instruction::AtomicFence),
+            OpCode::ThreadsExtI32AtomicLoad => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicLoad operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicLoad",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicLoad(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicLoad => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicLoad operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicLoad",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicLoad(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicLoad8Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicLoad8U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicLoad8U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicLoad8U(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicLoad16Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicLoad16U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicLoad16U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicLoad16U(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicLoad8Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicLoad8U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicLoad8U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicLoad8U(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicLoad16Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicLoad16U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicLoad16U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicLoad16U(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicLoad32Unsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicLoad32U operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicLoad32U",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicLoad32U(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicStore => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicStore operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicStore",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicStore(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicStore => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicStore operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicStore",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicStore(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicStore8 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicStore8 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicStore8",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicStore8(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicStore16 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicStore16 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicStore16",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicStore16(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicStore8 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicStore8 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicStore8",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicStore8(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicStore16 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicStore16 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicStore16",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicStore16(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicStore32 => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicStore32 operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicStore32",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicStore32(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmwAdd => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmwAdd operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwAdd",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmwAdd(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmwAdd => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmwAdd operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwAdd",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmwAdd(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw8AddUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8AddU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8AddU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8AddU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw16AddUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw16AddU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16AddU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16AddU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw8AddUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw8AddU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8AddU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8AddU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw16AddUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw16AddU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16AddU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16AddU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw32AddUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw32AddU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32AddU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32AddU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmwSub => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmwSub operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwSub",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmwSub(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmwSub => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmwSub operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwSub",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmwSub(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw8SubUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8SubU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8SubU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8SubU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw16SubUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw16SubU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16SubU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16SubU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw8SubUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw8SubU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8SubU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8SubU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw16SubUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw16SubU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16SubU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16SubU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw32SubUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw32SubU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32SubU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32SubU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmwAnd => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmwAnd operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwAnd",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmwAnd(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmwAnd => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmwAnd operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwAnd",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmwAnd(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw8AndUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8AndU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8AndU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8AndU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw16AndUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw16AndU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16AndU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16AndU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw8AndUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw8AndU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8AndU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8AndU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw16AndUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw16AndU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16AndU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16AndU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw32AndUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw32AndU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32AndU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32AndU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmwOr => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmwOr operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwOr",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmwOr(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmwOr => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmwOr operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwOr",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmwOr(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw8OrUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8OrU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8OrU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Load32S(memarg))
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8OrU(memarg))
             }
-            OpCode::I64Load32Unsigned => {
+            OpCode::ThreadsExtI32AtomicRmw16OrUnsigned => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("I64Load32U operator not found"))?
+                    .ok_or(Error::Missing("I32AtomicRmw16OrU operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Load32U operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16OrU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Load32U(memarg))
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16OrU(memarg))
             }
-            OpCode::I32Store => {
+            OpCode::ThreadsExtI64AtomicRmw8OrUnsigned => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("I32Store operator not found"))?
+                    .ok_or(Error::Missing("I64AtomicRmw8OrU operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Store operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8OrU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I32Store(memarg))
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8OrU(memarg))
             }
-            OpCode::I64Store => {
+            OpCode::ThreadsExtI64AtomicRmw16OrUnsigned => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("I64Store operator not found"))?
+                    .ok_or(Error::Missing("I64AtomicRmw16OrU operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Store operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16OrU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Store(memarg))
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16OrU(memarg))
             }
-            OpCode::F32Store => {
+            OpCode::ThreadsExtI64AtomicRmw32OrUnsigned => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("F32Store operator not found"))?
+                    .ok_or(Error::Missing("I64AtomicRmw32OrU operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for F32Store operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32OrU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::F32Store(memarg))
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32OrU(memarg))
             }
-            OpCode::F64Store => {
+            OpCode::ThreadsExtI32AtomicRmwXor => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("F64Store operator not found"))?
+                    .ok_or(Error::Missing("I32AtomicRmwXor operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for F64Store operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwXor",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::F64Store(memarg))
+                Ok(wasm_encoder::Instruction::I32AtomicRmwXor(memarg))
             }
-            OpCode::I32Store8 => {
+            OpCode::ThreadsExtI64AtomicRmwXor => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("I32Store8 operator not found"))?
+                    .ok_or(Error::Missing("I64AtomicRmwXor operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Store8 operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwXor",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I32Store8(memarg))
+                Ok(wasm_encoder::Instruction::I64AtomicRmwXor(memarg))
             }
-            OpCode::I32Store16 => {
+            OpCode::ThreadsExtI32AtomicRmw8XorUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8XorU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8XorU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8XorU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw16XorUnsigned => {
                 let memarg = match operator
                     .operator
-                    .ok_or(anyhow!("I32Store16 operator not found"))?
+                    .ok_or(Error::Missing("I32AtomicRmw16XorU operator"))?
                 {
                     operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I32Store16 operator")),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16XorU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I32Store16(memarg))
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16XorU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw8XorUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw8XorU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8XorU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8XorU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw16XorUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw16XorU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16XorU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16XorU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw32XorUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw32XorU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32XorU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32XorU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmwXchg => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmwXchg operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwXchg",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmwXchg(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmwXchg => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmwXchg operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwXchg",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmwXchg(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw8XchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8XchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8XchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8XchgU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw16XchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw16XchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16XchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16XchgU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw8XchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw8XchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8XchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8XchgU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw16XchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw16XchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16XchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16XchgU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw32XchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw32XchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32XchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32XchgU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmwCmpxchg => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmwCmpxchg operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmwCmpxchg",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmwCmpxchg(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmwCmpxchg => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmwCmpxchg operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmwCmpxchg",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmwCmpxchg(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw8CmpxchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw8CmpxchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw8CmpxchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw8CmpxchgU(memarg))
+            }
+            OpCode::ThreadsExtI32AtomicRmw16CmpxchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I32AtomicRmw16CmpxchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I32AtomicRmw16CmpxchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I32AtomicRmw16CmpxchgU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw8CmpxchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw8CmpxchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw8CmpxchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw8CmpxchgU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw16CmpxchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw16CmpxchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw16CmpxchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw16CmpxchgU(memarg))
+            }
+            OpCode::ThreadsExtI64AtomicRmw32CmpxchgUnsigned => {
+                let memarg = match operator
+                    .operator
+                    .ok_or(Error::Missing("I64AtomicRmw32CmpxchgU operator"))?
+                {
+                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "MemArg",
+                            operator: "I64AtomicRmw32CmpxchgU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::I64AtomicRmw32CmpxchgU(memarg))
+            }
+            OpCode::ReferenceTypesExtTypedSelect => {
+                let ty = match operator
+                    .operator
+                    .ok_or(Error::Missing("TypedSelect operator"))?
+                {
+                    operator::Operator::SelectType(ty) => wasm_encoder::ValType::try_from(ty)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "SelectType",
+                            operator: "TypedSelect",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TypedSelect(ty))
+            }
+            OpCode::ReferenceTypesExtRefNull => {
+                let heap_type = match operator
+                    .operator
+                    .ok_or(Error::Missing("RefNull operator"))?
+                {
+                    operator::Operator::HeapType(ht) => wasm_encoder::HeapType::try_from(ht)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "HeapType",
+                            operator: "RefNull",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::RefNull(heap_type))
+            }
+            OpCode::ReferenceTypesExtRefIsNull => Ok(wasm_encoder::Instruction::RefIsNull),
+            OpCode::ReferenceTypesExtRefFunc => {
+                let function_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("RefFunc operator"))?
+                {
+                    operator::Operator::FunctionIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "FunctionIndex",
+                            operator: "RefFunc",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::RefFunc(function_index))
+            }
+            OpCode::ReferenceTypesExtTableFill => {
+                let table = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableFill operator"))?
+                {
+                    operator::Operator::TableIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableIndex",
+                            operator: "TableFill",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableFill(table))
+            }
+            OpCode::ReferenceTypesExtTableGet => {
+                let table = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableGet operator"))?
+                {
+                    operator::Operator::TableIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableIndex",
+                            operator: "TableGet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableGet(table))
+            }
+            OpCode::ReferenceTypesExtTableSet => {
+                let table = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableSet operator"))?
+                {
+                    operator::Operator::TableIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableIndex",
+                            operator: "TableSet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableSet(table))
+            }
+            OpCode::ReferenceTypesExtTableGrow => {
+                let table = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableGrow operator"))?
+                {
+                    operator::Operator::TableIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableIndex",
+                            operator: "TableGrow",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableGrow(table))
+            }
+            OpCode::ReferenceTypesExtTableSize => {
+                let table = match operator
+                    .operator
+                    .ok_or(Error::Missing("TableSize operator"))?
+                {
+                    operator::Operator::TableIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TableIndex",
+                            operator: "TableSize",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::TableSize(table))
+            }
+            OpCode::TailCallExtReturnCall => {
+                let function_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("ReturnCall operator"))?
+                {
+                    operator::Operator::FunctionIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "FunctionIndex",
+                            operator: "ReturnCall",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::ReturnCall(function_index))
+            }
+            OpCode::TailCallExtReturnCallIndirect => {
+                let (type_index, table_index) = match operator
+                    .operator
+                    .ok_or(Error::Missing("ReturnCallIndirect operator"))?
+                {
+                    operator::Operator::CallIndirect(ci) => {
+                        let type_index = ci
+                            .type_index
+                            .ok_or(Error::Missing("ReturnCallIndirect type_index"))?;
+                        let table_index = ci
+                            .table_index
+                            .ok_or(Error::Missing("ReturnCallIndirect table_index"))?;
+                        (type_index, table_index)
+                    }
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "CallIndirectOp",
+                            operator: "ReturnCallIndirect",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::ReturnCallIndirect {
+                    type_index,
+                    table_index,
+                })
+            }
+            OpCode::TailCallExtReturnCallRef => {
+                let type_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("ReturnCallRef operator"))?
+                {
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ReturnCallRef",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::ReturnCallRef(type_index))
+            }
+            OpCode::GcExtRefEq => Ok(wasm_encoder::Instruction::RefEq),
+            OpCode::GcExtStructNew => {
+                let type_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("StructNew operator"))?
+                {
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "StructNew",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::StructNew(type_index))
+            }
+            OpCode::GcExtStructNewDefault => {
+                let type_index = match operator
+                    .operator
+                    .ok_or(Error::Missing("StructNewDefault operator"))?
+                {
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "StructNewDefault",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::StructNewDefault(type_index))
+            }
+            OpCode::GcExtStructGet => {
+                let (struct_type_index, field_index) = match operator
+                    .operator
+                    .ok_or(Error::Missing("StructGet operator"))?
+                {
+                    operator::Operator::StructField(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("StructGet type_index"))?,
+                        op.field_index
+                            .ok_or(Error::Missing("StructGet field_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "StructFieldOp",
+                            operator: "StructGet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::StructGet {
+                    struct_type_index,
+                    field_index,
+                })
+            }
+            OpCode::GcExtStructGetSigned => {
+                let (struct_type_index, field_index) = match operator
+                    .operator
+                    .ok_or(Error::Missing("StructGetS operator"))?
+                {
+                    operator::Operator::StructField(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("StructGetS type_index"))?,
+                        op.field_index
+                            .ok_or(Error::Missing("StructGetS field_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "StructFieldOp",
+                            operator: "StructGetS",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::StructGetS {
+                    struct_type_index,
+                    field_index,
+                })
+            }
+            OpCode::GcExtStructGetUnsigned => {
+                let (struct_type_index, field_index) = match operator
+                    .operator
+                    .ok_or(Error::Missing("StructGetU operator"))?
+                {
+                    operator::Operator::StructField(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("StructGetU type_index"))?,
+                        op.field_index
+                            .ok_or(Error::Missing("StructGetU field_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "StructFieldOp",
+                            operator: "StructGetU",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::StructGetU {
+                    struct_type_index,
+                    field_index,
+                })
+            }
+            OpCode::GcExtStructSet => {
+                let (struct_type_index, field_index) = match operator
+                    .operator
+                    .ok_or(Error::Missing("StructSet operator"))?
+                {
+                    operator::Operator::StructField(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("StructSet type_index"))?,
+                        op.field_index
+                            .ok_or(Error::Missing("StructSet field_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "StructFieldOp",
+                            operator: "StructSet",
+                        });
+                    }
+                };
+                Ok(wasm_encoder::Instruction::StructSet {
+                    struct_type_index,
+                    field_index,
+                })
             }
-            OpCode::I64Store8 => {
-                let memarg = match operator
+            OpCode::GcExtArrayNew => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("I64Store8 operator not found"))?
+                    .ok_or(Error::Missing("ArrayNew operator"))?
                 {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Store8 operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArrayNew",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Store8(memarg))
+                Ok(wasm_encoder::Instruction::ArrayNew(type_index))
             }
-            OpCode::I64Store16 => {
-                let memarg = match operator
+            OpCode::GcExtArrayNewDefault => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("I64Store16 operator not found"))?
+                    .ok_or(Error::Missing("ArrayNewDefault operator"))?
                 {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Store16 operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArrayNewDefault",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Store16(memarg))
+                Ok(wasm_encoder::Instruction::ArrayNewDefault(type_index))
             }
-            OpCode::I64Store32 => {
-                let memarg = match operator
+            OpCode::GcExtArrayNewFixed => {
+                let (array_type_index, array_size) = match operator
                     .operator
-                    .ok_or(anyhow!("I64Store32 operator not found"))?
+                    .ok_or(Error::Missing("ArrayNewFixed operator"))?
                 {
-                    operator::Operator::Memarg(ma) => wasm_encoder::MemArg::try_from(ma)?,
-                    _ => return Err(anyhow!("Expected MemArg for I64Store32 operator")),
+                    operator::Operator::ArrayNewFixed(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("ArrayNewFixed type_index"))?,
+                        op.size.ok_or(Error::Missing("ArrayNewFixed size"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ArrayNewFixedOp",
+                            operator: "ArrayNewFixed",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Store32(memarg))
+                Ok(wasm_encoder::Instruction::ArrayNewFixed {
+                    array_type_index,
+                    array_size,
+                })
             }
-            OpCode::MemorySize => {
-                let mem = match operator
+            OpCode::GcExtArrayNewData => {
+                let (array_type_index, array_data_index) = match operator
                     .operator
-                    .ok_or(anyhow!("MemorySize operator not found"))?
+                    .ok_or(Error::Missing("ArrayNewData operator"))?
                 {
-                    operator::Operator::Mem(m) => m,
-                    _ => return Err(anyhow!("Expected Mem for MemorySize operator")),
+                    operator::Operator::ArrayData(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("ArrayNewData type_index"))?,
+                        op.data_index
+                            .ok_or(Error::Missing("ArrayNewData data_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ArrayDataOp",
+                            operator: "ArrayNewData",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::MemorySize(mem))
+                Ok(wasm_encoder::Instruction::ArrayNewData {
+                    array_type_index,
+                    array_data_index,
+                })
             }
-            OpCode::MemoryGrow => {
-                let mem = match operator
+            OpCode::GcExtArrayNewElem => {
+                let (array_type_index, array_elem_index) = match operator
                     .operator
-                    .ok_or(anyhow!("MemoryGrow operator not found"))?
+                    .ok_or(Error::Missing("ArrayNewElem operator"))?
                 {
-                    operator::Operator::Mem(m) => m,
-                    _ => return Err(anyhow!("Expected Mem for MemoryGrow operator")),
+                    operator::Operator::ArrayElem(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("ArrayNewElem type_index"))?,
+                        op.element_index
+                            .ok_or(Error::Missing("ArrayNewElem element_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ArrayElemOp",
+                            operator: "ArrayNewElem",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::MemoryGrow(mem))
+                Ok(wasm_encoder::Instruction::ArrayNewElem {
+                    array_type_index,
+                    array_elem_index,
+                })
             }
-            OpCode::I32Constant => {
-                let value = match operator
+            OpCode::GcExtArrayGet => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("I32Const operator not found"))?
+                    .ok_or(Error::Missing("ArrayGet operator"))?
                 {
-                    operator::Operator::I32Value(v) => v,
-                    _ => return Err(anyhow!("Expected I32value for I32Const operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArrayGet",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I32Const(value))
+                Ok(wasm_encoder::Instruction::ArrayGet(type_index))
             }
-            OpCode::I64Constant => {
-                let value = match operator
+            OpCode::GcExtArrayGetSigned => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("I64Const operator not found"))?
+                    .ok_or(Error::Missing("ArrayGetS operator"))?
                 {
-                    operator::Operator::I64Value(v) => v,
-                    _ => return Err(anyhow!("Expected I64value for I64Const operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArrayGetS",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::I64Const(value))
+                Ok(wasm_encoder::Instruction::ArrayGetS(type_index))
             }
-            OpCode::F32Constant => {
-                let value = match operator
+            OpCode::GcExtArrayGetUnsigned => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("F32Const operator not found"))?
+                    .ok_or(Error::Missing("ArrayGetU operator"))?
                 {
-                    operator::Operator::F32Value(bits) => wasm_encoder::Ieee32::new(bits),
-                    _ => return Err(anyhow!("Expected F32value for F32Const operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArrayGetU",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::F32Const(value))
+                Ok(wasm_encoder::Instruction::ArrayGetU(type_index))
             }
-            OpCode::F64Constant => {
-                let value = match operator
+            OpCode::GcExtArraySet => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("F64Const operator not found"))?
+                    .ok_or(Error::Missing("ArraySet operator"))?
                 {
-                    operator::Operator::F64Value(bits) => wasm_encoder::Ieee64::new(bits),
-                    _ => return Err(anyhow!("Expected F64value for F64Const operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArraySet",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::F64Const(value))
-            }
-            OpCode::I32Eqz => Ok(wasm_encoder::Instruction::I32Eqz),
-            OpCode::I32Eq => Ok(wasm_encoder::Instruction::I32Eq),
-            OpCode::I32Ne => Ok(wasm_encoder::Instruction::I32Ne),
-            OpCode::I32LtSigned => Ok(wasm_encoder::Instruction::I32LtS),
-            OpCode::I32LtUnsigned => Ok(wasm_encoder::Instruction::I32LtU),
-            OpCode::I32GtSigned => Ok(wasm_encoder::Instruction::I32GtS),
-            OpCode::I32GtUnsigned => Ok(wasm_encoder::Instruction::I32GtU),
-            OpCode::I32LeSigned => Ok(wasm_encoder::Instruction::I32LeS),
-            OpCode::I32LeUnsigned => Ok(wasm_encoder::Instruction::I32LeU),
-            OpCode::I32GeSigned => Ok(wasm_encoder::Instruction::I32GeS),
-            OpCode::I32GeUnsigned => Ok(wasm_encoder::Instruction::I32GeU),
-            OpCode::I64Eqz => Ok(wasm_encoder::Instruction::I64Eqz),
-            OpCode::I64Eq => Ok(wasm_encoder::Instruction::I64Eq),
-            OpCode::I64Ne => Ok(wasm_encoder::Instruction::I64Ne),
-            OpCode::I64LtSigned => Ok(wasm_encoder::Instruction::I64LtS),
-            OpCode::I64LtUnsigned => Ok(wasm_encoder::Instruction::I64LtU),
-            OpCode::I64GtSigned => Ok(wasm_encoder::Instruction::I64GtS),
-            OpCode::I64GtUnsigned => Ok(wasm_encoder::Instruction::I64GtU),
-            OpCode::I64LeSigned => Ok(wasm_encoder::Instruction::I64LeS),
-            OpCode::I64LeUnsigned => Ok(wasm_encoder::Instruction::I64LeU),
-            OpCode::I64GeSigned => Ok(wasm_encoder::Instruction::I64GeS),
-            OpCode::I64GeUnsigned => Ok(wasm_encoder::Instruction::I64GeU),
-            OpCode::F32Eq => Ok(wasm_encoder::Instruction::F32Eq),
-            OpCode::F32Ne => Ok(wasm_encoder::Instruction::F32Ne),
-            OpCode::F32Lt => Ok(wasm_encoder::Instruction::F32Lt),
-            OpCode::F32Gt => Ok(wasm_encoder::Instruction::F32Gt),
-            OpCode::F32Le => Ok(wasm_encoder::Instruction::F32Le),
-            OpCode::F32Ge => Ok(wasm_encoder::Instruction::F32Ge),
-            OpCode::F64Eq => Ok(wasm_encoder::Instruction::F64Eq),
-            OpCode::F64Ne => Ok(wasm_encoder::Instruction::F64Ne),
-            OpCode::F64Lt => Ok(wasm_encoder::Instruction::F64Lt),
-            OpCode::F64Gt => Ok(wasm_encoder::Instruction::F64Gt),
-            OpCode::F64Le => Ok(wasm_encoder::Instruction::F64Le),
-            OpCode::F64Ge => Ok(wasm_encoder::Instruction::F64Ge),
-            OpCode::I32Clz => Ok(wasm_encoder::Instruction::I32Clz),
-            OpCode::I32Ctz => Ok(wasm_encoder::Instruction::I32Ctz),
-            OpCode::I32Popcnt => Ok(wasm_encoder::Instruction::I32Popcnt),
-            OpCode::I32Add => Ok(wasm_encoder::Instruction::I32Add),
-            OpCode::I32Sub => Ok(wasm_encoder::Instruction::I32Sub),
-            OpCode::I32Mul => Ok(wasm_encoder::Instruction::I32Mul),
-            OpCode::I32DivSigned => Ok(wasm_encoder::Instruction::I32DivS),
-            OpCode::I32DivUnsigned => Ok(wasm_encoder::Instruction::I32DivU),
-            OpCode::I32RemSigned => Ok(wasm_encoder::Instruction::I32RemS),
-            OpCode::I32RemUnsigned => Ok(wasm_encoder::Instruction::I32RemU),
-            OpCode::I32And => Ok(wasm_encoder::Instruction::I32And),
-            OpCode::I32Or => Ok(wasm_encoder::Instruction::I32Or),
-            OpCode::I32Xor => Ok(wasm_encoder::Instruction::I32Xor),
-            OpCode::I32Shl => Ok(wasm_encoder::Instruction::I32Shl),
-            OpCode::I32ShrSigned => Ok(wasm_encoder::Instruction::I32ShrS),
-            OpCode::I32ShrUnsigned => Ok(wasm_encoder::Instruction::I32ShrU),
-            OpCode::I32Rotl => Ok(wasm_encoder::Instruction::I32Rotl),
-            OpCode::I32Rotr => Ok(wasm_encoder::Instruction::I32Rotr),
-            OpCode::I64Clz => Ok(wasm_encoder::Instruction::I64Clz),
-            OpCode::I64Ctz => Ok(wasm_encoder::Instruction::I64Ctz),
-            OpCode::I64Popcnt => Ok(wasm_encoder::Instruction::I64Popcnt),
-            OpCode::I64Add => Ok(wasm_encoder::Instruction::I64Add),
-            OpCode::I64Sub => Ok(wasm_encoder::Instruction::I64Sub),
-            OpCode::I64Mul => Ok(wasm_encoder::Instruction::I64Mul),
-            OpCode::I64DivSigned => Ok(wasm_encoder::Instruction::I64DivS),
-            OpCode::I64DivUnsigned => Ok(wasm_encoder::Instruction::I64DivU),
-            OpCode::I64RemSigned => Ok(wasm_encoder::Instruction::I64RemS),
-            OpCode::I64RemUnsigned => Ok(wasm_encoder::Instruction::I64RemU),
-            OpCode::I64And => Ok(wasm_encoder::Instruction::I64And),
-            OpCode::I64Or => Ok(wasm_encoder::Instruction::I64Or),
-            OpCode::I64Xor => Ok(wasm_encoder::Instruction::I64Xor),
-            OpCode::I64Shl => Ok(wasm_encoder::Instruction::I64Shl),
-            OpCode::I64ShrSigned => Ok(wasm_encoder::Instruction::I64ShrS),
-            OpCode::I64ShrUnsigned => Ok(wasm_encoder::Instruction::I64ShrU),
-            OpCode::I64Rotl => Ok(wasm_encoder::Instruction::I64Rotl),
-            OpCode::I64Rotr => Ok(wasm_encoder::Instruction::I64Rotr),
-            OpCode::F32Abs => Ok(wasm_encoder::Instruction::F32Abs),
-            OpCode::F32Neg => Ok(wasm_encoder::Instruction::F32Neg),
-            OpCode::F32Ceil => Ok(wasm_encoder::Instruction::F32Ceil),
-            OpCode::F32Floor => Ok(wasm_encoder::Instruction::F32Floor),
-            OpCode::F32Trunc => Ok(wasm_encoder::Instruction::F32Trunc),
-            OpCode::F32Nearest => Ok(wasm_encoder::Instruction::F32Nearest),
-            OpCode::F32Sqrt => Ok(wasm_encoder::Instruction::F32Sqrt),
-            OpCode::F32Add => Ok(wasm_encoder::Instruction::F32Add),
-            OpCode::F32Sub => Ok(wasm_encoder::Instruction::F32Sub),
-            OpCode::F32Mul => Ok(wasm_encoder::Instruction::F32Mul),
-            OpCode::F32Div => Ok(wasm_encoder::Instruction::F32Div),
-            OpCode::F32Min => Ok(wasm_encoder::Instruction::F32Min),
-            OpCode::F32Max => Ok(wasm_encoder::Instruction::F32Max),
-            OpCode::F32Copysign => Ok(wasm_encoder::Instruction::F32Copysign),
-            OpCode::F64Abs => Ok(wasm_encoder::Instruction::F64Abs),
-            OpCode::F64Neg => Ok(wasm_encoder::Instruction::F64Neg),
-            OpCode::F64Ceil => Ok(wasm_encoder::Instruction::F64Ceil),
-            OpCode::F64Floor => Ok(wasm_encoder::Instruction::F64Floor),
-            OpCode::F64Trunc => Ok(wasm_encoder::Instruction::F64Trunc),
-            OpCode::F64Nearest => Ok(wasm_encoder::Instruction::F64Nearest),
-            OpCode::F64Sqrt => Ok(wasm_encoder::Instruction::F64Sqrt),
-            OpCode::F64Add => Ok(wasm_encoder::Instruction::F64Add),
-            OpCode::F64Sub => Ok(wasm_encoder::Instruction::F64Sub),
-            OpCode::F64Mul => Ok(wasm_encoder::Instruction::F64Mul),
-            OpCode::F64Div => Ok(wasm_encoder::Instruction::F64Div),
-            OpCode::F64Min => Ok(wasm_encoder::Instruction::F64Min),
-            OpCode::F64Max => Ok(wasm_encoder::Instruction::F64Max),
-            OpCode::F64Copysign => Ok(wasm_encoder::Instruction::F64Copysign),
-            OpCode::I32WrapI64 => Ok(wasm_encoder::Instruction::I32WrapI64),
-            OpCode::I32TruncF32Signed => Ok(wasm_encoder::Instruction::I32TruncF32S),
-            OpCode::I32TruncF32Unsigned => Ok(wasm_encoder::Instruction::I32TruncF32U),
-            OpCode::I32TruncF64Signed => Ok(wasm_encoder::Instruction::I32TruncF64S),
-            OpCode::I32TruncF64Unsigned => Ok(wasm_encoder::Instruction::I32TruncF64U),
-            OpCode::I64ExtendI32Signed => Ok(wasm_encoder::Instruction::I64ExtendI32S),
-            OpCode::I64ExtendI32Unsigned => Ok(wasm_encoder::Instruction::I64ExtendI32U),
-            OpCode::I64TruncF32Signed => Ok(wasm_encoder::Instruction::I64TruncF32S),
-            OpCode::I64TruncF32Unsigned => Ok(wasm_encoder::Instruction::I64TruncF32U),
-            OpCode::I64TruncF64Signed => Ok(wasm_encoder::Instruction::I64TruncF64S),
-            OpCode::I64TruncF64Unsigned => Ok(wasm_encoder::Instruction::I64TruncF64U),
-            OpCode::F32ConvertI32Signed => Ok(wasm_encoder::Instruction::F32ConvertI32S),
-            OpCode::F32ConvertI32Unsigned => Ok(wasm_encoder::Instruction::F32ConvertI32U),
-            OpCode::F32ConvertI64Signed => Ok(wasm_encoder::Instruction::F32ConvertI64S),
-            OpCode::F32ConvertI64Unsigned => Ok(wasm_encoder::Instruction::F32ConvertI64U),
-            OpCode::F32DemoteF64 => Ok(wasm_encoder::Instruction::F32DemoteF64),
-            OpCode::F64ConvertI32Signed => Ok(wasm_encoder::Instruction::F64ConvertI32S),
-            OpCode::F64ConvertI32Unsigned => Ok(wasm_encoder::Instruction::F64ConvertI32U),
-            OpCode::F64ConvertI64Signed => Ok(wasm_encoder::Instruction::F64ConvertI64S),
-            OpCode::F64ConvertI64Unsigned => Ok(wasm_encoder::Instruction::F64ConvertI64U),
-            OpCode::F64PromoteF32 => Ok(wasm_encoder::Instruction::F64PromoteF32),
-            OpCode::I32ReinterpretF32 => Ok(wasm_encoder::Instruction::I32ReinterpretF32),
-            OpCode::I64ReinterpretF64 => Ok(wasm_encoder::Instruction::I64ReinterpretF64),
-            OpCode::F32ReinterpretI32 => Ok(wasm_encoder::Instruction::F32ReinterpretI32),
-            OpCode::F64ReinterpretI64 => Ok(wasm_encoder::Instruction::F64ReinterpretI64),
-            OpCode::SignExtI32Extend8Signed => Ok(wasm_encoder::Instruction::I32Extend8S),
-            OpCode::SignExtI32Extend16Signed => Ok(wasm_encoder::Instruction::I32Extend16S),
-            OpCode::SignExtI64Extend8Signed => Ok(wasm_encoder::Instruction::I64Extend8S),
-            OpCode::SignExtI64Extend16Signed => Ok(wasm_encoder::Instruction::I64Extend16S),
-            OpCode::SignExtI64Extend32Signed => Ok(wasm_encoder::Instruction::I64Extend32S),
-            OpCode::SaturatingFloatToIntExtI32TruncSatF32Signed => {
-                Ok(wasm_encoder::Instruction::I32TruncSatF32S)
-            }
-            OpCode::SaturatingFloatToIntExtI32TruncSatF32Unsigned => {
-                Ok(wasm_encoder::Instruction::I32TruncSatF32U)
-            }
-            OpCode::SaturatingFloatToIntExtI32TruncSatF64Signed => {
-                Ok(wasm_encoder::Instruction::I32TruncSatF64S)
-            }
-            OpCode::SaturatingFloatToIntExtI32TruncSatF64Unsigned => {
-                Ok(wasm_encoder::Instruction::I32TruncSatF64U)
-            }
-            OpCode::SaturatingFloatToIntExtI64TruncSatF32Signed => {
-                Ok(wasm_encoder::Instruction::I64TruncSatF32S)
-            }
-            OpCode::SaturatingFloatToIntExtI64TruncSatF32Unsigned => {
-                Ok(wasm_encoder::Instruction::I64TruncSatF32U)
-            }
-            OpCode::SaturatingFloatToIntExtI64TruncSatF64Signed => {
-                Ok(wasm_encoder::Instruction::I64TruncSatF64S)
-            }
-            OpCode::SaturatingFloatToIntExtI64TruncSatF64Unsigned => {
-                Ok(wasm_encoder::Instruction::I64TruncSatF64U)
+                Ok(wasm_encoder::Instruction::ArraySet(type_index))
             }
-            OpCode::BulkMemoryExtMemoryInit => {
-                let (data_index, mem) = match operator
+            OpCode::GcExtArrayLen => Ok(wasm_encoder::Instruction::ArrayLen),
+            OpCode::GcExtArrayFill => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("MemoryInit operator not found"))?
+                    .ok_or(Error::Missing("ArrayFill operator"))?
                 {
-                    operator::Operator::MemoryInit(mi) => {
-                        let data_index = mi
-                            .data_index
-                            .ok_or(anyhow!("MemoryInit data_index not found"))?;
-                        let mem = mi.address.ok_or(anyhow!("MemoryInit address not found"))?;
-                        (data_index, mem)
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "ArrayFill",
+                        });
                     }
-                    _ => return Err(anyhow!("Expected MemoryInitOp for MemoryInit operator")),
                 };
-                Ok(wasm_encoder::Instruction::MemoryInit { mem, data_index })
+                Ok(wasm_encoder::Instruction::ArrayFill(type_index))
             }
-            OpCode::BulkMemoryExtDataDrop => {
-                let data_index = match operator
+            OpCode::GcExtArrayCopy => {
+                let (array_type_index_dst, array_type_index_src) = match operator
                     .operator
-                    .ok_or(anyhow!("DataDrop operator not found"))?
+                    .ok_or(Error::Missing("ArrayCopy operator"))?
                 {
-                    operator::Operator::DataIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected DataIndex for DataDrop operator")),
+                    operator::Operator::ArrayCopy(op) => (
+                        op.dst_type_index
+                            .ok_or(Error::Missing("ArrayCopy dst_type_index"))?,
+                        op.src_type_index
+                            .ok_or(Error::Missing("ArrayCopy src_type_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ArrayCopyOp",
+                            operator: "ArrayCopy",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::DataDrop(data_index))
+                Ok(wasm_encoder::Instruction::ArrayCopy {
+                    array_type_index_dst,
+                    array_type_index_src,
+                })
             }
-            OpCode::BulkMemoryExtMemoryCopy => {
-                let (dst_mem, src_mem) = match operator
+            OpCode::GcExtArrayInitData => {
+                let (array_type_index, array_data_index) = match operator
                     .operator
-                    .ok_or(anyhow!("MemoryCopy operator not found"))?
+                    .ok_or(Error::Missing("ArrayInitData operator"))?
                 {
-                    operator::Operator::MemoryCopy(mc) => {
-                        let dst_mem = mc
-                            .destination_address
-                            .ok_or(anyhow!("MemoryCopy destination_address not found"))?;
-                        let src_mem = mc
-                            .source_address
-                            .ok_or(anyhow!("MemoryCopy source_address not found"))?;
-                        (dst_mem, src_mem)
+                    operator::Operator::ArrayData(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("ArrayInitData type_index"))?,
+                        op.data_index
+                            .ok_or(Error::Missing("ArrayInitData data_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ArrayDataOp",
+                            operator: "ArrayInitData",
+                        });
                     }
-                    _ => return Err(anyhow!("Expected MemoryCopyOp for MemoryCopy operator")),
                 };
-                Ok(wasm_encoder::Instruction::MemoryCopy { dst_mem, src_mem })
+                Ok(wasm_encoder::Instruction::ArrayInitData {
+                    array_type_index,
+                    array_data_index,
+                })
             }
-            OpCode::BulkMemoryExtMemoryFill => {
-                let mem = match operator
+            OpCode::GcExtArrayInitElem => {
+                let (array_type_index, array_elem_index) = match operator
                     .operator
-                    .ok_or(anyhow!("MemoryFill operator not found"))?
+                    .ok_or(Error::Missing("ArrayInitElem operator"))?
                 {
-                    operator::Operator::Mem(m) => m,
-                    _ => return Err(anyhow!("Expected Mem for MemoryFill operator")),
+                    operator::Operator::ArrayElem(op) => (
+                        op.type_index
+                            .ok_or(Error::Missing("ArrayInitElem type_index"))?,
+                        op.element_index
+                            .ok_or(Error::Missing("ArrayInitElem element_index"))?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "ArrayElemOp",
+                            operator: "ArrayInitElem",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::MemoryFill(mem))
+                Ok(wasm_encoder::Instruction::ArrayInitElem {
+                    array_type_index,
+                    array_elem_index,
+                })
             }
-            OpCode::BulkMemoryExtTableInit => {
-                let (elem_index, table) = match operator
+            OpCode::GcExtRefTestNonNull => {
+                let heap_type = match operator
                     .operator
-                    .ok_or(anyhow!("TableInit operator not found"))?
+                    .ok_or(Error::Missing("RefTestNonNull operator"))?
                 {
-                    operator::Operator::TableInit(ti) => {
-                        let elem_index = ti
-                            .element_index
-                            .ok_or(anyhow!("TableInit elem_index not found"))?;
-                        let table = ti.table.ok_or(anyhow!("TableInit table not found"))?;
-                        (elem_index, table)
+                    operator::Operator::HeapType(ht) => wasm_encoder::HeapType::try_from(ht)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "HeapType",
+                            operator: "RefTestNonNull",
+                        });
                     }
-                    _ => return Err(anyhow!("Expected TableInitOp for TableInit operator")),
                 };
-                Ok(wasm_encoder::Instruction::TableInit { table, elem_index })
+                Ok(wasm_encoder::Instruction::RefTestNonNull(heap_type))
             }
-            OpCode::BulkMemoryExtElemDrop => {
-                let elem_index = match operator
+            OpCode::GcExtRefTestNullable => {
+                let heap_type = match operator
                     .operator
-                    .ok_or(anyhow!("ElemDrop operator not found"))?
+                    .ok_or(Error::Missing("RefTestNullable operator"))?
                 {
-                    operator::Operator::ElementIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected ElemIndex for ElemDrop operator")),
+                    operator::Operator::HeapType(ht) => wasm_encoder::HeapType::try_from(ht)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "HeapType",
+                            operator: "RefTestNullable",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::ElemDrop(elem_index))
+                Ok(wasm_encoder::Instruction::RefTestNullable(heap_type))
             }
-            OpCode::BulkMemoryExtTableCopy => {
-                let (dst_table, src_table) = match operator
+            OpCode::GcExtRefCastNonNull => {
+                let heap_type = match operator
                     .operator
-                    .ok_or(anyhow!("TableCopy operator not found"))?
+                    .ok_or(Error::Missing("RefCastNonNull operator"))?
                 {
-                    operator::Operator::TableCopy(tc) => {
-                        let dst_table = tc
-                            .dst_table
-                            .ok_or(anyhow!("TableCopy dst_table not found"))?;
-                        let src_table = tc
-                            .src_table
-                            .ok_or(anyhow!("TableCopy src_table not found"))?;
-                        (dst_table, src_table)
+                    operator::Operator::HeapType(ht) => wasm_encoder::HeapType::try_from(ht)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "HeapType",
+                            operator: "RefCastNonNull",
+                        });
                     }
-                    _ => return Err(anyhow!("Expected TableCopyOp for TableCopy operator")),
                 };
-                Ok(wasm_encoder::Instruction::TableCopy {
-                    dst_table,
-                    src_table,
-                })
+                Ok(wasm_encoder::Instruction::RefCastNonNull(heap_type))
             }
-            OpCode::ExceptionsExtTryTable => {
-                let ty = match operator
+            OpCode::GcExtRefCastNullable => {
+                let heap_type = match operator
                     .operator
-                    .ok_or(anyhow!("TryTable operator not found"))?
+                    .ok_or(Error::Missing("RefCastNullable operator"))?
                 {
-                    operator::Operator::TryTable(tt) => {
-                        tt.r#type.ok_or(anyhow!("TryTable type not found"))?
+                    operator::Operator::HeapType(ht) => wasm_encoder::HeapType::try_from(ht)?,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "HeapType",
+                            operator: "RefCastNullable",
+                        });
                     }
-                    _ => return Err(anyhow!("Expected TryTableOp for TryTable operator")),
-                };
-                let catches: Vec<wasm_encoder::Catch> = Vec::new();
-                Ok(wasm_encoder::Instruction::TryTable(
-                    wasm_encoder::BlockType::try_from(ty)?,
-                    catches.into(),
-                ))
+                };
+                Ok(wasm_encoder::Instruction::RefCastNullable(heap_type))
             }
-            OpCode::ExceptionsExtThrow => {
-                let tag_index = match operator
+            OpCode::GcExtAnyConvertExtern => Ok(wasm_encoder::Instruction::AnyConvertExtern),
+            OpCode::GcExtExternConvertAny => Ok(wasm_encoder::Instruction::ExternConvertAny),
+            OpCode::GcExtRefI31 => Ok(wasm_encoder::Instruction::RefI31),
+            OpCode::GcExtI31GetSigned => Ok(wasm_encoder::Instruction::I31GetS),
+            OpCode::GcExtI31GetUnsigned => Ok(wasm_encoder::Instruction::I31GetU),
+            OpCode::GcExtBrOnCast => {
+                let (relative_depth, from_ref_type, to_ref_type) = match operator
                     .operator
-                    .ok_or(anyhow!("Throw operator not found"))?
+                    .ok_or(Error::Missing("BrOnCast operator"))?
                 {
-                    operator::Operator::ThrowOp(to) => {
-                        to.tag_index.ok_or(anyhow!("Throw tag_index not found"))?
+                    operator::Operator::BrOnCast(op) => (
+                        op.relative_depth
+                            .ok_or(Error::Missing("BrOnCast relative_depth"))?,
+                        wasm_encoder::RefType::try_from(
+                            op.from_type.ok_or(Error::Missing("BrOnCast from_type"))?,
+                        )?,
+                        wasm_encoder::RefType::try_from(
+                            op.to_type.ok_or(Error::Missing("BrOnCast to_type"))?,
+                        )?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "BrOnCastOp",
+                            operator: "BrOnCast",
+                        });
                     }
-                    _ => return Err(anyhow!("Expected ThrowOp for Throw operator")),
                 };
-                Ok(wasm_encoder::Instruction::Throw(tag_index))
+                Ok(wasm_encoder::Instruction::BrOnCast {
+                    relative_depth,
+                    from_ref_type,
+                    to_ref_type,
+                })
             }
-            OpCode::ExceptionsExtThrowRef => Ok(wasm_encoder::Instruction::ThrowRef),
-            OpCode::LegacyExceptionsExtTry => {
-                let ty = match operator.operator.ok_or(anyhow!("Try operator not found"))? {
-                    operator::Operator::BlockType(bt) => wasm_encoder::BlockType::try_from(bt)?,
-                    _ => return Err(anyhow!("Expected BlockType for Try operator")),
+            OpCode::GcExtBrOnCastFail => {
+                let (relative_depth, from_ref_type, to_ref_type) = match operator
+                    .operator
+                    .ok_or(Error::Missing("BrOnCastFail operator"))?
+                {
+                    operator::Operator::BrOnCast(op) => (
+                        op.relative_depth
+                            .ok_or(Error::Missing("BrOnCastFail relative_depth"))?,
+                        wasm_encoder::RefType::try_from(
+                            op.from_type
+                                .ok_or(Error::Missing("BrOnCastFail from_type"))?,
+                        )?,
+                        wasm_encoder::RefType::try_from(
+                            op.to_type.ok_or(Error::Missing("BrOnCastFail to_type"))?,
+                        )?,
+                    ),
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "BrOnCastOp",
+                            operator: "BrOnCastFail",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::Try(ty))
+                Ok(wasm_encoder::Instruction::BrOnCastFail {
+                    relative_depth,
+                    from_ref_type,
+                    to_ref_type,
+                })
             }
-            OpCode::LegacyExceptionsExtCatch => {
-                let tag_index = match operator
+            OpCode::FunctionReferencesExtCallRef => {
+                let type_index = match operator
                     .operator
-                    .ok_or(anyhow!("Catch operator not found"))?
+                    .ok_or(Error::Missing("CallRef operator"))?
                 {
-                    operator::Operator::TagIndex(idx) => idx,
-                    _ => return Err(anyhow!("Expected TagIndex for Catch operator")),
+                    operator::Operator::TypeIndex(idx) => idx,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "TypeIndex",
+                            operator: "CallRef",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::Catch(tag_index))
+                Ok(wasm_encoder::Instruction::CallRef(type_index))
             }
-            OpCode::LegacyExceptionsExtRethrow => {
+            OpCode::FunctionReferencesExtRefAsNonNull => {
+                Ok(wasm_encoder::Instruction::RefAsNonNull)
+            }
+            OpCode::FunctionReferencesExtBrOnNull => {
                 let relative_depth = match operator
                     .operator
-                    .ok_or(anyhow!("Rethrow operator not found"))?
+                    .ok_or(Error::Missing("BrOnNull operator"))?
                 {
-                    operator::Operator::RelativeDepth(idx) => idx,
-                    _ => return Err(anyhow!("Expected RelativeDepth for Rethrow operator")),
+                    operator::Operator::RelativeDepth(depth) => depth,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "RelativeDepth",
+                            operator: "BrOnNull",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::Rethrow(relative_depth))
+                Ok(wasm_encoder::Instruction::BrOnNull(relative_depth))
             }
-            OpCode::LegacyExceptionsExtDelegate => {
+            OpCode::FunctionReferencesExtBrOnNonNull => {
                 let relative_depth = match operator
                     .operator
-                    .ok_or(anyhow!("Delegate operator not found"))?
+                    .ok_or(Error::Missing("BrOnNonNull operator"))?
                 {
-                    operator::Operator::RelativeDepth(idx) => idx,
-                    _ => return Err(anyhow!("Expected RelativeDepth for Delegate operator")),
+                    operator::Operator::RelativeDepth(depth) => depth,
+                    _ => {
+                        return Err(Error::UnexpectedOperand {
+                            expected: "RelativeDepth",
+                            operator: "BrOnNonNull",
+                        });
+                    }
                 };
-                Ok(wasm_encoder::Instruction::Delegate(relative_depth))
+                Ok(wasm_encoder::Instruction::BrOnNonNull(relative_depth))
             }
-            OpCode::LegacyExceptionsExtCatchAll => Ok(wasm_encoder::Instruction::CatchAll),
         }
     }
 }
@@ -1762,7 +6356,8 @@ mod tests {
                     valtype,
                     ValueType {
                         value_type: Some(PlainType::ValueTypeI32 as i32),
-                        reference_type: None
+                        reference_type: None,
+                        reference: None
                     }
                 );
             }
@@ -1797,6 +6392,7 @@ mod tests {
             block_type: Some(block_type::BlockType::ValueType(ValueType {
                 value_type: Some(PlainType::ValueTypeI32 as i32),
                 reference_type: None,
+                reference: None,
             })),
         };
         let result = wasm_encoder::BlockType::try_from(blocktype).unwrap();
@@ -2879,6 +7475,86 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_catch_element_to_wasm_encoder() {
+        let catches = [
+            wasmparser::Catch::One { tag: 1, label: 2 },
+            wasmparser::Catch::OneRef { tag: 3, label: 4 },
+            wasmparser::Catch::All { label: 5 },
+            wasmparser::Catch::AllRef { label: 6 },
+        ];
+        let mut back = Vec::new();
+        for catch in catches {
+            back.push(
+                wasm_encoder::Catch::try_from(CatchElement::try_from(catch).unwrap()).unwrap(),
+            );
+        }
+        assert!(matches!(
+            back.as_slice(),
+            [
+                wasm_encoder::Catch::One { tag: 1, label: 2 },
+                wasm_encoder::Catch::OneRef { tag: 3, label: 4 },
+                wasm_encoder::Catch::All { label: 5 },
+                wasm_encoder::Catch::AllRef { label: 6 },
+            ]
+        ));
+    }
+
+    #[test]
+    fn test_catch_element_to_wasm_encoder_missing_fields() {
+        let missing_element = CatchElement {
+            catch_element: None,
+        };
+        assert!(matches!(
+            wasm_encoder::Catch::try_from(missing_element),
+            Err(Error::Missing("Catch element"))
+        ));
+
+        let missing_tag = CatchElement {
+            catch_element: Some(catch_element::CatchElement::One(CatchOne {
+                tag: None,
+                label: Some(0),
+            })),
+        };
+        assert!(matches!(
+            wasm_encoder::Catch::try_from(missing_tag),
+            Err(Error::Missing("Catch tag"))
+        ));
+
+        let missing_label = CatchElement {
+            catch_element: Some(catch_element::CatchElement::AllRef(CatchAllRef {
+                label: None,
+            })),
+        };
+        assert!(matches!(
+            wasm_encoder::Catch::try_from(missing_label),
+            Err(Error::Missing("Catch label"))
+        ));
+    }
+
+    #[test]
+    fn test_operator_try_table_roundtrip_keeps_catches() {
+        let bytes = encode_instructions(&[wasm_encoder::Instruction::TryTable(
+            wasm_encoder::BlockType::Result(wasm_encoder::ValType::I32),
+            vec![
+                wasm_encoder::Catch::One { tag: 0, label: 0 },
+                wasm_encoder::Catch::OneRef { tag: 1, label: 1 },
+                wasm_encoder::Catch::All { label: 2 },
+                wasm_encoder::Catch::AllRef { label: 3 },
+            ]
+            .into(),
+        )]);
+        let mut reader = wasmparser::OperatorsReader::new(wasmparser::BinaryReader::new(&bytes, 0));
+        let proto = Operator::try_from(reader.read().unwrap()).unwrap();
+        match &proto.operator {
+            Some(operator::Operator::TryTable(try_table)) => {
+                assert_eq!(try_table.catches.len(), 4)
+            }
+            _ => panic!("Expected TryTable"),
+        }
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
+
     // Exception-related operator tests
     #[test]
     fn test_operator_try() {
@@ -2969,6 +7645,7 @@ mod tests {
             block_type: Some(block_type::BlockType::ValueType(ValueType {
                 value_type: Some(PlainType::ValueTypeI64 as i32),
                 reference_type: None,
+                reference: None,
             })),
         };
         let op = Operator {
@@ -4185,4 +8862,553 @@ mod tests {
             }
         }
     }
+
+    fn encode_instructions(instructions: &[wasm_encoder::Instruction<'_>]) -> Vec<u8> {
+        use wasm_encoder::Encode;
+        let mut bytes = Vec::new();
+        for instruction in instructions {
+            instruction.encode(&mut bytes);
+        }
+        bytes
+    }
+
+    fn roundtrip_encoded(bytes: &[u8]) -> Vec<u8> {
+        let mut reader = wasmparser::OperatorsReader::new(wasmparser::BinaryReader::new(bytes, 0));
+        let mut instructions = Vec::new();
+        while !reader.eof() {
+            let proto = Operator::try_from(reader.read().unwrap()).unwrap();
+            instructions.push(wasm_encoder::Instruction::try_from(proto).unwrap());
+        }
+        encode_instructions(&instructions)
+    }
+
+    #[test]
+    fn test_simd_operator_v128_const() {
+        let value = 0x0102030405060708090a0b0c0d0e0f10i128;
+        let bytes = encode_instructions(&[wasm_encoder::Instruction::V128Const(value)]);
+        let mut reader = wasmparser::OperatorsReader::new(wasmparser::BinaryReader::new(&bytes, 0));
+        let proto = Operator::try_from(reader.read().unwrap()).unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::SimdExtV128Const as i32));
+        assert_eq!(
+            proto.operator,
+            Some(operator::Operator::V128Value(value.to_le_bytes().to_vec()))
+        );
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::V128Const(back) => assert_eq!(back, value),
+            _ => panic!("Expected V128Const"),
+        }
+    }
+
+    #[test]
+    fn test_simd_operator_shuffle() {
+        let lanes = [0, 17, 2, 19, 4, 21, 6, 23, 8, 25, 10, 27, 12, 29, 14, 31];
+        let proto = Operator::try_from(wasmparser::Operator::I8x16Shuffle { lanes }).unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::SimdExtI8x16Shuffle as i32));
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::I8x16Shuffle(back) => assert_eq!(back, lanes),
+            _ => panic!("Expected I8x16Shuffle"),
+        }
+    }
+
+    #[test]
+    fn test_simd_operator_load_lane() {
+        let memarg = wasmparser::MemArg {
+            align: 2,
+            max_align: 2,
+            offset: 16,
+            memory: 0,
+        };
+        let proto =
+            Operator::try_from(wasmparser::Operator::V128Load32Lane { memarg, lane: 3 }).unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::SimdExtV128Load32Lane as i32));
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::V128Load32Lane { memarg, lane } => {
+                assert_eq!(memarg.align, 2);
+                assert_eq!(memarg.offset, 16);
+                assert_eq!(lane, 3);
+            }
+            _ => panic!("Expected V128Load32Lane"),
+        }
+    }
+
+    #[test]
+    fn test_simd_operator_extract_lane_signed() {
+        let proto =
+            Operator::try_from(wasmparser::Operator::I8x16ExtractLaneS { lane: 15 }).unwrap();
+        assert_eq!(
+            proto.opcode,
+            Some(OpCode::SimdExtI8x16ExtractLaneSigned as i32)
+        );
+        assert_eq!(proto.operator, Some(operator::Operator::Lane(15)));
+    }
+
+    #[test]
+    fn test_simd_operator_to_instruction_lane_out_of_range() {
+        let op = Operator {
+            opcode: Some(OpCode::SimdExtI32x4ReplaceLane as i32),
+            operator: Some(operator::Operator::Lane(256)),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_simd_operator_to_instruction_lane_beyond_lane_count() {
+        for (opcode, lane) in [
+            (OpCode::SimdExtI8x16ExtractLaneSigned, 16),
+            (OpCode::SimdExtI32x4ExtractLane, 7),
+            (OpCode::SimdExtI64x2ReplaceLane, 200),
+            (OpCode::SimdExtF64x2ExtractLane, 2),
+        ] {
+            let op = with_operand(opcode, operator::Operator::Lane(lane));
+            assert!(matches!(
+                wasm_encoder::Instruction::try_from(op),
+                Err(Error::Invalid(_))
+            ));
+        }
+        let op = with_operand(
+            OpCode::SimdExtV128Load16Lane,
+            operator::Operator::MemargLane(MemArgLane {
+                memarg: Some(MemArg {
+                    align: Some(0),
+                    max_align: Some(0),
+                    offset: Some(0),
+                    memory: Some(0),
+                }),
+                lane: Some(8),
+            }),
+        );
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_simd_operator_to_instruction_shuffle_lane_out_of_range() {
+        let mut lanes: Vec<u8> = (0..16).collect();
+        lanes[3] = 32;
+        let op = with_operand(
+            OpCode::SimdExtI8x16Shuffle,
+            operator::Operator::Lanes(lanes),
+        );
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_simd_operator_to_instruction_short_v128_const() {
+        let op = Operator {
+            opcode: Some(OpCode::SimdExtV128Const as i32),
+            operator: Some(operator::Operator::V128Value(vec![0; 8])),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::Invalid(_))
+        ));
+    }
+
+    #[test]
+    fn test_simd_operator_to_instruction_missing_lane() {
+        let op = Operator {
+            opcode: Some(OpCode::SimdExtV128Store8Lane as i32),
+            operator: Some(operator::Operator::MemargLane(MemArgLane {
+                memarg: Some(MemArg {
+                    align: Some(0),
+                    max_align: Some(0),
+                    offset: Some(0),
+                    memory: Some(0),
+                }),
+                lane: None,
+            })),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::UnexpectedOperand { .. })
+        ));
+    }
+
+    #[test]
+    fn test_simd_operators_encoding_roundtrip() {
+        let memarg = wasm_encoder::MemArg {
+            align: 4,
+            offset: 32,
+            memory_index: 0,
+        };
+        let bytes = encode_instructions(&[
+            wasm_encoder::Instruction::V128Load(memarg),
+            wasm_encoder::Instruction::V128Load8x8S(memarg),
+            wasm_encoder::Instruction::V128Load64Splat(memarg),
+            wasm_encoder::Instruction::V128Load32Zero(memarg),
+            wasm_encoder::Instruction::V128Store(memarg),
+            wasm_encoder::Instruction::V128Load8Lane { memarg, lane: 7 },
+            wasm_encoder::Instruction::V128Store64Lane { memarg, lane: 1 },
+            wasm_encoder::Instruction::V128Const(-1),
+            wasm_encoder::Instruction::I8x16Shuffle([15; 16]),
+            wasm_encoder::Instruction::I16x8ExtractLaneU(5),
+            wasm_encoder::Instruction::F64x2ReplaceLane(1),
+            wasm_encoder::Instruction::I32x4Add,
+            wasm_encoder::Instruction::I8x16Swizzle,
+            wasm_encoder::Instruction::I16x8Q15MulrSatS,
+            wasm_encoder::Instruction::F32x4PMin,
+            wasm_encoder::Instruction::I32x4TruncSatF64x2UZero,
+            wasm_encoder::Instruction::V128AnyTrue,
+            wasm_encoder::Instruction::I8x16RelaxedSwizzle,
+            wasm_encoder::Instruction::F64x2RelaxedNmadd,
+            wasm_encoder::Instruction::I64x2RelaxedLaneselect,
+            wasm_encoder::Instruction::I32x4RelaxedDotI8x16I7x16AddS,
+        ]);
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
+
+    #[test]
+    fn test_atomic_operator_rmw() {
+        let memarg = wasmparser::MemArg {
+            align: 3,
+            max_align: 3,
+            offset: 8,
+            memory: 0,
+        };
+        let proto =
+            Operator::try_from(wasmparser::Operator::I64AtomicRmw32CmpxchgU { memarg }).unwrap();
+        assert_eq!(
+            proto.opcode,
+            Some(OpCode::ThreadsExtI64AtomicRmw32CmpxchgUnsigned as i32)
+        );
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::I64AtomicRmw32CmpxchgU(ma) => {
+                assert_eq!(ma.align, 3);
+                assert_eq!(ma.offset, 8);
+            }
+            _ => panic!("Expected I64AtomicRmw32CmpxchgU"),
+        }
+    }
+
+    #[test]
+    fn test_atomic_operator_fence() {
+        let proto = Operator::try_from(wasmparser::Operator::AtomicFence).unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::ThreadsExtAtomicFence as i32));
+        assert!(proto.operator.is_none());
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(proto).unwrap(),
+            wasm_encoder::Instruction::AtomicFence
+        ));
+    }
+
+    #[test]
+    fn test_atomic_operator_to_instruction_missing_memarg() {
+        let op = Operator {
+            opcode: Some(OpCode::ThreadsExtMemoryAtomicWait32 as i32),
+            ..Operator::default()
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::Missing("MemoryAtomicWait32 operator"))
+        ));
+    }
+
+    #[test]
+    fn test_atomic_operators_encoding_roundtrip() {
+        let memarg = wasm_encoder::MemArg {
+            align: 2,
+            offset: 4,
+            memory_index: 0,
+        };
+        let bytes = encode_instructions(&[
+            wasm_encoder::Instruction::MemoryAtomicNotify(memarg),
+            wasm_encoder::Instruction::MemoryAtomicWait32(memarg),
+            wasm_encoder::Instruction::MemoryAtomicWait64(memarg),
+            wasm_encoder::Instruction::AtomicFence,
+            wasm_encoder::Instruction::I32AtomicLoad(memarg),
+            wasm_encoder::Instruction::I64AtomicLoad32U(memarg),
+            wasm_encoder::Instruction::I32AtomicStore16(memarg),
+            wasm_encoder::Instruction::I64AtomicRmwSub(memarg),
+            wasm_encoder::Instruction::I32AtomicRmw8AndU(memarg),
+            wasm_encoder::Instruction::I64AtomicRmw16OrU(memarg),
+            wasm_encoder::Instruction::I32AtomicRmwXor(memarg),
+            wasm_encoder::Instruction::I64AtomicRmw32XchgU(memarg),
+            wasm_encoder::Instruction::I32AtomicRmw16CmpxchgU(memarg),
+        ]);
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
+
+    #[test]
+    fn test_reference_types_operator_ref_null() {
+        let proto = Operator::try_from(wasmparser::Operator::RefNull {
+            hty: wasmparser::HeapType::EXTERN,
+        })
+        .unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::ReferenceTypesExtRefNull as i32));
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::RefNull(heap_type) => {
+                assert_eq!(heap_type, wasm_encoder::HeapType::EXTERN)
+            }
+            _ => panic!("Expected RefNull"),
+        }
+    }
+
+    #[test]
+    fn test_reference_types_operator_table_grow() {
+        let proto = Operator::try_from(wasmparser::Operator::TableGrow { table: 2 }).unwrap();
+        assert_eq!(
+            proto.opcode,
+            Some(OpCode::ReferenceTypesExtTableGrow as i32)
+        );
+        assert_eq!(proto.operator, Some(operator::Operator::TableIndex(2)));
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(proto).unwrap(),
+            wasm_encoder::Instruction::TableGrow(2)
+        ));
+    }
+
+    #[test]
+    fn test_reference_types_operator_to_instruction_wrong_operand() {
+        let op = Operator {
+            opcode: Some(OpCode::ReferenceTypesExtTypedSelect as i32),
+            operator: Some(operator::Operator::TableIndex(0)),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::UnexpectedOperand {
+                expected: "SelectType",
+                operator: "TypedSelect",
+            })
+        ));
+    }
+
+    #[test]
+    fn test_reference_types_operators_encoding_roundtrip() {
+        let bytes = encode_instructions(&[
+            wasm_encoder::Instruction::RefNull(wasm_encoder::HeapType::FUNC),
+            wasm_encoder::Instruction::RefIsNull,
+            wasm_encoder::Instruction::RefFunc(3),
+            wasm_encoder::Instruction::TypedSelect(wasm_encoder::ValType::EXTERNREF),
+            wasm_encoder::Instruction::TypedSelect(wasm_encoder::ValType::I64),
+            wasm_encoder::Instruction::TableGet(0),
+            wasm_encoder::Instruction::TableSet(1),
+            wasm_encoder::Instruction::TableGrow(0),
+            wasm_encoder::Instruction::TableSize(1),
+            wasm_encoder::Instruction::TableFill(0),
+        ]);
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
+
+    #[test]
+    fn test_tail_call_operator_return_call_indirect() {
+        let proto = Operator::try_from(wasmparser::Operator::ReturnCallIndirect {
+            type_index: 4,
+            table_index: 1,
+        })
+        .unwrap();
+        assert_eq!(
+            proto.opcode,
+            Some(OpCode::TailCallExtReturnCallIndirect as i32)
+        );
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::ReturnCallIndirect {
+                type_index,
+                table_index,
+            } => {
+                assert_eq!(type_index, 4);
+                assert_eq!(table_index, 1);
+            }
+            _ => panic!("Expected ReturnCallIndirect"),
+        }
+    }
+
+    #[test]
+    fn test_tail_call_operator_to_instruction_missing_table_index() {
+        let op = Operator {
+            opcode: Some(OpCode::TailCallExtReturnCallIndirect as i32),
+            operator: Some(operator::Operator::CallIndirect(CallIndirectOp {
+                type_index: Some(0),
+                table_index: None,
+            })),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(op),
+            Err(Error::Missing("ReturnCallIndirect table_index"))
+        ));
+    }
+
+    #[test]
+    fn test_tail_call_operators_encoding_roundtrip() {
+        let bytes = encode_instructions(&[
+            wasm_encoder::Instruction::ReturnCall(7),
+            wasm_encoder::Instruction::ReturnCallIndirect {
+                type_index: 2,
+                table_index: 0,
+            },
+            wasm_encoder::Instruction::ReturnCallRef(5),
+        ]);
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
+
+    #[test]
+    fn test_gc_operator_struct_get_signed() {
+        let proto = Operator::try_from(wasmparser::Operator::StructGetS {
+            struct_type_index: 3,
+            field_index: 1,
+        })
+        .unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::GcExtStructGetSigned as i32));
+        assert_eq!(
+            proto.operator,
+            Some(operator::Operator::StructField(StructFieldOp {
+                type_index: Some(3),
+                field_index: Some(1),
+            }))
+        );
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::StructGetS {
+                struct_type_index,
+                field_index,
+            } => {
+                assert_eq!(struct_type_index, 3);
+                assert_eq!(field_index, 1);
+            }
+            _ => panic!("Expected StructGetS"),
+        }
+    }
+
+    #[test]
+    fn test_gc_operator_ref_cast_concrete() {
+        let proto = Operator::try_from(wasmparser::Operator::RefCastNullable {
+            hty: wasmparser::HeapType::Concrete(wasmparser::UnpackedIndex::Module(7)),
+        })
+        .unwrap();
+        assert_eq!(proto.opcode, Some(OpCode::GcExtRefCastNullable as i32));
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::RefCastNullable(heap_type) => {
+                assert_eq!(heap_type, wasm_encoder::HeapType::Concrete(7))
+            }
+            _ => panic!("Expected RefCastNullable"),
+        }
+    }
+
+    #[test]
+    fn test_gc_operator_to_instruction_missing_from_type() {
+        let proto = Operator {
+            opcode: Some(OpCode::GcExtBrOnCast as i32),
+            operator: Some(operator::Operator::BrOnCast(BrOnCastOp {
+                relative_depth: Some(0),
+                from_type: None,
+                to_type: None,
+            })),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(proto),
+            Err(Error::Missing("BrOnCast from_type"))
+        ));
+    }
+
+    #[test]
+    fn test_gc_operator_to_instruction_wrong_operand() {
+        let proto = Operator {
+            opcode: Some(OpCode::GcExtArrayCopy as i32),
+            operator: Some(operator::Operator::TypeIndex(1)),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(proto),
+            Err(Error::UnexpectedOperand {
+                expected: "ArrayCopyOp",
+                operator: "ArrayCopy",
+            })
+        ));
+    }
+
+    #[test]
+    fn test_gc_operators_encoding_roundtrip() {
+        let bytes = encode_instructions(&[
+            wasm_encoder::Instruction::StructNew(0),
+            wasm_encoder::Instruction::StructNewDefault(0),
+            wasm_encoder::Instruction::StructSet {
+                struct_type_index: 0,
+                field_index: 2,
+            },
+            wasm_encoder::Instruction::ArrayNewFixed {
+                array_type_index: 1,
+                array_size: 4,
+            },
+            wasm_encoder::Instruction::ArrayNewData {
+                array_type_index: 1,
+                array_data_index: 0,
+            },
+            wasm_encoder::Instruction::ArrayInitElem {
+                array_type_index: 1,
+                array_elem_index: 2,
+            },
+            wasm_encoder::Instruction::ArrayGetU(1),
+            wasm_encoder::Instruction::ArrayLen,
+            wasm_encoder::Instruction::ArrayCopy {
+                array_type_index_dst: 1,
+                array_type_index_src: 2,
+            },
+            wasm_encoder::Instruction::RefTestNonNull(wasm_encoder::HeapType::Abstract {
+                shared: false,
+                ty: wasm_encoder::AbstractHeapType::Eq,
+            }),
+            wasm_encoder::Instruction::BrOnCastFail {
+                relative_depth: 1,
+                from_ref_type: wasm_encoder::RefType::ANYREF,
+                to_ref_type: wasm_encoder::RefType {
+                    nullable: false,
+                    heap_type: wasm_encoder::HeapType::Concrete(0),
+                },
+            },
+            wasm_encoder::Instruction::RefI31,
+            wasm_encoder::Instruction::I31GetS,
+            wasm_encoder::Instruction::AnyConvertExtern,
+            wasm_encoder::Instruction::ExternConvertAny,
+            wasm_encoder::Instruction::RefEq,
+        ]);
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
+
+    #[test]
+    fn test_function_references_operator_br_on_null() {
+        let proto =
+            Operator::try_from(wasmparser::Operator::BrOnNull { relative_depth: 3 }).unwrap();
+        assert_eq!(
+            proto.opcode,
+            Some(OpCode::FunctionReferencesExtBrOnNull as i32)
+        );
+        assert_eq!(proto.operator, Some(operator::Operator::RelativeDepth(3)));
+        match wasm_encoder::Instruction::try_from(proto).unwrap() {
+            wasm_encoder::Instruction::BrOnNull(relative_depth) => assert_eq!(relative_depth, 3),
+            _ => panic!("Expected BrOnNull"),
+        }
+    }
+
+    #[test]
+    fn test_function_references_operator_to_instruction_wrong_operand() {
+        let proto = Operator {
+            opcode: Some(OpCode::FunctionReferencesExtCallRef as i32),
+            operator: Some(operator::Operator::FunctionIndex(0)),
+        };
+        assert!(matches!(
+            wasm_encoder::Instruction::try_from(proto),
+            Err(Error::UnexpectedOperand {
+                expected: "TypeIndex",
+                operator: "CallRef",
+            })
+        ));
+    }
+
+    #[test]
+    fn test_function_references_operators_encoding_roundtrip() {
+        let bytes = encode_instructions(&[
+            wasm_encoder::Instruction::CallRef(1),
+            wasm_encoder::Instruction::ReturnCallRef(2),
+            wasm_encoder::Instruction::RefAsNonNull,
+            wasm_encoder::Instruction::BrOnNull(0),
+            wasm_encoder::Instruction::BrOnNonNull(1),
+            wasm_encoder::Instruction::RefNull(wasm_encoder::HeapType::Concrete(3)),
+        ]);
+        assert_eq!(roundtrip_encoded(&bytes), bytes);
+    }
 }
@@ -0,0 +1,427 @@
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+use crate::program_module;
+
+pub fn from_wasm_component(bytes: &[u8]) -> Result<ProgramComponent> {
+    use component_section::Section;
+    use wasmparser::{Chunk, Encoding, Parser, Payload};
+    let mut program_component = ProgramComponent {
+        protocol_version: Some(1),
+        ..Default::default()
+    };
+    let mut parser = Parser::new(0);
+    let mut offset = 0;
+    loop {
+        let (consumed, payload) = match parser.parse(&bytes[offset..], true)? {
+            Chunk::Parsed { consumed, payload } => (consumed, payload),
+            Chunk::NeedMoreData(_) => {
+                return Err(Error::Invalid("Unexpected end of component".to_string()));
+            }
+        };
+        offset += consumed;
+        let section = match payload {
+            Payload::Version { .. } => {
+                program_component.version =
+                    Version::from_wasmparser(&payload, Encoding::Component)?;
+                continue;
+            }
+            // the parser only consumes the header of nested modules and components, their
+            // bodies are converted separately
+            Payload::ModuleSection {
+                unchecked_range, ..
+            } => {
+                offset += unchecked_range.len();
+                Section::Module(program_module::parse_module(&bytes[unchecked_range])?)
+            }
+            Payload::ComponentSection {
+                unchecked_range, ..
+            } => {
+                offset += unchecked_range.len();
+                Section::Component(from_wasm_component(&bytes[unchecked_range])?)
+            }
+            Payload::InstanceSection(reader) => {
+                Section::CoreInstanceSection(CoreInstanceSection::from_wasmparser(reader)?)
+            }
+            Payload::CoreTypeSection(reader) => {
+                Section::CoreTypeSection(CoreTypeSection::from_wasmparser(reader)?)
+            }
+            Payload::ComponentInstanceSection(reader) => {
+                Section::InstanceSection(ComponentInstanceSection::from_wasmparser(reader)?)
+            }
+            Payload::ComponentAliasSection(reader) => {
+                Section::AliasSection(ComponentAliasSection::from_wasmparser(reader)?)
+            }
+            Payload::ComponentTypeSection(reader) => {
+                Section::TypeSection(ComponentTypeSection::from_wasmparser(reader)?)
+            }
+            Payload::ComponentCanonicalSection(reader) => {
+                Section::CanonicalSection(ComponentCanonicalSection::from_wasmparser(reader)?)
+            }
+            Payload::ComponentStartSection { start, .. } => {
+                Section::StartSection(ComponentStartFunction::from_wasmparser(start))
+            }
+            Payload::ComponentImportSection(reader) => {
+                Section::ImportSection(ComponentImportSection::from_wasmparser(reader)?)
+            }
+            Payload::ComponentExportSection(reader) => {
+                Section::ExportSection(ComponentExportSection::from_wasmparser(reader)?)
+            }
+            // custom sections keep their position in the section list, so no anchor is needed
+            Payload::CustomSection(reader) => Section::CustomSection(CustomSection {
+                name: Some(reader.name().to_string()),
+                data: Some(reader.data().to_vec()),
+                after_section_id: None,
+                name_section: None,
+            }),
+            Payload::End(_) => break,
+            rest => {
+                return Err(Error::Unsupported(format!(
+                    "Unknown component section {:?}",
+                    rest
+                )));
+            }
+        };
+        program_component.sections.push(ComponentSection {
+            section: Some(section),
+        });
+    }
+    Ok(program_component)
+}
+
+pub fn render_wasm_component(program: ProgramComponent) -> Result<Vec<u8>> {
+    render_component(&program)
+}
+
+fn render_component(program: &ProgramComponent) -> Result<Vec<u8>> {
+    use component_section::Section;
+    use wasm_encoder::{Component, ComponentSectionId, RawSection};
+    let mut component = Component::new();
+    for section in &program.sections {
+        match section
+            .section
+            .as_ref()
+            .ok_or(Error::Missing("Component section"))?
+        {
+            Section::Module(module) => {
+                let bytes = program_module::render_wasm(module.clone())?;
+                component.section(&RawSection {
+                    id: ComponentSectionId::CoreModule.into(),
+                    data: &bytes,
+                });
+            }
+            Section::Component(nested) => {
+                let bytes = render_component(nested)?;
+                component.section(&RawSection {
+                    id: ComponentSectionId::Component.into(),
+                    data: &bytes,
+                });
+            }
+            Section::CoreInstanceSection(section) => section.render_wasm(&mut component)?,
+            Section::CoreTypeSection(section) => section.render_wasm(&mut component)?,
+            Section::InstanceSection(section) => section.render_wasm(&mut component)?,
+            Section::AliasSection(section) => section.render_wasm(&mut component)?,
+            Section::TypeSection(section) => section.render_wasm(&mut component)?,
+            Section::CanonicalSection(section) => section.render_wasm(&mut component)?,
+            Section::StartSection(section) => section.render_wasm(&mut component)?,
+            Section::ImportSection(section) => section.render_wasm(&mut component)?,
+            Section::ExportSection(section) => section.render_wasm(&mut component)?,
+            Section::CustomSection(section) => {
+                component.section(&wasm_encoder::CustomSection {
+                    name: section
+                        .name
+                        .as_deref()
+                        .ok_or(Error::Missing("Custom section name"))?
+                        .into(),
+                    data: section
+                        .data
+                        .as_deref()
+                        .ok_or(Error::Missing("Custom section data"))?
+                        .into(),
+                });
+            }
+        }
+    }
+    Ok(component.finish())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use wasm_encoder::{
+        Alias, CanonicalFunctionSection, CanonicalOption, Component, ComponentAliasSection,
+        ComponentExportKind, ComponentExportSection, ComponentImportSection,
+        ComponentInstanceSection, ComponentTypeRef, ComponentTypeSection, CoreTypeSection,
+        EntityType, ExportKind, InstanceSection, InstanceType, ModuleArg, ModuleSection,
+        ModuleType, PrimitiveValType, ValType,
+    };
+
+    /// Creates a core module exporting `add: (i32, i32) -> i32`
+    fn create_adder_module() -> wasm_encoder::Module {
+        use wasm_encoder::{CodeSection, Function, FunctionSection, Instruction, TypeSection};
+        let mut module = wasm_encoder::Module::new();
+        let mut types = TypeSection::new();
+        types
+            .ty()
+            .function([ValType::I32, ValType::I32], [ValType::I32]);
+        module.section(&types);
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("add", ExportKind::Func, 0);
+        module.section(&exports);
+        let mut code = CodeSection::new();
+        let mut function = Function::new(vec![]);
+        function.instruction(&Instruction::LocalGet(0));
+        function.instruction(&Instruction::LocalGet(1));
+        function.instruction(&Instruction::I32Add);
+        function.instruction(&Instruction::End);
+        code.function(&function);
+        module.section(&code);
+        module
+    }
+
+    /// Creates a component lifting the adder module's `add` export
+    fn create_adder_component() -> Vec<u8> {
+        let mut component = Component::new();
+        component.section(&ModuleSection(&create_adder_module()));
+
+        let mut instances = InstanceSection::new();
+        instances.instantiate(0, Vec::<(&str, ModuleArg)>::new());
+        component.section(&instances);
+
+        let mut aliases = ComponentAliasSection::new();
+        aliases.alias(Alias::CoreInstanceExport {
+            instance: 0,
+            kind: ExportKind::Func,
+            name: "add",
+        });
+        component.section(&aliases);
+
+        let mut types = ComponentTypeSection::new();
+        types
+            .function()
+            .params([("a", PrimitiveValType::S32), ("b", PrimitiveValType::S32)])
+            .result(Some(PrimitiveValType::S32.into()));
+        component.section(&types);
+
+        let mut canonical = CanonicalFunctionSection::new();
+        canonical.lift(0, 0, []);
+        component.section(&canonical);
+
+        let mut exports = ComponentExportSection::new();
+        exports.export("add", ComponentExportKind::Func, 0, None);
+        component.section(&exports);
+
+        component.finish()
+    }
+
+    /// Creates a component importing a host logger, lowering it into a core module that has no
+    /// code of its own, and defining one of each kind of value type
+    fn create_component_with_types() -> Vec<u8> {
+        use wasm_encoder::{ImportSection, MemorySection, MemoryType, TypeSection};
+        let mut component = Component::new();
+
+        let mut host = InstanceType::new();
+        host.ty()
+            .function()
+            .params([("msg", PrimitiveValType::String)])
+            .result(None);
+        host.export("log", ComponentTypeRef::Func(0));
+        let mut types = ComponentTypeSection::new();
+        types.instance(&host);
+        component.section(&types);
+
+        let mut imports = ComponentImportSection::new();
+        imports.import("host", ComponentTypeRef::Instance(0));
+        component.section(&imports);
+
+        let mut aliases = ComponentAliasSection::new();
+        aliases.alias(Alias::InstanceExport {
+            instance: 0,
+            kind: ComponentExportKind::Func,
+            name: "log",
+        });
+        component.section(&aliases);
+
+        let mut memory_module = wasm_encoder::Module::new();
+        let mut memories = MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        });
+        memory_module.section(&memories);
+        let mut memory_exports = wasm_encoder::ExportSection::new();
+        memory_exports.export("memory", ExportKind::Memory, 0);
+        memory_module.section(&memory_exports);
+        component.section(&ModuleSection(&memory_module));
+
+        let mut logger_module = wasm_encoder::Module::new();
+        let mut logger_types = TypeSection::new();
+        logger_types.ty().function([ValType::I32, ValType::I32], []);
+        logger_module.section(&logger_types);
+        let mut logger_imports = ImportSection::new();
+        logger_imports.import("host", "log", EntityType::Function(0));
+        logger_module.section(&logger_imports);
+        component.section(&ModuleSection(&logger_module));
+
+        let mut instances = InstanceSection::new();
+        instances.instantiate(0, Vec::<(&str, ModuleArg)>::new());
+        component.section(&instances);
+
+        let mut aliases = ComponentAliasSection::new();
+        aliases.alias(Alias::CoreInstanceExport {
+            instance: 0,
+            kind: ExportKind::Memory,
+            name: "memory",
+        });
+        component.section(&aliases);
+
+        let mut canonical = CanonicalFunctionSection::new();
+        canonical.lower(0, [CanonicalOption::UTF8, CanonicalOption::Memory(0)]);
+        component.section(&canonical);
+
+        let mut instances = InstanceSection::new();
+        instances.export_items([("log", ExportKind::Func, 0)]);
+        instances.instantiate(1, [("host", ModuleArg::Instance(1))]);
+        component.section(&instances);
+
+        let mut types = ComponentTypeSection::new();
+        types
+            .defined_type()
+            .record([("x", PrimitiveValType::S32), ("y", PrimitiveValType::S32)]);
+        types.defined_type().variant([
+            ("circle", Some(PrimitiveValType::U32.into()), None),
+            ("empty", None, None),
+        ]);
+        types.defined_type().list(PrimitiveValType::U8);
+        types
+            .defined_type()
+            .tuple([PrimitiveValType::U8, PrimitiveValType::String]);
+        types.defined_type().flags(["read", "write"]);
+        types.defined_type().enum_type(["red", "green"]);
+        types.defined_type().option(PrimitiveValType::U32);
+        types.defined_type().result(
+            Some(PrimitiveValType::U32.into()),
+            Some(PrimitiveValType::String.into()),
+        );
+        types.resource(ValType::I32, None);
+        types.defined_type().own(9);
+        component.section(&types);
+
+        let mut nested = Component::new();
+        let mut module_type = ModuleType::new();
+        module_type.ty().function([], []);
+        module_type.import("env", "f", EntityType::Function(0));
+        module_type.export("g", EntityType::Function(0));
+        let mut core_types = CoreTypeSection::new();
+        core_types
+            .ty()
+            .core()
+            .function([ValType::I64], [ValType::I64]);
+        core_types.ty().module(&module_type);
+        nested.section(&core_types);
+        let mut nested_types = ComponentTypeSection::new();
+        nested_types.defined_type().primitive(PrimitiveValType::U8);
+        nested.section(&nested_types);
+        component.section(&wasm_encoder::NestedComponentSection(&nested));
+
+        let mut component_instances = ComponentInstanceSection::new();
+        component_instances.instantiate(0, Vec::<(&str, ComponentExportKind, u32)>::new());
+        component.section(&component_instances);
+
+        component.section(&wasm_encoder::CustomSection {
+            name: "note".into(),
+            data: [1, 2, 3].as_slice().into(),
+        });
+
+        let mut exports = ComponentExportSection::new();
+        exports.export("point", ComponentExportKind::Type, 1, None);
+        component.section(&exports);
+
+        component.finish()
+    }
+
+    fn validate(bytes: &[u8]) {
+        wasmparser::Validator::new().validate_all(bytes).unwrap();
+    }
+
+    #[test]
+    fn test_adder_component_roundtrip() {
+        let bytes = create_adder_component();
+        validate(&bytes);
+        let program = from_wasm_component(&bytes).unwrap();
+        assert_eq!(program.sections.len(), 6);
+        let rendered = render_wasm_component(program).unwrap();
+        assert_eq!(rendered, bytes);
+    }
+
+    #[test]
+    fn test_nested_module_is_converted() {
+        let program = from_wasm_component(&create_adder_component()).unwrap();
+        let Some(component_section::Section::Module(module)) = &program.sections[0].section else {
+            panic!("Expected a nested module");
+        };
+        assert!(module.code_section.is_some());
+        assert_eq!(
+            module.export_section.as_ref().unwrap().exports[0].name,
+            Some("add".to_string())
+        );
+    }
+
+    #[test]
+    fn test_component_with_types_roundtrip() {
+        let bytes = create_component_with_types();
+        validate(&bytes);
+        let program = from_wasm_component(&bytes).unwrap();
+        let rendered = render_wasm_component(program).unwrap();
+        validate(&rendered);
+        assert_eq!(rendered, bytes);
+    }
+
+    #[test]
+    fn test_component_version() {
+        let program = from_wasm_component(&create_adder_component()).unwrap();
+        let version = program.version.unwrap();
+        assert_eq!(version.r#number, Some(0xd));
+        assert_eq!(
+            version.encoding,
+            Some(wasmparser::Encoding::Component as i32)
+        );
+    }
+
+    #[test]
+    fn test_from_wasm_component_rejects_module() {
+        let bytes = create_adder_module().finish();
+        assert!(matches!(
+            from_wasm_component(&bytes),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_from_wasm_rejects_component() {
+        assert!(program_module::from_wasm(&create_adder_component()).is_err());
+    }
+
+    #[test]
+    fn test_from_wasm_component_truncated() {
+        let bytes = create_adder_component();
+        assert!(from_wasm_component(&bytes[..bytes.len() - 3]).is_err());
+    }
+
+    #[test]
+    fn test_render_wasm_component_missing_section() {
+        let program = ProgramComponent {
+            sections: vec![ComponentSection { section: None }],
+            ..Default::default()
+        };
+        assert!(matches!(
+            render_wasm_component(program),
+            Err(Error::Missing(_))
+        ));
+    }
+}
@@ -1,19 +1,33 @@
+use crate::error::{Error, Result};
 use crate::libernet_wasm::*;
 
-use anyhow::{Ok, Result, bail};
-
 pub fn from_wasm(bytes: &[u8]) -> Result<ProgramModule> {
-    use wasmparser::{Parser, Payload};
+    let program_module = parse_module(bytes)?;
+    if program_module.type_section.is_none()
+        || program_module.function_section.is_none()
+        || program_module.code_section.is_none()
+    {
+        return Err(Error::Invalid("Code section is required".to_string()));
+    }
+    Ok(program_module)
+}
+
+/// Converts a module without requiring it to define any functions, as core modules nested in a
+/// component may only re-export their imports.
+pub(crate) fn parse_module(bytes: &[u8]) -> Result<ProgramModule> {
+    use wasmparser::{Encoding, Parser, Payload};
     let mut program_module = ProgramModule {
         protocol_version: Some(1),
         ..Default::default()
     };
     let mut code_entries: Vec<CodeSectionEntry> = Vec::new();
+    let mut last_section_id: u8 = 0;
     for payload in Parser::new(0).parse_all(bytes) {
         let payload = payload?;
+        let section_id = payload.as_section().map(|(id, _)| id);
         match payload {
             Payload::Version { .. } => {
-                program_module.version = Version::from_wasmparser(&payload)?;
+                program_module.version = Version::from_wasmparser(&payload, Encoding::Module)?;
             }
             Payload::CodeSectionEntry { .. } => {
                 code_entries.push(CodeSectionEntry::from_wasmparser(&payload)?);
@@ -43,41 +57,39 @@ pub fn from_wasm(bytes: &[u8]) -> Result<ProgramModule> {
                 program_module.export_section = Some(ExportSection::from_wasmparser(section)?);
             }
             Payload::StartSection { .. } => {
-                bail!("StartSection is not supported");
+                program_module.start_section = Some(StartSection::from_wasmparser(&payload)?);
             }
             Payload::ElementSection(section) => {
                 program_module.element_section = Some(ElementSection::from_wasmparser(section)?);
             }
-            Payload::DataCountSection { .. } => {}
+            Payload::DataCountSection { .. } => {
+                program_module.data_count_section =
+                    Some(DataCountSection::from_wasmparser(&payload)?);
+            }
             Payload::DataSection(section) => {
                 program_module.data_section = Some(DataSection::from_wasmparser(section)?);
             }
             Payload::CodeSectionStart { .. } => {
                 // this section provider information about code sections count, we don't need it
             }
-            Payload::InstanceSection(_) => {}
-            Payload::CoreTypeSection(_) => {}
-            Payload::ComponentInstanceSection(_) => {}
-            Payload::ComponentAliasSection(_) => {}
-            Payload::ComponentTypeSection(_) => {}
-            Payload::ComponentCanonicalSection(_) => {}
-            Payload::ComponentStartSection { .. } => {}
-            Payload::ComponentImportSection(_) => {}
-            Payload::ComponentExportSection(_) => {}
-            Payload::CustomSection(_) => {}
+            Payload::CustomSection(section) => {
+                program_module
+                    .custom_sections
+                    .push(CustomSection::from_wasmparser(&section, last_section_id)?);
+            }
             Payload::End(_) => {}
             rest => {
-                bail!("Unknown section {:?}", rest);
+                return Err(Error::Unsupported(format!("Unknown section {:?}", rest)));
             }
         };
+        if let Some(id) = section_id
+            && id != 0
+        {
+            last_section_id = id;
+        }
     }
 
-    if program_module.type_section.is_none()
-        || program_module.function_section.is_none()
-        || code_entries.is_empty()
-    {
-        bail!("Code section is required");
-    } else {
+    if !code_entries.is_empty() {
         program_module.code_section = Some(CodeSection {
             code_section_entry: code_entries,
         });
@@ -86,45 +98,123 @@ pub fn from_wasm(bytes: &[u8]) -> Result<ProgramModule> {
     Ok(program_module)
 }
 
+impl ProgramModule {
+    pub fn name_section(&self) -> Option<&NameSection> {
+        self.custom_sections
+            .iter()
+            .find_map(|section| section.name_section.as_ref())
+    }
+}
+
 pub fn render_wasm(program: ProgramModule) -> Result<Vec<u8>> {
-    use wasm_encoder::Module;
+    use wasm_encoder::{Module, SectionId};
     let mut module: Module = Module::new();
-    program
-        .type_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .import_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .function_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .table_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .memory_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .tag_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .global_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .export_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .element_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .code_section
-        .map(|section| section.render_wasm(&mut module));
-    program
-        .data_section
-        .map(|section| section.render_wasm(&mut module));
+    // custom sections that precede every known section
+    render_custom_sections(&program, SectionId::Custom, &mut module)?;
+    if let Some(section) = &program.type_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Type, &mut module)?;
+    if let Some(section) = &program.import_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Import, &mut module)?;
+    if let Some(section) = &program.function_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Function, &mut module)?;
+    if let Some(section) = &program.table_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Table, &mut module)?;
+    if let Some(section) = &program.memory_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Memory, &mut module)?;
+    if let Some(section) = &program.tag_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Tag, &mut module)?;
+    if let Some(section) = &program.global_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Global, &mut module)?;
+    if let Some(section) = &program.export_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Export, &mut module)?;
+    if let Some(section) = &program.start_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Start, &mut module)?;
+    if let Some(section) = &program.element_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Element, &mut module)?;
+    if let Some(section) = &program.data_count_section {
+        section.render_wasm(&mut module)?;
+    } else if uses_data_count(&program) {
+        DataCountSection {
+            count: Some(
+                program
+                    .data_section
+                    .as_ref()
+                    .map_or(0, |section| section.datas.len() as u32),
+            ),
+        }
+        .render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::DataCount, &mut module)?;
+    if let Some(section) = &program.code_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Code, &mut module)?;
+    if let Some(section) = &program.data_section {
+        section.render_wasm(&mut module)?;
+    }
+    render_custom_sections(&program, SectionId::Data, &mut module)?;
+    // custom sections anchored to a section id we don't know go last
+    for section in &program.custom_sections {
+        if section.after_section_id.unwrap_or(0) > SectionId::Tag as u32 {
+            section.render_wasm(&mut module)?;
+        }
+    }
     Ok(module.finish())
 }
 
+/// Whether the code references data segments, which requires a data count section.
+fn uses_data_count(program: &ProgramModule) -> bool {
+    let Some(code_section) = &program.code_section else {
+        return false;
+    };
+    code_section
+        .code_section_entry
+        .iter()
+        .flat_map(|entry| &entry.body)
+        .any(|operator| {
+            matches!(
+                operator.opcode.map(OpCode::try_from),
+                Some(Ok(OpCode::BulkMemoryExtMemoryInit
+                    | OpCode::BulkMemoryExtDataDrop
+                    | OpCode::GcExtArrayNewData
+                    | OpCode::GcExtArrayInitData))
+            )
+        })
+}
+
+fn render_custom_sections(
+    program: &ProgramModule,
+    after: wasm_encoder::SectionId,
+    module: &mut wasm_encoder::Module,
+) -> Result<()> {
+    for section in &program.custom_sections {
+        if section.after_section_id.unwrap_or(0) == after as u32 {
+            section.render_wasm(module)?;
+        }
+    }
+    Ok(())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -208,6 +298,51 @@ mod tests {
         module.finish()
     }
 
+    /// Creates a WASM module with a start function and an export for testing
+    fn create_wasm_module_with_start() -> Vec<u8> {
+        use wasm_encoder::{CompositeInnerType, CompositeType, StartSection, SubType};
+        let mut module = Module::new();
+
+        // Type section: (func)
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(vec![], vec![])),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        // Function section: init and main
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        functions.function(0);
+        module.section(&functions);
+
+        // Export section
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("main", wasm_encoder::ExportKind::Func, 1);
+        module.section(&exports);
+
+        // Start section
+        module.section(&StartSection { function_index: 0 });
+
+        // Code section
+        let mut code = CodeSection::new();
+        for _ in 0..2 {
+            let mut func = Function::new(vec![]);
+            func.instruction(&Instruction::End);
+            code.function(&func);
+        }
+        module.section(&code);
+
+        module.finish()
+    }
+
     #[test]
     fn test_from_wasm_valid_module() {
         let wasm_bytes = create_minimal_wasm_module();
@@ -250,6 +385,31 @@ mod tests {
         assert!(result.is_err());
     }
 
+    #[test]
+    fn test_from_wasm_invalid_bytes_is_parse_error() {
+        let invalid_bytes = vec![0x00, 0x01, 0x02, 0x03];
+        let result = from_wasm(&invalid_bytes);
+        assert!(matches!(result, Err(Error::Parse(_))));
+    }
+
+    #[test]
+    fn test_render_wasm_missing_field_error() {
+        let program = ProgramModule {
+            function_section: Some(FunctionSection { type_idxs: vec![0] }),
+            code_section: Some(crate::libernet_wasm::CodeSection {
+                code_section_entry: vec![CodeSectionEntry {
+                    locals: vec![Locals {
+                        count: None,
+                        value_type: None,
+                    }],
+                    body: vec![],
+                }],
+            }),
+            ..Default::default()
+        };
+        assert!(matches!(render_wasm(program), Err(Error::Missing("Count"))));
+    }
+
     #[test]
     fn test_from_wasm_module_with_exports() {
         let wasm_bytes = create_wasm_module_with_exports();
@@ -341,6 +501,754 @@ mod tests {
         assert!(round_trip.export_section.is_some());
     }
 
+    #[test]
+    fn test_from_wasm_module_with_start() {
+        let wasm_bytes = create_wasm_module_with_start();
+        let program = from_wasm(&wasm_bytes).unwrap();
+
+        let start = program.start_section.unwrap();
+        assert_eq!(start.function_index, Some(0));
+    }
+
+    #[test]
+    fn test_round_trip_with_start() {
+        let original_wasm = create_wasm_module_with_start();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        // The start section must be emitted after exports and before code
+        wasmparser::Validator::new()
+            .validate_all(&rendered_wasm)
+            .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+
+        let round_trip = from_wasm(&rendered_wasm).unwrap();
+        assert_eq!(
+            round_trip.start_section,
+            Some(StartSection {
+                function_index: Some(0)
+            })
+        );
+    }
+
+    #[test]
+    fn test_render_wasm_start_missing_function_index() {
+        let mut program = from_wasm(&create_wasm_module_with_start()).unwrap();
+        program.start_section = Some(StartSection::default());
+        assert!(render_wasm(program).is_err());
+    }
+
+    /// Creates a WASM module with custom sections at the start, in the middle and at the end
+    fn create_wasm_module_with_custom_sections() -> Vec<u8> {
+        use wasm_encoder::{CompositeInnerType, CompositeType, CustomSection, SubType};
+        let mut module = Module::new();
+
+        module.section(&CustomSection {
+            name: "target_features".into(),
+            data: b"\x01+\x0bbulk-memory".as_slice().into(),
+        });
+
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(vec![], vec![])),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        module.section(&CustomSection {
+            name: "producers".into(),
+            data: b"\x00".as_slice().into(),
+        });
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        let mut names = wasm_encoder::NameSection::new();
+        names.module("test");
+        module.section(&names);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_from_wasm_module_with_custom_sections() {
+        let wasm_bytes = create_wasm_module_with_custom_sections();
+        let program = from_wasm(&wasm_bytes).unwrap();
+
+        let sections = &program.custom_sections;
+        assert_eq!(sections.len(), 3);
+        assert_eq!(sections[0].name.as_deref(), Some("target_features"));
+        assert_eq!(sections[0].after_section_id, Some(0));
+        assert_eq!(sections[1].name.as_deref(), Some("producers"));
+        assert_eq!(sections[1].data, Some(vec![0]));
+        assert_eq!(
+            sections[1].after_section_id,
+            Some(wasm_encoder::SectionId::Type as u32)
+        );
+        assert_eq!(sections[2].name.as_deref(), Some("name"));
+        assert_eq!(
+            sections[2].after_section_id,
+            Some(wasm_encoder::SectionId::Code as u32)
+        );
+        assert_eq!(sections[2].data, None);
+        assert_eq!(
+            program
+                .name_section()
+                .and_then(|names| names.module_name.as_deref()),
+            Some("test")
+        );
+    }
+
+    #[test]
+    fn test_round_trip_with_custom_sections() {
+        let original_wasm = create_wasm_module_with_custom_sections();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    #[test]
+    fn test_render_wasm_custom_section_unknown_position() {
+        let mut program = from_wasm(&create_minimal_wasm_module()).unwrap();
+        program
+            .custom_sections
+            .push(crate::libernet_wasm::CustomSection {
+                name: Some("extra".to_string()),
+                data: Some(vec![1, 2, 3]),
+                after_section_id: Some(200),
+                name_section: None,
+            });
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        let last = wasmparser::Parser::new(0)
+            .parse_all(&rendered_wasm)
+            .filter_map(|payload| match payload.unwrap() {
+                wasmparser::Payload::CustomSection(reader) => Some(reader.name().to_string()),
+                _ => None,
+            })
+            .last();
+        assert_eq!(last.as_deref(), Some("extra"));
+    }
+
+    fn create_wasm_module_with_shared_memory() -> Vec<u8> {
+        use wasm_encoder::{CompositeInnerType, CompositeType, MemArg, MemoryType, SubType};
+        let mut module = Module::new();
+
+        // Type section: (func (param i32) (result i32))
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(
+                    vec![ValType::I32],
+                    vec![ValType::I32],
+                )),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        let mut memories = wasm_encoder::MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: Some(16),
+            memory64: false,
+            shared: true,
+            page_size_log2: None,
+        });
+        module.section(&memories);
+
+        // Code section: bump a counter, wake a waiter and fence
+        let memarg = MemArg {
+            align: 2,
+            offset: 0,
+            memory_index: 0,
+        };
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::LocalGet(0));
+        func.instruction(&Instruction::I32Const(1));
+        func.instruction(&Instruction::I32AtomicRmwAdd(memarg));
+        func.instruction(&Instruction::Drop);
+        func.instruction(&Instruction::LocalGet(0));
+        func.instruction(&Instruction::I32Const(0));
+        func.instruction(&Instruction::I32Const(1));
+        func.instruction(&Instruction::I32AtomicRmwCmpxchg(memarg));
+        func.instruction(&Instruction::Drop);
+        func.instruction(&Instruction::AtomicFence);
+        func.instruction(&Instruction::LocalGet(0));
+        func.instruction(&Instruction::I32Const(1));
+        func.instruction(&Instruction::MemoryAtomicNotify(memarg));
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_round_trip_with_shared_memory() {
+        let original_wasm = create_wasm_module_with_shared_memory();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        assert_eq!(
+            program.memory_section.as_ref().unwrap().memory_types[0].shared,
+            Some(true)
+        );
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new_with_features(
+            wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::THREADS,
+        )
+        .validate_all(&rendered_wasm)
+        .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    fn create_wasm_module_with_multiple_memories() -> Vec<u8> {
+        use wasm_encoder::{
+            CompositeInnerType, CompositeType, ConstExpr, DataSection, MemArg, MemoryType, SubType,
+        };
+        let mut module = Module::new();
+
+        // Type section: (func (param i32 i64) (result i64))
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(
+                    vec![ValType::I32, ValType::I64],
+                    vec![ValType::I64],
+                )),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        // a regular 32-bit memory and a 64-bit memory with single-byte pages
+        let mut memories = wasm_encoder::MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        });
+        memories.memory(MemoryType {
+            minimum: 0x1_0000_0000,
+            maximum: Some(0x2_0000_0000),
+            memory64: true,
+            shared: false,
+            page_size_log2: Some(0),
+        });
+        module.section(&memories);
+
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::I32Const(0));
+        func.instruction(&Instruction::LocalGet(1));
+        func.instruction(&Instruction::LocalGet(0));
+        func.instruction(&Instruction::MemoryCopy {
+            dst_mem: 0,
+            src_mem: 1,
+        });
+        func.instruction(&Instruction::LocalGet(1));
+        func.instruction(&Instruction::I64Load(MemArg {
+            align: 3,
+            offset: 0xFFFF_0000,
+            memory_index: 1,
+        }));
+        func.instruction(&Instruction::MemorySize(1));
+        func.instruction(&Instruction::I64Add);
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        let mut data = DataSection::new();
+        data.active(1, &ConstExpr::i64_const(0x1_0000_0000), vec![1, 2, 3]);
+        module.section(&data);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_round_trip_with_multiple_memories() {
+        let original_wasm = create_wasm_module_with_multiple_memories();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        let memory_types = &program.memory_section.as_ref().unwrap().memory_types;
+        assert_eq!(memory_types.len(), 2);
+        assert_eq!(memory_types[1].memory64, Some(true));
+        assert_eq!(memory_types[1].initial, Some(0x1_0000_0000));
+        assert_eq!(memory_types[1].page_size_log2, Some(0));
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new_with_features(
+            wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::CUSTOM_PAGE_SIZES,
+        )
+        .validate_all(&rendered_wasm)
+        .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    #[test]
+    fn test_multiple_memories_need_custom_page_sizes() {
+        let rendered_wasm =
+            render_wasm(from_wasm(&create_wasm_module_with_multiple_memories()).unwrap()).unwrap();
+        assert!(
+            wasmparser::Validator::new()
+                .validate_all(&rendered_wasm)
+                .is_err()
+        );
+    }
+
+    #[test]
+    fn test_memory64_offset_round_trip() {
+        let mut program = from_wasm(&create_wasm_module_with_multiple_memories()).unwrap();
+        let operators = &mut program.code_section.as_mut().unwrap().code_section_entry[0].body;
+        let memarg = operators
+            .iter_mut()
+            .find_map(|operator| match &mut operator.operator {
+                Some(operator::Operator::Memarg(memarg)) => Some(memarg),
+                _ => None,
+            })
+            .unwrap();
+        assert_eq!(memarg.memory, Some(1));
+        assert_eq!(memarg.offset, Some(0xFFFF_0000));
+        memarg.offset = Some(0x1_0000_0000);
+
+        let rendered_wasm = render_wasm(program).unwrap();
+        wasmparser::Validator::new_with_features(
+            wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::CUSTOM_PAGE_SIZES,
+        )
+        .validate_all(&rendered_wasm)
+        .unwrap();
+        let program = from_wasm(&rendered_wasm).unwrap();
+        assert!(
+            program.code_section.unwrap().code_section_entry[0]
+                .body
+                .iter()
+                .any(|operator| matches!(
+                    &operator.operator,
+                    Some(operator::Operator::Memarg(MemArg {
+                        offset: Some(0x1_0000_0000),
+                        ..
+                    }))
+                ))
+        );
+    }
+
+    fn create_wasm_module_with_passive_data() -> Vec<u8> {
+        use wasm_encoder::{
+            CompositeInnerType, CompositeType, DataCountSection, DataSection, MemoryType, SubType,
+        };
+        let mut module = Module::new();
+
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(vec![], vec![])),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        let mut memories = wasm_encoder::MemorySection::new();
+        memories.memory(MemoryType {
+            minimum: 1,
+            maximum: None,
+            memory64: false,
+            shared: false,
+            page_size_log2: None,
+        });
+        module.section(&memories);
+
+        module.section(&DataCountSection { count: 2 });
+
+        // Code section: copy the second segment into memory and drop it
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::I32Const(0));
+        func.instruction(&Instruction::I32Const(0));
+        func.instruction(&Instruction::I32Const(3));
+        func.instruction(&Instruction::MemoryInit {
+            mem: 0,
+            data_index: 1,
+        });
+        func.instruction(&Instruction::DataDrop(1));
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        let mut data = DataSection::new();
+        data.passive(vec![1]);
+        data.passive(vec![2, 3, 4]);
+        module.section(&data);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_round_trip_with_data_count() {
+        let original_wasm = create_wasm_module_with_passive_data();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        assert_eq!(
+            program.data_count_section,
+            Some(DataCountSection { count: Some(2) })
+        );
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new()
+            .validate_all(&rendered_wasm)
+            .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    #[test]
+    fn test_render_wasm_derives_data_count() {
+        let original_wasm = create_wasm_module_with_passive_data();
+
+        let mut program = from_wasm(&original_wasm).unwrap();
+        program.data_count_section = None;
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new()
+            .validate_all(&rendered_wasm)
+            .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    #[test]
+    fn test_render_wasm_without_data_count() {
+        let program = from_wasm(&create_wasm_module_with_exports()).unwrap();
+        assert!(program.data_count_section.is_none());
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        let has_data_count = wasmparser::Parser::new(0)
+            .parse_all(&rendered_wasm)
+            .any(|payload| matches!(payload, Ok(wasmparser::Payload::DataCountSection { .. })));
+        assert!(!has_data_count);
+    }
+
+    fn create_wasm_module_with_reference_types() -> Vec<u8> {
+        use wasm_encoder::{
+            CompositeInnerType, CompositeType, ConstExpr, ElementSection, Elements, HeapType,
+            RefType, SubType, TableType,
+        };
+        let mut module = Module::new();
+
+        // Type section: (func)
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(vec![], vec![])),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        functions.function(0);
+        module.section(&functions);
+
+        let mut tables = wasm_encoder::TableSection::new();
+        tables.table(TableType {
+            element_type: RefType::FUNCREF,
+            table64: false,
+            minimum: 2,
+            maximum: None,
+            shared: false,
+        });
+        tables.table(TableType {
+            element_type: RefType::EXTERNREF,
+            table64: false,
+            minimum: 1,
+            maximum: None,
+            shared: false,
+        });
+        module.section(&tables);
+
+        // Element section: an active segment built from ref.func and ref.null
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            Elements::Expressions(
+                RefType::FUNCREF,
+                vec![ConstExpr::ref_func(0), ConstExpr::ref_null(HeapType::FUNC)].into(),
+            ),
+        );
+        module.section(&elements);
+
+        // Code section: shuffle references between the two tables
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::I32Const(1));
+        func.instruction(&Instruction::RefFunc(0));
+        func.instruction(&Instruction::TableSet(0));
+        func.instruction(&Instruction::RefNull(HeapType::EXTERN));
+        func.instruction(&Instruction::I32Const(1));
+        func.instruction(&Instruction::TableGrow(1));
+        func.instruction(&Instruction::Drop);
+        func.instruction(&Instruction::I32Const(0));
+        func.instruction(&Instruction::TableGet(0));
+        func.instruction(&Instruction::RefIsNull);
+        func.instruction(&Instruction::Drop);
+        func.instruction(&Instruction::I32Const(0));
+        func.instruction(&Instruction::RefNull(HeapType::FUNC));
+        func.instruction(&Instruction::TableSize(0));
+        func.instruction(&Instruction::TableFill(0));
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_round_trip_with_reference_types() {
+        let original_wasm = create_wasm_module_with_reference_types();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new()
+            .validate_all(&rendered_wasm)
+            .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    fn create_wasm_module_with_typed_references() -> Vec<u8> {
+        use wasm_encoder::{ConstExpr, ElementSection, Elements, HeapType, RefType, TableType};
+        let mut module = Module::new();
+
+        // Type section: (func)
+        let mut types = TypeSection::new();
+        types.ty().function([], []);
+        module.section(&types);
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        // Table section: (table 1 (ref 0) (ref.func 0))
+        let typed_ref = RefType {
+            nullable: false,
+            heap_type: HeapType::Concrete(0),
+        };
+        let mut tables = wasm_encoder::TableSection::new();
+        tables.table_with_init(
+            TableType {
+                element_type: typed_ref,
+                table64: false,
+                minimum: 1,
+                maximum: None,
+                shared: false,
+            },
+            &ConstExpr::ref_func(0),
+        );
+        module.section(&tables);
+
+        // Element section: (elem (table 0) (i32.const 0) (ref 0) (ref.func 0))
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            Elements::Expressions(typed_ref, vec![ConstExpr::ref_func(0)].into()),
+        );
+        module.section(&elements);
+
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_round_trip_with_typed_references() {
+        let original_wasm = create_wasm_module_with_typed_references();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        let table_type = &program.table_section.as_ref().unwrap().types[0];
+        assert_eq!(
+            table_type.reference,
+            Some(ReferenceType {
+                nullable: Some(false),
+                heap_type: Some(HeapType {
+                    heap_type: Some(heap_type::HeapType::ConcreteType(0)),
+                    shared: None,
+                }),
+            })
+        );
+        assert!(table_type.init_expr.is_some());
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all())
+            .validate_all(&rendered_wasm)
+            .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    fn create_wasm_module_with_imports() -> Vec<u8> {
+        use wasm_encoder::{
+            CompositeInnerType, CompositeType, EntityType, GlobalType, ImportSection, MemoryType,
+            RefType, SubType, TableType, TagKind, TagType,
+        };
+        let mut module = Module::new();
+
+        // Type section: (func (param i32))
+        let mut types = TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: CompositeType {
+                inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(
+                    vec![ValType::I32],
+                    vec![],
+                )),
+                shared: false,
+                descriptor: None,
+                describes: None,
+            },
+        });
+        module.section(&types);
+
+        // Import section: one import of every kind, as emitted by wasm-ld --import-memory
+        let mut imports = ImportSection::new();
+        imports.import("env", "log", EntityType::Function(0));
+        imports.import(
+            "env",
+            "__indirect_function_table",
+            EntityType::Table(TableType {
+                element_type: RefType::FUNCREF,
+                table64: false,
+                minimum: 1,
+                maximum: None,
+                shared: false,
+            }),
+        );
+        imports.import(
+            "env",
+            "memory",
+            EntityType::Memory(MemoryType {
+                memory64: false,
+                shared: false,
+                minimum: 2,
+                maximum: Some(256),
+                page_size_log2: None,
+            }),
+        );
+        imports.import(
+            "env",
+            "__stack_pointer",
+            EntityType::Global(GlobalType {
+                val_type: ValType::I32,
+                mutable: true,
+                shared: false,
+            }),
+        );
+        imports.import(
+            "env",
+            "__cpp_exception",
+            EntityType::Tag(TagType {
+                kind: TagKind::Exception,
+                func_type_idx: 0,
+            }),
+        );
+        module.section(&imports);
+
+        let mut functions = wasm_encoder::FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+
+        // Code section: forward the parameter to the imported function
+        let mut code = CodeSection::new();
+        let mut func = Function::new(vec![]);
+        func.instruction(&Instruction::LocalGet(0));
+        func.instruction(&Instruction::Call(0));
+        func.instruction(&Instruction::End);
+        code.function(&func);
+        module.section(&code);
+
+        module.finish()
+    }
+
+    #[test]
+    fn test_round_trip_with_imports() {
+        let original_wasm = create_wasm_module_with_imports();
+
+        let program = from_wasm(&original_wasm).unwrap();
+        assert_eq!(program.import_section.as_ref().unwrap().imports.len(), 5);
+        let rendered_wasm = render_wasm(program).unwrap();
+
+        wasmparser::Validator::new_with_features(
+            wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::EXCEPTIONS,
+        )
+        .validate_all(&rendered_wasm)
+        .unwrap();
+        assert_eq!(rendered_wasm, original_wasm);
+    }
+
+    #[test]
+    fn test_render_wasm_import_missing_type() {
+        let mut program = from_wasm(&create_wasm_module_with_imports()).unwrap();
+        program.import_section.as_mut().unwrap().imports[0].ty = None;
+        assert!(matches!(
+            render_wasm(program),
+            Err(Error::Missing("Import type"))
+        ));
+    }
+
     #[test]
     fn test_program_module_structure() {
         let wasm_bytes = create_minimal_wasm_module();
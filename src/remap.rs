@@ -0,0 +1,1188 @@
+use std::collections::HashMap;
+
+use crate::libernet_wasm::*;
+use crate::operators::simple;
+
+/// The index spaces of a module that items refer to each other through.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum IndexSpace {
+    Function,
+    Type,
+    Global,
+    Table,
+    Memory,
+    Tag,
+    Data,
+    Element,
+}
+
+/// The number of imported items in an index space, which come before the defined ones.
+pub(crate) fn imported_count(program: &ProgramModule, space: IndexSpace) -> u32 {
+    program
+        .import_section
+        .iter()
+        .flat_map(|section| &section.imports)
+        .filter(|import| {
+            let import_space = match import.ty {
+                Some(import::Ty::FunctionType(_)) => IndexSpace::Function,
+                Some(import::Ty::Table(_)) => IndexSpace::Table,
+                Some(import::Ty::Memory(_)) => IndexSpace::Memory,
+                Some(import::Ty::Global(_)) => IndexSpace::Global,
+                Some(import::Ty::Tag(_)) => IndexSpace::Tag,
+                None => return false,
+            };
+            import_space == space
+        })
+        .count() as u32
+}
+
+/// Appends a global initialized with the constant `init` after every existing global, so no
+/// index moves, and returns its index.
+pub(crate) fn append_global(
+    program: &mut ProgramModule,
+    global_type: GlobalType,
+    init: Operator,
+) -> u32 {
+    let imported_globals = imported_count(program, IndexSpace::Global);
+    let global_section = program.global_section.get_or_insert_with(Default::default);
+    let global_index = imported_globals + global_section.globals.len() as u32;
+    global_section.globals.push(Global {
+        r#type: Some(global_type),
+        init_expr: Some(Expression {
+            operators: vec![init, simple(OpCode::End)],
+        }),
+    });
+    global_index
+}
+
+/// Keeps the items whose entry in `live` is set, along with any item past its end.
+pub(crate) fn retain_live<T>(items: &mut Vec<T>, live: &[bool]) {
+    let mut live = live.iter();
+    items.retain(|_| live.next().copied().unwrap_or(true));
+}
+
+/// The new index of every item once the ones not in `live` are removed.
+pub(crate) fn renumbering(live: &[bool]) -> Vec<u32> {
+    live.iter()
+        .scan(0, |next, &live| {
+            let index = *next;
+            *next += live as u32;
+            Some(index)
+        })
+        .collect()
+}
+
+/// Every operator of the module, in function bodies and in constant expressions.
+pub(crate) fn operators_mut(program: &mut ProgramModule) -> impl Iterator<Item = &mut Operator> {
+    let bodies = program
+        .code_section
+        .iter_mut()
+        .flat_map(|section| section.code_section_entry.iter_mut())
+        .flat_map(|entry| entry.body.iter_mut());
+    let tables = program
+        .table_section
+        .iter_mut()
+        .flat_map(|section| section.types.iter_mut())
+        .flat_map(|table| table.init_expr.iter_mut());
+    let globals = program
+        .global_section
+        .iter_mut()
+        .flat_map(|section| section.globals.iter_mut())
+        .flat_map(|global| global.init_expr.iter_mut());
+    let elements = program
+        .element_section
+        .iter_mut()
+        .flat_map(|section| section.elements.iter_mut())
+        .flat_map(|element| {
+            let items = match &mut element.items {
+                Some(element::Items::Expressions(expressions)) => {
+                    expressions.expressions.iter_mut()
+                }
+                _ => Default::default(),
+            };
+            element
+                .kind
+                .iter_mut()
+                .flat_map(|kind| kind.expression.iter_mut())
+                .chain(items)
+        });
+    let datas = program
+        .data_section
+        .iter_mut()
+        .flat_map(|section| section.datas.iter_mut())
+        .flat_map(|data| data.kind.iter_mut())
+        .flat_map(|kind| kind.expression.iter_mut());
+    let expressions = tables
+        .chain(globals)
+        .chain(elements)
+        .chain(datas)
+        .flat_map(|expression| expression.operators.iter_mut());
+    bodies.chain(expressions)
+}
+
+fn visit_value_type(value_type: &mut ValueType, visit: &mut impl FnMut(IndexSpace, &mut u32)) {
+    visit_reference_type(value_type.reference.as_mut(), visit);
+}
+
+fn visit_heap_type(heap_type: &mut HeapType, visit: &mut impl FnMut(IndexSpace, &mut u32)) {
+    if let Some(heap_type::HeapType::ConcreteType(index)) = &mut heap_type.heap_type {
+        visit(IndexSpace::Type, index);
+    }
+}
+
+fn visit_reference_type(
+    reference_type: Option<&mut ReferenceType>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    if let Some(heap_type) = reference_type.and_then(|ty| ty.heap_type.as_mut()) {
+        visit_heap_type(heap_type, visit);
+    }
+}
+
+fn visit_block_type(
+    block_type: Option<&mut BlockType>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    match block_type.and_then(|block_type| block_type.block_type.as_mut()) {
+        Some(block_type::BlockType::ValueType(value_type)) => visit_value_type(value_type, visit),
+        Some(block_type::BlockType::TypeIndex(index)) => visit(IndexSpace::Type, index),
+        Some(block_type::BlockType::Empty(_)) | None => {}
+    }
+}
+
+fn visit_field_type(
+    field_type: Option<&mut FieldType>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    if let Some(field_type::StorageType::ValueType(value_type)) =
+        field_type.and_then(|field_type| field_type.storage_type.as_mut())
+    {
+        visit_value_type(value_type, visit);
+    }
+}
+
+fn visit_option(
+    space: IndexSpace,
+    index: Option<&mut u32>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    if let Some(index) = index {
+        visit(space, index);
+    }
+}
+
+/// Visits every index held by the operand of an operator. The match is exhaustive on purpose:
+/// a new operand kind doesn't compile until its indices are accounted for.
+pub(crate) fn visit_operator_indices(
+    operator: &mut Operator,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    let Some(operand) = &mut operator.operator else {
+        return;
+    };
+    match operand {
+        operator::Operator::BlockType(block_type) => visit_block_type(Some(block_type), visit),
+        operator::Operator::FunctionIndex(index) => visit(IndexSpace::Function, index),
+        operator::Operator::CallIndirect(call_indirect) => {
+            visit_option(IndexSpace::Type, call_indirect.type_index.as_mut(), visit);
+            visit_option(IndexSpace::Table, call_indirect.table_index.as_mut(), visit);
+        }
+        operator::Operator::GlobalIndex(index) => visit(IndexSpace::Global, index),
+        operator::Operator::Memarg(memarg) => {
+            visit_option(IndexSpace::Memory, memarg.memory.as_mut(), visit)
+        }
+        operator::Operator::Mem(index) => visit(IndexSpace::Memory, index),
+        operator::Operator::MemoryInit(memory_init) => {
+            visit_option(IndexSpace::Data, memory_init.data_index.as_mut(), visit);
+            visit_option(IndexSpace::Memory, memory_init.address.as_mut(), visit);
+        }
+        operator::Operator::DataIndex(index) => visit(IndexSpace::Data, index),
+        operator::Operator::MemoryCopy(memory_copy) => {
+            visit_option(
+                IndexSpace::Memory,
+                memory_copy.destination_address.as_mut(),
+                visit,
+            );
+            visit_option(
+                IndexSpace::Memory,
+                memory_copy.source_address.as_mut(),
+                visit,
+            );
+        }
+        operator::Operator::TableInit(table_init) => {
+            visit_option(
+                IndexSpace::Element,
+                table_init.element_index.as_mut(),
+                visit,
+            );
+            visit_option(IndexSpace::Table, table_init.table.as_mut(), visit);
+        }
+        operator::Operator::ElementIndex(index) => visit(IndexSpace::Element, index),
+        operator::Operator::TableCopy(table_copy) => {
+            visit_option(IndexSpace::Table, table_copy.dst_table.as_mut(), visit);
+            visit_option(IndexSpace::Table, table_copy.src_table.as_mut(), visit);
+        }
+        operator::Operator::TryTable(try_table) => {
+            visit_block_type(try_table.r#type.as_mut(), visit);
+            for catch in &mut try_table.catches {
+                let tag = match &mut catch.catch_element {
+                    Some(catch_element::CatchElement::One(one)) => one.tag.as_mut(),
+                    Some(catch_element::CatchElement::OneRef(one_ref)) => one_ref.tag.as_mut(),
+                    Some(
+                        catch_element::CatchElement::All(_)
+                        | catch_element::CatchElement::AllRef(_),
+                    )
+                    | None => None,
+                };
+                visit_option(IndexSpace::Tag, tag, visit);
+            }
+        }
+        operator::Operator::ThrowOp(throw) => {
+            visit_option(IndexSpace::Tag, throw.tag_index.as_mut(), visit)
+        }
+        operator::Operator::TagIndex(index) => visit(IndexSpace::Tag, index),
+        operator::Operator::MemargLane(memarg_lane) => {
+            let memory = memarg_lane
+                .memarg
+                .as_mut()
+                .and_then(|memarg| memarg.memory.as_mut());
+            visit_option(IndexSpace::Memory, memory, visit);
+        }
+        operator::Operator::HeapType(heap_type) => visit_heap_type(heap_type, visit),
+        operator::Operator::SelectType(value_type) => visit_value_type(value_type, visit),
+        operator::Operator::TableIndex(index) => visit(IndexSpace::Table, index),
+        operator::Operator::TypeIndex(index) => visit(IndexSpace::Type, index),
+        operator::Operator::StructField(struct_field) => {
+            visit_option(IndexSpace::Type, struct_field.type_index.as_mut(), visit)
+        }
+        operator::Operator::ArrayNewFixed(array_new_fixed) => {
+            visit_option(IndexSpace::Type, array_new_fixed.type_index.as_mut(), visit)
+        }
+        operator::Operator::ArrayData(array_data) => {
+            visit_option(IndexSpace::Type, array_data.type_index.as_mut(), visit);
+            visit_option(IndexSpace::Data, array_data.data_index.as_mut(), visit);
+        }
+        operator::Operator::ArrayElem(array_elem) => {
+            visit_option(IndexSpace::Type, array_elem.type_index.as_mut(), visit);
+            visit_option(
+                IndexSpace::Element,
+                array_elem.element_index.as_mut(),
+                visit,
+            );
+        }
+        operator::Operator::ArrayCopy(array_copy) => {
+            visit_option(IndexSpace::Type, array_copy.dst_type_index.as_mut(), visit);
+            visit_option(IndexSpace::Type, array_copy.src_type_index.as_mut(), visit);
+        }
+        operator::Operator::BrOnCast(br_on_cast) => {
+            visit_reference_type(br_on_cast.from_type.as_mut(), visit);
+            visit_reference_type(br_on_cast.to_type.as_mut(), visit);
+        }
+        operator::Operator::RelativeDepth(_)
+        | operator::Operator::Targets(_)
+        | operator::Operator::LocalIndex(_)
+        | operator::Operator::I32Value(_)
+        | operator::Operator::I64Value(_)
+        | operator::Operator::F32Value(_)
+        | operator::Operator::F64Value(_)
+        | operator::Operator::V128Value(_)
+        | operator::Operator::Lanes(_)
+        | operator::Operator::Lane(_) => {}
+    }
+}
+
+fn visit_sub_type(sub_type: &mut SubType, visit: &mut impl FnMut(IndexSpace, &mut u32)) {
+    visit_option(IndexSpace::Type, sub_type.supertype_index.as_mut(), visit);
+    match &mut sub_type.kind {
+        Some(sub_type::Kind::Func(func_type)) => {
+            for value_type in func_type.params.iter_mut().chain(&mut func_type.results) {
+                visit_value_type(value_type, visit);
+            }
+        }
+        Some(sub_type::Kind::StructType(struct_type)) => {
+            for field_type in &mut struct_type.fields {
+                visit_field_type(Some(field_type), visit);
+            }
+        }
+        Some(sub_type::Kind::ArrayType(array_type)) => {
+            visit_field_type(array_type.element_type.as_mut(), visit)
+        }
+        None => {}
+    }
+}
+
+fn visit_global_type(
+    global_type: Option<&mut GlobalType>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    if let Some(value_type) = global_type.and_then(|ty| ty.content_type.as_mut()) {
+        visit_value_type(value_type, visit);
+    }
+}
+
+fn visit_table_type(table_type: &mut TableType, visit: &mut impl FnMut(IndexSpace, &mut u32)) {
+    visit_reference_type(table_type.reference.as_mut(), visit);
+}
+
+fn visit_name_map(
+    space: IndexSpace,
+    names: Option<&mut NameMap>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    let namings = names.into_iter().flat_map(|names| names.names.iter_mut());
+    for naming in namings {
+        visit_option(space, naming.index.as_mut(), visit);
+    }
+}
+
+fn visit_indirect_name_map(
+    space: IndexSpace,
+    names: Option<&mut IndirectNameMap>,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    let namings = names.into_iter().flat_map(|names| names.names.iter_mut());
+    for naming in namings {
+        visit_option(space, naming.index.as_mut(), visit);
+    }
+}
+
+/// Visits every index of the module referring to an item of an index space: in section
+/// entries, in operator payloads and in the name section.
+pub(crate) fn visit_indices(
+    program: &mut ProgramModule,
+    visit: &mut impl FnMut(IndexSpace, &mut u32),
+) {
+    let types = program
+        .type_section
+        .iter_mut()
+        .flat_map(|section| section.types.iter_mut());
+    for sub_type in types {
+        visit_sub_type(sub_type, visit);
+    }
+    let imports = program
+        .import_section
+        .iter_mut()
+        .flat_map(|section| section.imports.iter_mut());
+    for import in imports {
+        match &mut import.ty {
+            Some(import::Ty::FunctionType(index)) => visit(IndexSpace::Type, index),
+            Some(import::Ty::Global(global_type)) => visit_global_type(Some(global_type), visit),
+            Some(import::Ty::Tag(tag_type)) => {
+                visit_option(IndexSpace::Type, tag_type.function_type_idx.as_mut(), visit)
+            }
+            Some(import::Ty::Table(table_type)) => visit_table_type(table_type, visit),
+            Some(import::Ty::Memory(_)) | None => {}
+        }
+    }
+    let function_types = program
+        .function_section
+        .iter_mut()
+        .flat_map(|section| section.type_idxs.iter_mut());
+    for index in function_types {
+        visit(IndexSpace::Type, index);
+    }
+    let tables = program
+        .table_section
+        .iter_mut()
+        .flat_map(|section| section.types.iter_mut());
+    for table_type in tables {
+        visit_table_type(table_type, visit);
+    }
+    let tags = program
+        .tag_section
+        .iter_mut()
+        .flat_map(|section| section.tags.iter_mut());
+    for tag_type in tags {
+        visit_option(IndexSpace::Type, tag_type.function_type_idx.as_mut(), visit);
+    }
+    let globals = program
+        .global_section
+        .iter_mut()
+        .flat_map(|section| section.globals.iter_mut());
+    for global in globals {
+        visit_global_type(global.r#type.as_mut(), visit);
+    }
+    let exports = program
+        .export_section
+        .iter_mut()
+        .flat_map(|section| section.exports.iter_mut());
+    for export in exports {
+        let space = match export.kind.map(ExternalKind::try_from) {
+            Some(Ok(ExternalKind::ExtFunc | ExternalKind::ExtFuncExact)) => IndexSpace::Function,
+            Some(Ok(ExternalKind::ExtTable)) => IndexSpace::Table,
+            Some(Ok(ExternalKind::ExtMemory)) => IndexSpace::Memory,
+            Some(Ok(ExternalKind::ExtGlobal)) => IndexSpace::Global,
+            Some(Ok(ExternalKind::ExtTag)) => IndexSpace::Tag,
+            Some(Err(_)) | None => continue,
+        };
+        visit_option(space, export.index.as_mut(), visit);
+    }
+    let start = program
+        .start_section
+        .as_mut()
+        .and_then(|section| section.function_index.as_mut());
+    visit_option(IndexSpace::Function, start, visit);
+    let elements = program
+        .element_section
+        .iter_mut()
+        .flat_map(|section| section.elements.iter_mut());
+    for element in elements {
+        let table = element
+            .kind
+            .as_mut()
+            .and_then(|kind| kind.table_index.as_mut());
+        visit_option(IndexSpace::Table, table, visit);
+        match &mut element.items {
+            Some(element::Items::Functions(functions)) => {
+                for index in &mut functions.functions {
+                    visit(IndexSpace::Function, index);
+                }
+            }
+            Some(element::Items::Expressions(expressions)) => {
+                visit_reference_type(expressions.reference.as_mut(), visit)
+            }
+            None => {}
+        }
+    }
+    let locals = program
+        .code_section
+        .iter_mut()
+        .flat_map(|section| section.code_section_entry.iter_mut())
+        .flat_map(|entry| entry.locals.iter_mut())
+        .filter_map(|locals| locals.value_type.as_mut());
+    for value_type in locals {
+        visit_value_type(value_type, visit);
+    }
+    let datas = program
+        .data_section
+        .iter_mut()
+        .flat_map(|section| section.datas.iter_mut());
+    for data in datas {
+        let memory = data
+            .kind
+            .as_mut()
+            .and_then(|kind| kind.memory_index.as_mut());
+        visit_option(IndexSpace::Memory, memory, visit);
+    }
+    for operator in operators_mut(program) {
+        visit_operator_indices(operator, visit);
+    }
+    let name_sections = program
+        .custom_sections
+        .iter_mut()
+        .filter_map(|section| section.name_section.as_mut());
+    for names in name_sections {
+        visit_name_map(IndexSpace::Function, names.function_names.as_mut(), visit);
+        visit_indirect_name_map(IndexSpace::Function, names.local_names.as_mut(), visit);
+        visit_indirect_name_map(IndexSpace::Function, names.label_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Type, names.type_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Table, names.table_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Memory, names.memory_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Global, names.global_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Element, names.element_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Data, names.data_names.as_mut(), visit);
+        visit_indirect_name_map(IndexSpace::Type, names.field_names.as_mut(), visit);
+        visit_name_map(IndexSpace::Tag, names.tag_names.as_mut(), visit);
+    }
+}
+
+/// A mapping of indices per index space, for transforms that add, remove or reorder items.
+/// Index spaces without a mapping keep their indices.
+#[derive(Default)]
+pub struct Remapping<'a> {
+    maps: HashMap<IndexSpace, Box<dyn Fn(u32) -> u32 + 'a>>,
+}
+
+impl<'a> Remapping<'a> {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Maps the indices of `space` through `map`, replacing any previous mapping for it.
+    pub fn with(mut self, space: IndexSpace, map: impl Fn(u32) -> u32 + 'a) -> Self {
+        self.maps.insert(space, Box::new(map));
+        self
+    }
+
+    pub fn map(&self, space: IndexSpace, index: u32) -> u32 {
+        self.maps.get(&space).map_or(index, |map| map(index))
+    }
+
+    /// Rewrites every reference of the module to the mapped index spaces, in section entries,
+    /// operator payloads and the name section.
+    pub fn apply(&self, program: &mut ProgramModule) {
+        if self.maps.is_empty() {
+            return;
+        }
+        visit_indices(program, &mut |space, index| {
+            *index = self.map(space, *index)
+        });
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Moves every index space to its own range, so a test can tell which space an index was
+    /// mapped as
+    fn shifted() -> Remapping<'static> {
+        [
+            (IndexSpace::Function, 100),
+            (IndexSpace::Type, 200),
+            (IndexSpace::Global, 300),
+            (IndexSpace::Table, 400),
+            (IndexSpace::Memory, 500),
+            (IndexSpace::Tag, 600),
+            (IndexSpace::Data, 700),
+            (IndexSpace::Element, 800),
+        ]
+        .into_iter()
+        .fold(Remapping::new(), |remapping, (space, offset)| {
+            remapping.with(space, move |index| index + offset)
+        })
+    }
+
+    /// Numbers the operand variants, failing to compile when one is added without a case below
+    fn variant(operand: &operator::Operator) -> usize {
+        match operand {
+            operator::Operator::BlockType(_) => 0,
+            operator::Operator::RelativeDepth(_) => 1,
+            operator::Operator::Targets(_) => 2,
+            operator::Operator::FunctionIndex(_) => 3,
+            operator::Operator::CallIndirect(_) => 4,
+            operator::Operator::LocalIndex(_) => 5,
+            operator::Operator::GlobalIndex(_) => 6,
+            operator::Operator::Memarg(_) => 7,
+            operator::Operator::Mem(_) => 8,
+            operator::Operator::I32Value(_) => 9,
+            operator::Operator::I64Value(_) => 10,
+            operator::Operator::F32Value(_) => 11,
+            operator::Operator::F64Value(_) => 12,
+            operator::Operator::MemoryInit(_) => 13,
+            operator::Operator::DataIndex(_) => 14,
+            operator::Operator::MemoryCopy(_) => 15,
+            operator::Operator::TableInit(_) => 16,
+            operator::Operator::ElementIndex(_) => 17,
+            operator::Operator::TableCopy(_) => 18,
+            operator::Operator::TryTable(_) => 19,
+            operator::Operator::ThrowOp(_) => 20,
+            operator::Operator::TagIndex(_) => 21,
+            operator::Operator::V128Value(_) => 22,
+            operator::Operator::Lanes(_) => 23,
+            operator::Operator::Lane(_) => 24,
+            operator::Operator::MemargLane(_) => 25,
+            operator::Operator::HeapType(_) => 26,
+            operator::Operator::SelectType(_) => 27,
+            operator::Operator::TableIndex(_) => 28,
+            operator::Operator::TypeIndex(_) => 29,
+            operator::Operator::StructField(_) => 30,
+            operator::Operator::ArrayNewFixed(_) => 31,
+            operator::Operator::ArrayData(_) => 32,
+            operator::Operator::ArrayElem(_) => 33,
+            operator::Operator::ArrayCopy(_) => 34,
+            operator::Operator::BrOnCast(_) => 35,
+        }
+    }
+
+    const VARIANTS: usize = 36;
+
+    fn concrete(index: u32) -> ValueType {
+        ValueType {
+            reference: Some(concrete_reference(index)),
+            ..Default::default()
+        }
+    }
+
+    fn concrete_heap_type(index: u32) -> HeapType {
+        HeapType {
+            heap_type: Some(heap_type::HeapType::ConcreteType(index)),
+            shared: Some(false),
+        }
+    }
+
+    fn concrete_reference(index: u32) -> ReferenceType {
+        ReferenceType {
+            nullable: Some(true),
+            heap_type: Some(concrete_heap_type(index)),
+        }
+    }
+
+    fn type_index_block(index: u32) -> BlockType {
+        BlockType {
+            block_type: Some(block_type::BlockType::TypeIndex(index)),
+        }
+    }
+
+    fn memarg(memory: u32) -> MemArg {
+        MemArg {
+            align: Some(2),
+            max_align: Some(2),
+            offset: Some(8),
+            memory: Some(memory),
+        }
+    }
+
+    fn try_table(type_index: u32, first_tag: u32, second_tag: u32) -> TryTableOp {
+        TryTableOp {
+            r#type: Some(type_index_block(type_index)),
+            catches: vec![
+                CatchElement {
+                    catch_element: Some(catch_element::CatchElement::One(CatchOne {
+                        tag: Some(first_tag),
+                        label: Some(1),
+                    })),
+                },
+                CatchElement {
+                    catch_element: Some(catch_element::CatchElement::OneRef(CatchOneRef {
+                        tag: Some(second_tag),
+                        label: Some(2),
+                    })),
+                },
+                CatchElement {
+                    catch_element: Some(catch_element::CatchElement::All(CatchAllElements {
+                        label: Some(3),
+                    })),
+                },
+                CatchElement {
+                    catch_element: Some(catch_element::CatchElement::AllRef(CatchAllRef {
+                        label: Some(4),
+                    })),
+                },
+            ],
+        }
+    }
+
+    fn br_on_cast(from_type: u32, to_type: u32) -> BrOnCastOp {
+        BrOnCastOp {
+            relative_depth: Some(1),
+            from_type: Some(ReferenceType {
+                nullable: Some(true),
+                heap_type: Some(concrete_heap_type(from_type)),
+            }),
+            to_type: Some(ReferenceType {
+                nullable: Some(false),
+                heap_type: Some(concrete_heap_type(to_type)),
+            }),
+        }
+    }
+
+    /// Every operand variant, before and after `shifted`
+    fn operand_cases() -> Vec<(operator::Operator, operator::Operator)> {
+        use operator::Operator as Operand;
+        let unchanged = |operand: Operand| (operand.clone(), operand);
+        vec![
+            (
+                Operand::BlockType(type_index_block(1)),
+                Operand::BlockType(type_index_block(201)),
+            ),
+            (
+                Operand::BlockType(BlockType {
+                    block_type: Some(block_type::BlockType::ValueType(concrete(1))),
+                }),
+                Operand::BlockType(BlockType {
+                    block_type: Some(block_type::BlockType::ValueType(concrete(201))),
+                }),
+            ),
+            unchanged(Operand::BlockType(BlockType {
+                block_type: Some(block_type::BlockType::Empty(0)),
+            })),
+            unchanged(Operand::RelativeDepth(1)),
+            unchanged(Operand::Targets(BreakTargets {
+                default: Some(1),
+                targets: vec![2, 3],
+            })),
+            (Operand::FunctionIndex(1), Operand::FunctionIndex(101)),
+            (
+                Operand::CallIndirect(CallIndirectOp {
+                    type_index: Some(1),
+                    table_index: Some(2),
+                }),
+                Operand::CallIndirect(CallIndirectOp {
+                    type_index: Some(201),
+                    table_index: Some(402),
+                }),
+            ),
+            unchanged(Operand::LocalIndex(1)),
+            (Operand::GlobalIndex(1), Operand::GlobalIndex(301)),
+            (Operand::Memarg(memarg(1)), Operand::Memarg(memarg(501))),
+            (Operand::Mem(1), Operand::Mem(501)),
+            unchanged(Operand::I32Value(1)),
+            unchanged(Operand::I64Value(1)),
+            unchanged(Operand::F32Value(1)),
+            unchanged(Operand::F64Value(1)),
+            (
+                Operand::MemoryInit(MemoryInitOp {
+                    data_index: Some(1),
+                    address: Some(2),
+                }),
+                Operand::MemoryInit(MemoryInitOp {
+                    data_index: Some(701),
+                    address: Some(502),
+                }),
+            ),
+            (Operand::DataIndex(1), Operand::DataIndex(701)),
+            (
+                Operand::MemoryCopy(MemoryCopyOp {
+                    destination_address: Some(1),
+                    source_address: Some(2),
+                }),
+                Operand::MemoryCopy(MemoryCopyOp {
+                    destination_address: Some(501),
+                    source_address: Some(502),
+                }),
+            ),
+            (
+                Operand::TableInit(TableInitOp {
+                    element_index: Some(1),
+                    table: Some(2),
+                }),
+                Operand::TableInit(TableInitOp {
+                    element_index: Some(801),
+                    table: Some(402),
+                }),
+            ),
+            (Operand::ElementIndex(1), Operand::ElementIndex(801)),
+            (
+                Operand::TableCopy(TableCopyOp {
+                    dst_table: Some(1),
+                    src_table: Some(2),
+                }),
+                Operand::TableCopy(TableCopyOp {
+                    dst_table: Some(401),
+                    src_table: Some(402),
+                }),
+            ),
+            (
+                Operand::TryTable(try_table(1, 1, 2)),
+                Operand::TryTable(try_table(201, 601, 602)),
+            ),
+            (
+                Operand::ThrowOp(ThrowOp { tag_index: Some(1) }),
+                Operand::ThrowOp(ThrowOp {
+                    tag_index: Some(601),
+                }),
+            ),
+            (Operand::TagIndex(1), Operand::TagIndex(601)),
+            unchanged(Operand::V128Value(vec![1; 16])),
+            unchanged(Operand::Lanes(vec![1; 16])),
+            unchanged(Operand::Lane(1)),
+            (
+                Operand::MemargLane(MemArgLane {
+                    memarg: Some(memarg(1)),
+                    lane: Some(1),
+                }),
+                Operand::MemargLane(MemArgLane {
+                    memarg: Some(memarg(501)),
+                    lane: Some(1),
+                }),
+            ),
+            (
+                Operand::HeapType(concrete_heap_type(1)),
+                Operand::HeapType(concrete_heap_type(201)),
+            ),
+            unchanged(Operand::HeapType(HeapType {
+                heap_type: Some(heap_type::HeapType::AbstractType(RefType::RefFunc as i32)),
+                shared: Some(false),
+            })),
+            (
+                Operand::SelectType(concrete(1)),
+                Operand::SelectType(concrete(201)),
+            ),
+            unchanged(Operand::SelectType(ValueType {
+                value_type: Some(PlainType::ValueTypeI32 as i32),
+                ..Default::default()
+            })),
+            (Operand::TableIndex(1), Operand::TableIndex(401)),
+            (Operand::TypeIndex(1), Operand::TypeIndex(201)),
+            (
+                Operand::StructField(StructFieldOp {
+                    type_index: Some(1),
+                    field_index: Some(2),
+                }),
+                Operand::StructField(StructFieldOp {
+                    type_index: Some(201),
+                    field_index: Some(2),
+                }),
+            ),
+            (
+                Operand::ArrayNewFixed(ArrayNewFixedOp {
+                    type_index: Some(1),
+                    size: Some(2),
+                }),
+                Operand::ArrayNewFixed(ArrayNewFixedOp {
+                    type_index: Some(201),
+                    size: Some(2),
+                }),
+            ),
+            (
+                Operand::ArrayData(ArrayDataOp {
+                    type_index: Some(1),
+                    data_index: Some(2),
+                }),
+                Operand::ArrayData(ArrayDataOp {
+                    type_index: Some(201),
+                    data_index: Some(702),
+                }),
+            ),
+            (
+                Operand::ArrayElem(ArrayElemOp {
+                    type_index: Some(1),
+                    element_index: Some(2),
+                }),
+                Operand::ArrayElem(ArrayElemOp {
+                    type_index: Some(201),
+                    element_index: Some(802),
+                }),
+            ),
+            (
+                Operand::ArrayCopy(ArrayCopyOp {
+                    dst_type_index: Some(1),
+                    src_type_index: Some(2),
+                }),
+                Operand::ArrayCopy(ArrayCopyOp {
+                    dst_type_index: Some(201),
+                    src_type_index: Some(202),
+                }),
+            ),
+            (
+                Operand::BrOnCast(br_on_cast(1, 2)),
+                Operand::BrOnCast(br_on_cast(201, 202)),
+            ),
+        ]
+    }
+
+    #[test]
+    fn test_operand_cases_cover_every_variant() {
+        let mut covered = [false; VARIANTS];
+        for (operand, _) in operand_cases() {
+            covered[variant(&operand)] = true;
+        }
+        assert_eq!(covered, [true; VARIANTS]);
+    }
+
+    #[test]
+    fn test_remap_operands() {
+        let remapping = shifted();
+        for (operand, expected) in operand_cases() {
+            let mut operator = Operator {
+                opcode: Some(OpCode::Nop as i32),
+                operator: Some(operand),
+            };
+            visit_operator_indices(&mut operator, &mut |space, index| {
+                *index = remapping.map(space, *index)
+            });
+            assert_eq!(operator.operator, Some(expected));
+        }
+    }
+
+    #[test]
+    fn test_remap_operands_in_module() {
+        let (operands, expected): (Vec<_>, Vec<_>) = operand_cases().into_iter().unzip();
+        let to_operators = |operands: Vec<operator::Operator>| -> Vec<Operator> {
+            operands
+                .into_iter()
+                .map(|operand| Operator {
+                    opcode: Some(OpCode::Nop as i32),
+                    operator: Some(operand),
+                })
+                .collect()
+        };
+        let mut program = ProgramModule {
+            code_section: Some(CodeSection {
+                code_section_entry: vec![CodeSectionEntry {
+                    locals: vec![Locals {
+                        count: Some(1),
+                        value_type: Some(concrete(1)),
+                    }],
+                    body: to_operators(operands),
+                }],
+            }),
+            ..Default::default()
+        };
+        shifted().apply(&mut program);
+        let entry = &program.code_section.unwrap().code_section_entry[0];
+        assert_eq!(entry.locals[0].value_type, Some(concrete(201)));
+        assert_eq!(entry.body, to_operators(expected));
+    }
+
+    fn expression(operand: operator::Operator) -> Expression {
+        Expression {
+            operators: vec![
+                Operator {
+                    opcode: Some(OpCode::GlobalGet as i32),
+                    operator: Some(operand),
+                },
+                Operator {
+                    opcode: Some(OpCode::End as i32),
+                    ..Default::default()
+                },
+            ],
+        }
+    }
+
+    fn naming(index: u32) -> NameMap {
+        NameMap {
+            names: vec![Naming {
+                index: Some(index),
+                name: Some("name".to_string()),
+            }],
+        }
+    }
+
+    fn indirect_naming(index: u32) -> IndirectNameMap {
+        IndirectNameMap {
+            names: vec![IndirectNaming {
+                index: Some(index),
+                names: Some(naming(1)),
+            }],
+        }
+    }
+
+    fn export(kind: ExternalKind, index: u32) -> Export {
+        Export {
+            name: Some(format!("{:?}", kind)),
+            kind: Some(kind as i32),
+            index: Some(index),
+        }
+    }
+
+    /// A module referring to item 1 of each index space from every section entry that can
+    fn create_program(shift: impl Fn(IndexSpace) -> u32) -> ProgramModule {
+        let index = |space| 1 + shift(space);
+        let function_type = |index| SubType {
+            kind: Some(sub_type::Kind::Func(FuncType {
+                params: vec![concrete(index)],
+                results: vec![],
+            })),
+            is_final: Some(false),
+            supertype_index: Some(index),
+        };
+        let struct_type = |index| SubType {
+            kind: Some(sub_type::Kind::StructType(StructType {
+                fields: vec![FieldType {
+                    storage_type: Some(field_type::StorageType::ValueType(concrete(index))),
+                    mutable: Some(true),
+                }],
+            })),
+            is_final: Some(true),
+            supertype_index: None,
+        };
+        let array_type = |index| SubType {
+            kind: Some(sub_type::Kind::ArrayType(ArrayType {
+                element_type: Some(FieldType {
+                    storage_type: Some(field_type::StorageType::ValueType(concrete(index))),
+                    mutable: Some(false),
+                }),
+            })),
+            is_final: Some(true),
+            supertype_index: None,
+        };
+        let global_type = |index| GlobalType {
+            content_type: Some(concrete(index)),
+            mutable: Some(false),
+            shared: Some(false),
+        };
+        let tag_type = |index| TagType {
+            kind: Some(TagKind::Exception as i32),
+            function_type_idx: Some(index),
+        };
+        let type_index = index(IndexSpace::Type);
+        ProgramModule {
+            type_section: Some(TypeSection {
+                types: vec![
+                    function_type(type_index),
+                    struct_type(type_index),
+                    array_type(type_index),
+                ],
+                rec_groups: vec![],
+            }),
+            import_section: Some(ImportSection {
+                imports: [
+                    import::Ty::FunctionType(type_index),
+                    import::Ty::Global(global_type(type_index)),
+                    import::Ty::Tag(tag_type(type_index)),
+                ]
+                .into_iter()
+                .map(|ty| Import {
+                    module: Some("env".to_string()),
+                    name: Some("item".to_string()),
+                    ty: Some(ty),
+                })
+                .collect(),
+                compact: None,
+            }),
+            function_section: Some(FunctionSection {
+                type_idxs: vec![type_index],
+            }),
+            table_section: Some(TableSection {
+                types: vec![TableType {
+                    init_expr: Some(expression(operator::Operator::GlobalIndex(index(
+                        IndexSpace::Global,
+                    )))),
+                    reference: Some(concrete_reference(type_index)),
+                    ..Default::default()
+                }],
+            }),
+            tag_section: Some(TagSection {
+                tags: vec![tag_type(type_index)],
+            }),
+            global_section: Some(GlobalSection {
+                globals: vec![Global {
+                    r#type: Some(global_type(type_index)),
+                    init_expr: Some(expression(operator::Operator::FunctionIndex(index(
+                        IndexSpace::Function,
+                    )))),
+                }],
+            }),
+            export_section: Some(ExportSection {
+                exports: vec![
+                    export(ExternalKind::ExtFunc, index(IndexSpace::Function)),
+                    export(ExternalKind::ExtFuncExact, index(IndexSpace::Function)),
+                    export(ExternalKind::ExtTable, index(IndexSpace::Table)),
+                    export(ExternalKind::ExtMemory, index(IndexSpace::Memory)),
+                    export(ExternalKind::ExtGlobal, index(IndexSpace::Global)),
+                    export(ExternalKind::ExtTag, index(IndexSpace::Tag)),
+                ],
+            }),
+            element_section: Some(ElementSection {
+                elements: vec![
+                    Element {
+                        kind: Some(ElementKind {
+                            r#type: Some(ElementKindType::ElActive as i32),
+                            table_index: Some(index(IndexSpace::Table)),
+                            expression: Some(expression(operator::Operator::GlobalIndex(index(
+                                IndexSpace::Global,
+                            )))),
+                        }),
+                        items: Some(element::Items::Functions(ElementFunctions {
+                            functions: vec![index(IndexSpace::Function)],
+                        })),
+                    },
+                    Element {
+                        kind: Some(ElementKind {
+                            r#type: Some(ElementKindType::ElPassive as i32),
+                            ..Default::default()
+                        }),
+                        items: Some(element::Items::Expressions(ElementExpressions {
+                            expressions: vec![expression(operator::Operator::FunctionIndex(
+                                index(IndexSpace::Function),
+                            ))],
+                            reference: Some(concrete_reference(type_index)),
+                            ..Default::default()
+                        })),
+                    },
+                ],
+            }),
+            data_section: Some(DataSection {
+                datas: vec![Data {
+                    kind: Some(DataKind {
+                        r#type: Some(DataKindType::Active as i32),
+                        memory_index: Some(index(IndexSpace::Memory)),
+                        expression: Some(expression(operator::Operator::GlobalIndex(index(
+                            IndexSpace::Global,
+                        )))),
+                    }),
+                    data: Some(vec![0]),
+                }],
+            }),
+            start_section: Some(StartSection {
+                function_index: Some(index(IndexSpace::Function)),
+            }),
+            custom_sections: vec![CustomSection {
+                name: Some("name".to_string()),
+                name_section: Some(NameSection {
+                    module_name: Some("module".to_string()),
+                    function_names: Some(naming(index(IndexSpace::Function))),
+                    local_names: Some(indirect_naming(index(IndexSpace::Function))),
+                    label_names: Some(indirect_naming(index(IndexSpace::Function))),
+                    type_names: Some(naming(type_index)),
+                    table_names: Some(naming(index(IndexSpace::Table))),
+                    memory_names: Some(naming(index(IndexSpace::Memory))),
+                    global_names: Some(naming(index(IndexSpace::Global))),
+                    element_names: Some(naming(index(IndexSpace::Element))),
+                    data_names: Some(naming(index(IndexSpace::Data))),
+                    field_names: Some(indirect_naming(type_index)),
+                    tag_names: Some(naming(index(IndexSpace::Tag))),
+                    ..Default::default()
+                }),
+                ..Default::default()
+            }],
+            ..Default::default()
+        }
+    }
+
+    fn offset(space: IndexSpace) -> u32 {
+        shifted().map(space, 0)
+    }
+
+    #[test]
+    fn test_remap_sections() {
+        let mut program = create_program(|_| 0);
+        shifted().apply(&mut program);
+        assert_eq!(program, create_program(offset));
+    }
+
+    #[test]
+    fn test_visit_indices() {
+        let mut program = create_program(|_| 0);
+        let mut visited: HashMap<IndexSpace, usize> = HashMap::new();
+        visit_indices(&mut program, &mut |space, index| {
+            assert_eq!(*index, 1);
+            *visited.entry(space).or_default() += 1;
+        });
+        assert_eq!(
+            visited,
+            HashMap::from([
+                (IndexSpace::Function, 9),
+                (IndexSpace::Type, 14),
+                (IndexSpace::Global, 5),
+                (IndexSpace::Table, 3),
+                (IndexSpace::Memory, 3),
+                (IndexSpace::Tag, 2),
+                (IndexSpace::Data, 1),
+                (IndexSpace::Element, 1),
+            ])
+        );
+    }
+
+    #[test]
+    fn test_remapping_keeps_unmapped_spaces() {
+        let mut program = create_program(|_| 0);
+        Remapping::new()
+            .with(IndexSpace::Tag, |index| index + 600)
+            .apply(&mut program);
+        assert_eq!(
+            program,
+            create_program(|space| if space == IndexSpace::Tag { 600 } else { 0 })
+        );
+    }
+
+    #[test]
+    fn test_append_global_after_imported_and_defined_globals() {
+        let mut program = create_program(|_| 0);
+        assert_eq!(imported_count(&program, IndexSpace::Function), 1);
+        assert_eq!(imported_count(&program, IndexSpace::Global), 1);
+        assert_eq!(imported_count(&program, IndexSpace::Memory), 0);
+
+        let global_type = GlobalType {
+            content_type: Some(ValueType {
+                value_type: Some(PlainType::ValueTypeI32 as i32),
+                ..Default::default()
+            }),
+            mutable: Some(true),
+            shared: Some(false),
+        };
+        let init =
+            crate::operators::with_operand(OpCode::I32Constant, operator::Operator::I32Value(7));
+        assert_eq!(append_global(&mut program, global_type, init.clone()), 2);
+        let globals = &program.global_section.unwrap().globals;
+        assert_eq!(globals.len(), 2);
+        assert_eq!(
+            globals[1].init_expr,
+            Some(Expression {
+                operators: vec![init, simple(OpCode::End)],
+            })
+        );
+    }
+}
@@ -1,17 +1,31 @@
+use crate::error::{Error, Result};
+use crate::helpers::encode_reference;
 use crate::libernet_wasm::*;
-use anyhow::{Ok, Result, anyhow, bail};
 
 impl Version {
-    pub fn from_wasmparser(payload: &wasmparser::Payload) -> Result<Option<Version>> {
+    pub fn from_wasmparser(
+        payload: &wasmparser::Payload,
+        expected_encoding: wasmparser::Encoding,
+    ) -> Result<Option<Version>> {
         use wasmparser::{Encoding, Payload};
         match payload {
             &Payload::Version { num, encoding, .. } => {
-                if num != 1 {
-                    bail!("Only version 1 is supported, got {}", num);
+                if encoding != expected_encoding {
+                    return Err(Error::Unsupported(format!(
+                        "Only Encoding::{:?} is supported, got {:?}",
+                        expected_encoding, encoding
+                    )));
                 }
 
-                if encoding != Encoding::Module {
-                    bail!("Only Encoding::Module is supported, got {:?}", encoding);
+                let expected_num = match expected_encoding {
+                    Encoding::Module => 1,
+                    Encoding::Component => 0xd,
+                };
+                if num != expected_num {
+                    return Err(Error::Unsupported(format!(
+                        "Only version {} is supported, got {}",
+                        expected_num, num
+                    )));
                 }
 
                 Ok(Some(Version {
@@ -28,82 +42,80 @@ impl TypeSection {
     pub fn from_wasmparser(
         types: wasmparser::SectionLimited<'_, wasmparser::RecGroup>,
     ) -> Result<TypeSection> {
-        use wasmparser::CompositeInnerType;
-
         let mut program_types: Vec<SubType> = Vec::new();
+        let mut rec_groups: Vec<RecGroup> = Vec::new();
+        let mut has_explicit_group = false;
         for group in types {
             let group = group?;
-            let types = group.types().collect::<Vec<_>>();
-            if types.is_empty() {
-                bail!("Expected at least 1 type in type group");
-            }
-            if types.len() > 1 {
-                bail!("Recursive & GC type groups are not supported");
-            }
-            let atype = types[0];
-            if !atype.is_final {
-                bail!("Non-final types are not supported");
+            if group.types().len() == 0 {
+                return Err(Error::Invalid(
+                    "Expected at least 1 type in type group".to_string(),
+                ));
             }
-            if atype.supertype_idx.is_some() {
-                bail!("Supertype indexes are not supported");
-            }
-            match &atype.composite_type.inner {
-                CompositeInnerType::Func(ft) => {
-                    let mut params: Vec<ValueType> = Vec::new();
-                    for param in ft.params() {
-                        params.push(ValueType::try_from(*param)?);
-                    }
-                    let mut results: Vec<ValueType> = Vec::new();
-                    for result in ft.results() {
-                        results.push(ValueType::try_from(*result)?);
-                    }
-                    program_types.push(SubType {
-                        kind: Some(sub_type::Kind::Func(FuncType { params, results })),
-                    });
-                }
-                inner_type => {
-                    bail!("Type is not supported {:?}", inner_type);
-                }
+            for atype in group.types() {
+                program_types.push(SubType::try_from(atype)?);
             }
+            has_explicit_group |= group.is_explicit_rec_group();
+            rec_groups.push(RecGroup {
+                type_count: Some(group.types().len() as u32),
+                explicit: Some(group.is_explicit_rec_group()),
+            });
+        }
+        // modules without `rec` blocks keep the plain list of types
+        if !has_explicit_group {
+            rec_groups.clear();
         }
         Ok(TypeSection {
             types: program_types,
+            rec_groups,
         })
     }
 
     pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
-        use wasm_encoder::{CompositeInnerType, CompositeType, SubType, TypeSection, ValType};
-        let mut types = TypeSection::new();
-        for ty in &self.types {
-            match &ty.kind {
-                Some(sub_type::Kind::Func(ft)) => {
-                    let mut params: Vec<ValType> = Vec::new();
-                    for p in &ft.params {
-                        params.push((*p).try_into()?);
-                    }
-                    let mut results: Vec<ValType> = Vec::new();
-                    for r in &ft.results {
-                        results.push((*r).try_into()?);
-                    }
-                    let ty = &SubType {
-                        is_final: true,
-                        supertype_idx: None,
-                        composite_type: CompositeType {
-                            inner: CompositeInnerType::Func(wasm_encoder::FuncType::new(
-                                params, results,
-                            )),
-                            shared: false,
-                            descriptor: None,
-                            describes: None,
-                        },
-                    };
-                    types.ty().subtype(ty);
-                }
-                None => {
-                    bail!("Type is not supported {:?}", ty);
-                }
+        let mut types = wasm_encoder::TypeSection::new();
+        if self.rec_groups.is_empty() {
+            for ty in &self.types {
+                types.ty().subtype(&ty.try_into()?);
+            }
+            module.section(&types);
+            return Ok(());
+        }
+
+        let mut remaining = self.types.as_slice();
+        for group in &self.rec_groups {
+            let type_count = group
+                .type_count
+                .ok_or(Error::Missing("Rec group type count"))?;
+            if type_count as usize > remaining.len() {
+                return Err(Error::Invalid(format!(
+                    "Rec group of {} types exceeds the {} remaining types",
+                    type_count,
+                    remaining.len()
+                )));
+            }
+            let (group_types, rest) = remaining.split_at(type_count as usize);
+            remaining = rest;
+            let mut sub_types: Vec<wasm_encoder::SubType> = Vec::new();
+            for ty in group_types {
+                sub_types.push(ty.try_into()?);
+            }
+            if group.explicit.unwrap_or(false) {
+                types.ty().rec(sub_types);
+            } else if let [sub_type] = sub_types.as_slice() {
+                types.ty().subtype(sub_type);
+            } else {
+                return Err(Error::Invalid(format!(
+                    "Implicit rec group must hold exactly 1 type, got {}",
+                    sub_types.len()
+                )));
             }
         }
+        if !remaining.is_empty() {
+            return Err(Error::Invalid(format!(
+                "{} types are not covered by a rec group",
+                remaining.len()
+            )));
+        }
         module.section(&types);
         Ok(())
     }
@@ -113,57 +125,141 @@ impl ImportSection {
     pub fn from_wasmparser(
         imports: wasmparser::SectionLimited<'_, wasmparser::Imports>,
     ) -> Result<ImportSection> {
-        use wasmparser::{Imports, TypeRef};
-        let mut refs: Vec<TypeRefFunc> = Vec::new();
+        use wasmparser::Imports;
+        let mut refs: Vec<Import> = Vec::new();
+        let mut compact = false;
         for import in imports {
             match import? {
                 Imports::Single(_, import) => {
-                    let module = import.module.to_string();
-                    let name = import.name.to_string();
-                    match import.ty {
-                        TypeRef::Func(ftype) => {
-                            refs.push(TypeRefFunc {
-                                module: Some(module),
-                                name: Some(name),
-                                function_type: Some(ftype),
-                            });
-                        }
-                        _ => bail!("ImportSection: only Func type imports are supported"),
+                    refs.push(Import {
+                        module: Some(import.module.to_string()),
+                        name: Some(import.name.to_string()),
+                        ty: Some(import.ty.try_into()?),
+                    });
+                }
+                Imports::Compact1 { module, items } => {
+                    compact = true;
+                    for item in items {
+                        let item = item?;
+                        refs.push(Import {
+                            module: Some(module.to_string()),
+                            name: Some(item.name.to_string()),
+                            ty: Some(item.ty.try_into()?),
+                        });
                     }
                 }
-                _ => {
-                    bail!("ImportSection: only Single Import is supported");
+                Imports::Compact2 { module, ty, names } => {
+                    compact = true;
+                    let ty = import::Ty::try_from(ty)?;
+                    for name in names {
+                        refs.push(Import {
+                            module: Some(module.to_string()),
+                            name: Some(name?.to_string()),
+                            ty: Some(ty.clone()),
+                        });
+                    }
                 }
             }
         }
 
-        Ok(ImportSection { imports: refs })
+        Ok(ImportSection {
+            imports: refs,
+            compact: Some(compact),
+        })
     }
 
     pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
-        use wasm_encoder::{EntityType, ImportSection};
-        let mut imports = ImportSection::new();
-        for import in &self.imports {
-            imports.import(
+        let mut imports = wasm_encoder::ImportSection::new();
+        // only modules that already used the compact encodings get them back, other
+        // consumers may not support the proposal
+        let compact = self.compact.unwrap_or(false);
+        let mut start = 0;
+        while start < self.imports.len() {
+            let mut end = start + 1;
+            if compact {
+                while end < self.imports.len()
+                    && self.imports[end].module == self.imports[start].module
+                {
+                    end += 1;
+                }
+            }
+            for entry in Self::smallest_encoding(&self.imports[start..end])? {
+                imports.imports(entry);
+            }
+            start = end;
+        }
+        module.section(&imports);
+        Ok(())
+    }
+
+    /// Picks the shortest encoding for a run of imports from the same module.
+    fn smallest_encoding(group: &[Import]) -> Result<Vec<wasm_encoder::Imports<'_>>> {
+        use wasm_encoder::{Encode, ImportCompact, Imports};
+        let mut items: Vec<(&str, &str, wasm_encoder::EntityType)> = Vec::new();
+        for import in group {
+            items.push((
                 import
                     .module
                     .as_ref()
-                    .ok_or(anyhow!("Module not found"))?
+                    .ok_or(Error::Missing("Module"))?
                     .as_str(),
+                import.name.as_ref().ok_or(Error::Missing("Name"))?.as_str(),
                 import
-                    .name
+                    .ty
                     .as_ref()
-                    .ok_or(anyhow!("Name not found"))?
-                    .as_str(),
-                EntityType::Function(
-                    import
-                        .function_type
-                        .ok_or(anyhow!("Function type not found"))?,
-                ),
-            );
+                    .ok_or(Error::Missing("Import type"))?
+                    .try_into()?,
+            ));
         }
-        module.section(&imports);
-        Ok(())
+        let singles: Vec<Imports> = items
+            .iter()
+            .map(|(module, name, ty)| {
+                Imports::Single(wasm_encoder::Import {
+                    module,
+                    name,
+                    ty: *ty,
+                })
+            })
+            .collect();
+        if items.len() < 2 {
+            return Ok(singles);
+        }
+
+        let module = items[0].0;
+        let mut candidates = vec![
+            singles,
+            vec![Imports::Compact1 {
+                module,
+                items: items
+                    .iter()
+                    .map(|(_, name, ty)| ImportCompact { name, ty: *ty })
+                    .collect(),
+            }],
+        ];
+        if group.iter().all(|import| import.ty == group[0].ty) {
+            candidates.push(vec![Imports::Compact2 {
+                module,
+                ty: items[0].2,
+                names: items.iter().map(|(_, name, _)| *name).collect(),
+            }]);
+        }
+
+        let encoded_len = |entries: &Vec<Imports>| {
+            let mut section = wasm_encoder::ImportSection::new();
+            for entry in entries {
+                section.imports(entry.clone());
+            }
+            let mut bytes = Vec::new();
+            section.encode(&mut bytes);
+            bytes.len()
+        };
+        let mut best = candidates.remove(0);
+        for candidate in candidates {
+            if encoded_len(&candidate) < encoded_len(&best) {
+                best = candidate;
+            }
+        }
+        Ok(best)
     }
 }
 
@@ -196,21 +292,17 @@ impl TableSection {
         let mut proto_tables: Vec<TableType> = Vec::new();
         for table in tables {
             let table = table?;
-            if let TableInit::Expr(_) = table.init {
-                bail!("Table init is not supported");
-            }
-
             if table.ty.shared {
-                bail!("Shared tables are not supported");
+                return Err(Error::Unsupported(
+                    "Shared tables are not supported".to_string(),
+                ));
             }
 
-            proto_tables.push(TableType {
-                reference_type: Some(RefType::try_from(table.ty.element_type)? as i32),
-                table64: Some(table.ty.table64),
-                initial: Some(table.ty.initial),
-                maximum: table.ty.maximum,
-                shared: Some(table.ty.shared),
-            });
+            let mut table_type = TableType::try_from(table.ty)?;
+            if let TableInit::Expr(expr) = table.init {
+                table_type.init_expr = Some(expr.try_into()?);
+            }
+            proto_tables.push(table_type);
         }
         Ok(TableSection {
             types: proto_tables,
@@ -218,18 +310,18 @@ impl TableSection {
     }
 
     pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
-        use wasm_encoder::{TableSection, TableType};
+        use wasm_encoder::{ConstExpr, TableSection};
         let mut table_types = TableSection::new();
         for ty in &self.types {
-            let ref_type =
-                RefType::try_from(ty.reference_type.ok_or(anyhow!("Ref type not found"))?)?;
-            table_types.table(TableType {
-                element_type: ref_type.try_into()?,
-                table64: ty.table64.ok_or(anyhow!("Table64 not found"))?,
-                minimum: ty.initial.ok_or(anyhow!("Initial not found"))?,
-                maximum: ty.maximum,
-                shared: ty.shared.ok_or(anyhow!("Shared not found"))?,
-            });
+            match &ty.init_expr {
+                Some(init_expr) => {
+                    table_types
+                        .table_with_init(ty.try_into()?, &ConstExpr::try_from(init_expr.clone())?);
+                }
+                None => {
+                    table_types.table(ty.try_into()?);
+                }
+            }
         }
         module.section(&table_types);
         Ok(())
@@ -242,14 +334,7 @@ impl MemorySection {
     ) -> Result<MemorySection> {
         let mut memory_types: Vec<MemoryType> = Vec::new();
         for memory in section {
-            let memory = memory?;
-            memory_types.push(MemoryType {
-                memory64: Some(memory.memory64),
-                shared: Some(memory.shared),
-                initial: Some(memory.initial),
-                maximum: memory.maximum,
-                page_size_log2: memory.page_size_log2,
-            })
+            memory_types.push(MemoryType::from(memory?));
         }
         Ok(MemorySection { memory_types })
     }
@@ -257,13 +342,7 @@ impl MemorySection {
         use wasm_encoder::MemorySection;
         let mut types = MemorySection::new();
         for memory in &self.memory_types {
-            types.memory(wasm_encoder::MemoryType {
-                memory64: memory.memory64.ok_or(anyhow!("Memory64 not found"))?,
-                shared: memory.shared.ok_or(anyhow!("Shared not found"))?,
-                minimum: memory.initial.ok_or(anyhow!("Initial not found"))?,
-                maximum: memory.maximum,
-                page_size_log2: memory.page_size_log2,
-            });
+            types.memory(memory.try_into()?);
         }
         module.section(&types);
         Ok(())
@@ -278,39 +357,25 @@ impl GlobalSection {
         for global in section {
             let global = global?;
             globals.push(Global {
-                r#type: Some(GlobalType {
-                    content_type: Some(ValueType::try_from(global.ty.content_type)?),
-                    mutable: Some(global.ty.mutable),
-                    shared: Some(global.ty.shared),
-                }),
+                r#type: Some(GlobalType::try_from(global.ty)?),
                 init_expr: Some(global.init_expr.try_into()?),
             });
         }
         Ok(GlobalSection { globals })
     }
     pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
-        use wasm_encoder::{ConstExpr, GlobalSection, GlobalType};
+        use wasm_encoder::{ConstExpr, GlobalSection};
         let mut globals = GlobalSection::new();
         for global in &self.globals {
             let ty = global
                 .r#type
                 .as_ref()
-                .ok_or(anyhow!("Global type not found"))?;
+                .ok_or(Error::Missing("Global type"))?;
             let init_expr = global
                 .init_expr
                 .clone()
-                .ok_or(anyhow!("Init expr not found"))?;
-            globals.global(
-                GlobalType {
-                    val_type: ty
-                        .content_type
-                        .ok_or(anyhow!("Content type not found"))?
-                        .try_into()?,
-                    mutable: ty.mutable.ok_or(anyhow!("Mutable not found"))?,
-                    shared: ty.shared.ok_or(anyhow!("Shared not found"))?,
-                },
-                &ConstExpr::try_from(init_expr)?,
-            );
+                .ok_or(Error::Missing("Init expr"))?;
+            globals.global(ty.try_into()?, &ConstExpr::try_from(init_expr)?);
         }
         module.section(&globals);
         Ok(())
@@ -338,14 +403,9 @@ impl ExportSection {
         let mut exports = ExportSection::new();
         for export in &self.exports {
             exports.export(
-                export
-                    .name
-                    .as_ref()
-                    .ok_or(anyhow!("Name not found"))?
-                    .as_str(),
-                ExternalKind::try_from(export.kind.ok_or(anyhow!("Kind not found"))?)?
-                    .try_into()?,
-                export.index.ok_or(anyhow!("Index not found"))?,
+                export.name.as_ref().ok_or(Error::Missing("Name"))?.as_str(),
+                ExternalKind::try_from(export.kind.ok_or(Error::Missing("Kind"))?)?.try_into()?,
+                export.index.ok_or(Error::Missing("Index"))?,
             );
         }
         module.section(&exports);
@@ -353,6 +413,44 @@ impl ExportSection {
     }
 }
 
+impl StartSection {
+    pub fn from_wasmparser(payload: &wasmparser::Payload) -> Result<StartSection> {
+        use wasmparser::Payload;
+        match payload {
+            &Payload::StartSection { func, .. } => Ok(StartSection {
+                function_index: Some(func),
+            }),
+            _ => Err(Error::Invalid(format!("Unexpected payload {:?}", payload))),
+        }
+    }
+
+    pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
+        module.section(&wasm_encoder::StartSection {
+            function_index: self
+                .function_index
+                .ok_or(Error::Missing("Start function index"))?,
+        });
+        Ok(())
+    }
+}
+
+impl DataCountSection {
+    pub fn from_wasmparser(payload: &wasmparser::Payload) -> Result<DataCountSection> {
+        use wasmparser::Payload;
+        match payload {
+            &Payload::DataCountSection { count, .. } => Ok(DataCountSection { count: Some(count) }),
+            _ => Err(Error::Invalid(format!("Unexpected payload {:?}", payload))),
+        }
+    }
+
+    pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
+        module.section(&wasm_encoder::DataCountSection {
+            count: self.count.ok_or(Error::Missing("Data count"))?,
+        });
+        Ok(())
+    }
+}
+
 impl ElementSection {
     pub fn from_wasmparser(
         section: wasmparser::SectionLimited<'_, wasmparser::Element>,
@@ -376,8 +474,9 @@ impl ElementSection {
                         expressions.push(Expression::try_from(expression?)?);
                     }
                     element::Items::Expressions(ElementExpressions {
-                        reference_type: Some(RefType::try_from(ref_type)? as i32),
+                        reference_type: None,
                         expressions,
+                        reference: Some(ReferenceType::try_from(ref_type)?),
                     })
                 }
             };
@@ -392,14 +491,11 @@ impl ElementSection {
     }
 
     pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
-        use wasm_encoder::{
-            ConstExpr, ElementMode, ElementSection, ElementSegment, Elements,
-            RefType as WasmRefType,
-        };
+        use wasm_encoder::{ConstExpr, ElementMode, ElementSection, ElementSegment, Elements};
         let mut elements = ElementSection::new();
         for element in &self.elements {
-            let kind = element.kind.as_ref().ok_or(anyhow!("Kind not found"))?;
-            let ty = kind.r#type.ok_or(anyhow!("Element kind type not found"))?;
+            let kind = element.kind.as_ref().ok_or(Error::Missing("Kind"))?;
+            let ty = kind.r#type.ok_or(Error::Missing("Element kind type"))?;
             let element_mode = match ElementKindType::try_from(ty)? {
                 ElementKindType::ElPassive => ElementMode::Passive,
                 ElementKindType::ElActive => ElementMode::Active {
@@ -407,13 +503,13 @@ impl ElementSection {
                     offset: &ConstExpr::try_from(
                         kind.expression
                             .as_ref()
-                            .ok_or(anyhow!("Expression not found"))?
+                            .ok_or(Error::Missing("Expression"))?
                             .clone(),
                     )?,
                 },
                 ElementKindType::ElDeclared => ElementMode::Declared,
             };
-            let items = match element.items.as_ref().ok_or(anyhow!("Items not found"))? {
+            let items = match element.items.as_ref().ok_or(Error::Missing("Items"))? {
                 element::Items::Functions(functions) => {
                     Elements::Functions(functions.functions.clone().into())
                 }
@@ -423,11 +519,7 @@ impl ElementSection {
                         instructions.push(ConstExpr::try_from(expression.clone())?);
                     }
                     Elements::Expressions(
-                        WasmRefType::try_from(RefType::try_from(
-                            expressions
-                                .reference_type
-                                .ok_or(anyhow!("Ref type not found"))?,
-                        )?)?,
+                        encode_reference(expressions.reference, expressions.reference_type)?,
                         instructions.into(),
                     )
                 }
@@ -447,7 +539,7 @@ impl CodeSectionEntry {
         use wasmparser::Payload;
         match &payload {
             &Payload::CodeSectionEntry(body) => Ok(CodeSectionEntry::from_function_body(body)?),
-            _ => bail!("Unexpected payload {:?}", payload),
+            _ => Err(Error::Invalid(format!("Unexpected payload {:?}", payload))),
         }
     }
 
@@ -472,15 +564,15 @@ impl CodeSectionEntry {
         })
     }
 
-    fn render_wasm(&self, code_section: &mut wasm_encoder::CodeSection) -> Result<()> {
+    pub fn render_wasm(&self, code_section: &mut wasm_encoder::CodeSection) -> Result<()> {
         use wasm_encoder::{Function, Instruction, ValType};
         let mut locals: Vec<(u32, ValType)> = Vec::new();
         for local in &self.locals {
             locals.push((
-                local.count.ok_or(anyhow!("Count not found"))?,
+                local.count.ok_or(Error::Missing("Count"))?,
                 local
                     .value_type
-                    .ok_or(anyhow!("Value type not found"))?
+                    .ok_or(Error::Missing("Value type"))?
                     .try_into()?,
             ));
         }
@@ -525,23 +617,23 @@ impl DataSection {
         use wasm_encoder::{ConstExpr, DataSection, DataSegment, DataSegmentMode};
         let mut section = DataSection::new();
         for data in &self.datas {
-            let kind = data.kind.as_ref().ok_or(anyhow!("Kind not found"))?;
-            let ty = kind.r#type.ok_or(anyhow!("Data kind type not found"))?;
+            let kind = data.kind.as_ref().ok_or(Error::Missing("Kind"))?;
+            let ty = kind.r#type.ok_or(Error::Missing("Data kind type"))?;
             let data_mode = match DataKindType::try_from(ty)? {
                 DataKindType::Passive => DataSegmentMode::Passive,
                 DataKindType::Active => DataSegmentMode::Active {
-                    memory_index: kind.memory_index.ok_or(anyhow!("Memory index not found"))?,
+                    memory_index: kind.memory_index.ok_or(Error::Missing("Memory index"))?,
                     offset: &ConstExpr::try_from(
                         kind.expression
                             .as_ref()
-                            .ok_or(anyhow!("Expression not found"))?
+                            .ok_or(Error::Missing("Expression"))?
                             .clone(),
                     )?,
                 },
             };
             section.segment(DataSegment {
                 mode: data_mode,
-                data: data.data.clone().ok_or(anyhow!("Data not found"))?,
+                data: data.data.clone().ok_or(Error::Missing("Data"))?,
             });
         }
         module.section(&section);
@@ -555,33 +647,66 @@ impl TagSection {
     ) -> Result<TagSection> {
         let mut tags: Vec<TagType> = Vec::new();
         for tag in section {
-            let tag = tag?;
-            if tag.kind != wasmparser::TagKind::Exception {
-                bail!("Only Exception tags are supported");
-            }
-            tags.push(TagType {
-                kind: Some(TagKind::Exception as i32),
-                function_type_idx: Some(tag.func_type_idx),
-            });
+            tags.push(TagType::try_from(tag?)?);
         }
         Ok(TagSection { tags })
     }
     pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
-        use wasm_encoder::{TagKind, TagSection, TagType};
+        use wasm_encoder::TagSection;
         let mut tags = TagSection::new();
         for tag in &self.tags {
-            tags.tag(TagType {
-                kind: TagKind::Exception,
-                func_type_idx: tag
-                    .function_type_idx
-                    .ok_or(anyhow!("Func type index not found"))?,
-            });
+            tags.tag(tag.try_into()?);
         }
         module.section(&tags);
         Ok(())
     }
 }
 
+impl CustomSection {
+    pub fn from_wasmparser(
+        section: &wasmparser::CustomSectionReader<'_>,
+        after_section_id: u8,
+    ) -> Result<CustomSection> {
+        // a malformed name section is kept as raw bytes, engines ignore it as well, and so is one
+        // with repeated or out-of-order subsections, which wouldn't render back the same
+        if let wasmparser::KnownCustom::Name(reader) = section.as_known()
+            && let Ok(name_section) = NameSection::from_wasmparser(reader)
+        {
+            return Ok(CustomSection {
+                name: Some(section.name().to_string()),
+                data: None,
+                after_section_id: Some(after_section_id as u32),
+                name_section: Some(name_section),
+            });
+        }
+        Ok(CustomSection {
+            name: Some(section.name().to_string()),
+            data: Some(section.data().to_vec()),
+            after_section_id: Some(after_section_id as u32),
+            name_section: None,
+        })
+    }
+
+    pub fn render_wasm(&self, module: &mut wasm_encoder::Module) -> Result<()> {
+        if let Some(name_section) = &self.name_section {
+            return name_section.render_wasm(module);
+        }
+        module.section(&wasm_encoder::CustomSection {
+            name: self
+                .name
+                .as_deref()
+                .ok_or(Error::Missing("Custom section name"))?
+                .into(),
+            data: self
+                .data
+                .as_deref()
+                .ok_or(Error::Missing("Custom section data"))?
+                .into(),
+        });
+        Ok(())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -616,7 +741,8 @@ mod tests {
         for payload in parser.parse_all(&wasm_bytes) {
             let payload = payload.unwrap();
             if let Payload::Version { .. } = &payload {
-                let result = Version::from_wasmparser(&payload).unwrap();
+                let result =
+                    Version::from_wasmparser(&payload, wasmparser::Encoding::Module).unwrap();
                 assert!(result.is_some());
                 let version = result.unwrap();
                 assert_eq!(version.r#number, Some(1));
@@ -659,6 +785,153 @@ mod tests {
         panic!("TypeSection payload not found");
     }
 
+    fn create_gc_type_section() -> Vec<u8> {
+        use wasm_encoder::{
+            ArrayType, CompositeInnerType, CompositeType, FieldType, StorageType, StructType,
+            SubType, ValType,
+        };
+        let composite = |inner| CompositeType {
+            inner,
+            shared: false,
+            descriptor: None,
+            describes: None,
+        };
+        let field = |element_type, mutable| FieldType {
+            element_type,
+            mutable,
+        };
+        let mut types = wasm_encoder::TypeSection::new();
+        types.ty().subtype(&SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: composite(CompositeInnerType::Func(wasm_encoder::FuncType::new(
+                vec![ValType::I32],
+                vec![],
+            ))),
+        });
+        types.ty().rec(vec![
+            SubType {
+                is_final: false,
+                supertype_idx: None,
+                composite_type: composite(CompositeInnerType::Struct(StructType {
+                    fields: vec![field(StorageType::Val(ValType::I32), false)].into(),
+                })),
+            },
+            SubType {
+                is_final: true,
+                supertype_idx: Some(1),
+                composite_type: composite(CompositeInnerType::Struct(StructType {
+                    fields: vec![
+                        field(StorageType::Val(ValType::I32), false),
+                        field(StorageType::I16, true),
+                        field(StorageType::Val(ValType::FUNCREF), true),
+                    ]
+                    .into(),
+                })),
+            },
+        ]);
+        types.ty().rec(vec![SubType {
+            is_final: true,
+            supertype_idx: None,
+            composite_type: composite(CompositeInnerType::Array(ArrayType(field(
+                StorageType::I8,
+                true,
+            )))),
+        }]);
+        let mut module = Module::new();
+        module.section(&types);
+        module.finish()
+    }
+
+    fn parse_type_section(wasm_bytes: &[u8]) -> TypeSection {
+        for payload in Parser::new(0).parse_all(wasm_bytes) {
+            if let Payload::TypeSection(section) = payload.unwrap() {
+                return TypeSection::from_wasmparser(section).unwrap();
+            }
+        }
+        panic!("TypeSection payload not found");
+    }
+
+    #[test]
+    fn test_section_from_wasmparser_gc_type_section() {
+        let type_section = parse_type_section(&create_gc_type_section());
+
+        assert_eq!(type_section.types.len(), 4);
+        assert_eq!(
+            type_section.rec_groups,
+            vec![
+                RecGroup {
+                    type_count: Some(1),
+                    explicit: Some(false),
+                },
+                RecGroup {
+                    type_count: Some(2),
+                    explicit: Some(true),
+                },
+                RecGroup {
+                    type_count: Some(1),
+                    explicit: Some(true),
+                },
+            ]
+        );
+        assert_eq!(type_section.types[1].is_final, Some(false));
+        assert_eq!(type_section.types[2].supertype_index, Some(1));
+        match &type_section.types[2].kind {
+            Some(sub_type::Kind::StructType(st)) => {
+                assert_eq!(st.fields.len(), 3);
+                assert_eq!(
+                    st.fields[1],
+                    FieldType {
+                        storage_type: Some(field_type::StorageType::PackedType(
+                            PackedType::I16 as i32
+                        )),
+                        mutable: Some(true),
+                    }
+                );
+            }
+            kind => panic!("Expected struct type, got {:?}", kind),
+        }
+        assert!(matches!(
+            type_section.types[3].kind,
+            Some(sub_type::Kind::ArrayType(_))
+        ));
+    }
+
+    #[test]
+    fn test_section_render_wasm_gc_type_section_round_trip() {
+        let original = create_gc_type_section();
+        let type_section = parse_type_section(&original);
+
+        let mut module = Module::new();
+        type_section.render_wasm(&mut module).unwrap();
+        let rendered = module.finish();
+
+        wasmparser::Validator::new_with_features(
+            wasmparser::WasmFeatures::default() | wasmparser::WasmFeatures::GC,
+        )
+        .validate_all(&rendered)
+        .unwrap();
+        assert_eq!(rendered, original);
+    }
+
+    #[test]
+    fn test_section_from_wasmparser_plain_type_section_has_no_rec_groups() {
+        let type_section = parse_type_section(&create_minimal_wasm());
+        assert!(type_section.rec_groups.is_empty());
+        assert_eq!(type_section.types[0].is_final, Some(true));
+    }
+
+    #[test]
+    fn test_section_render_wasm_rec_group_too_large() {
+        let mut type_section = parse_type_section(&create_gc_type_section());
+        type_section.rec_groups[2].type_count = Some(2);
+        let mut module = Module::new();
+        assert!(matches!(
+            type_section.render_wasm(&mut module),
+            Err(Error::Invalid(_))
+        ));
+    }
+
     #[test]
     fn test_section_from_wasmparser_function_section() {
         let mut module = Module::new();
@@ -716,7 +989,21 @@ mod tests {
         for payload in parser.parse_all(&wasm_bytes) {
             let payload = payload.unwrap();
             if let Payload::ImportSection(section) = payload {
-                assert!(ImportSection::from_wasmparser(section).is_err());
+                let import_section = ImportSection::from_wasmparser(section).unwrap();
+                assert_eq!(
+                    import_section.imports,
+                    vec![Import {
+                        module: Some("env".to_string()),
+                        name: Some("memory".to_string()),
+                        ty: Some(import::Ty::Memory(MemoryType {
+                            memory64: Some(false),
+                            shared: Some(false),
+                            initial: Some(1),
+                            maximum: None,
+                            page_size_log2: None,
+                        })),
+                    }]
+                );
                 return;
             }
         }
@@ -758,6 +1045,121 @@ mod tests {
         panic!("ImportSection payload not found");
     }
 
+    fn create_compact_import_section() -> Vec<u8> {
+        use wasm_encoder::{EntityType, ImportCompact, Imports};
+        let mut module = Module::new();
+        let mut imports = wasm_encoder::ImportSection::new();
+        imports.imports(Imports::Compact2 {
+            module: "env",
+            ty: EntityType::Function(0),
+            names: vec!["a", "b", "c"].into(),
+        });
+        imports.imports(Imports::Compact1 {
+            module: "wasi_snapshot_preview1",
+            items: vec![
+                ImportCompact {
+                    name: "fd_write",
+                    ty: EntityType::Function(1),
+                },
+                ImportCompact {
+                    name: "proc_exit",
+                    ty: EntityType::Function(2),
+                },
+            ]
+            .into(),
+        });
+        module.section(&imports);
+        module.finish()
+    }
+
+    #[test]
+    fn test_section_from_wasmparser_compact_import_section() {
+        let wasm_bytes = create_compact_import_section();
+        for payload in Parser::new(0).parse_all(&wasm_bytes) {
+            if let Payload::ImportSection(section) = payload.unwrap() {
+                let import_section = ImportSection::from_wasmparser(section).unwrap();
+                assert_eq!(import_section.compact, Some(true));
+                let flattened: Vec<(&str, &str, import::Ty)> = import_section
+                    .imports
+                    .iter()
+                    .map(|import| {
+                        (
+                            import.module.as_deref().unwrap(),
+                            import.name.as_deref().unwrap(),
+                            import.ty.clone().unwrap(),
+                        )
+                    })
+                    .collect();
+                assert_eq!(
+                    flattened,
+                    vec![
+                        ("env", "a", import::Ty::FunctionType(0)),
+                        ("env", "b", import::Ty::FunctionType(0)),
+                        ("env", "c", import::Ty::FunctionType(0)),
+                        (
+                            "wasi_snapshot_preview1",
+                            "fd_write",
+                            import::Ty::FunctionType(1)
+                        ),
+                        (
+                            "wasi_snapshot_preview1",
+                            "proc_exit",
+                            import::Ty::FunctionType(2)
+                        ),
+                    ]
+                );
+                return;
+            }
+        }
+        panic!("ImportSection payload not found");
+    }
+
+    #[test]
+    fn test_section_render_wasm_compact_import_section() {
+        let original = create_compact_import_section();
+        for payload in Parser::new(0).parse_all(&original) {
+            if let Payload::ImportSection(section) = payload.unwrap() {
+                let import_section = ImportSection::from_wasmparser(section).unwrap();
+                let mut module = Module::new();
+                import_section.render_wasm(&mut module).unwrap();
+                assert_eq!(module.finish(), original);
+                return;
+            }
+        }
+        panic!("ImportSection payload not found");
+    }
+
+    #[test]
+    fn test_section_render_wasm_import_section_not_compact() {
+        let import_section = ImportSection {
+            imports: vec![
+                Import {
+                    module: Some("env".to_string()),
+                    name: Some("a".to_string()),
+                    ty: Some(import::Ty::FunctionType(0)),
+                },
+                Import {
+                    module: Some("env".to_string()),
+                    name: Some("b".to_string()),
+                    ty: Some(import::Ty::FunctionType(0)),
+                },
+            ],
+            compact: None,
+        };
+        let mut module = Module::new();
+        import_section.render_wasm(&mut module).unwrap();
+        let wasm_bytes = module.finish();
+        for payload in Parser::new(0).parse_all(&wasm_bytes) {
+            if let Payload::ImportSection(section) = payload.unwrap() {
+                for imports in section {
+                    assert!(matches!(imports.unwrap(), wasmparser::Imports::Single(..)));
+                }
+                return;
+            }
+        }
+        panic!("ImportSection payload not found");
+    }
+
     #[test]
     fn test_section_from_wasmparser_table_section() {
         let mut module = Module::new();
@@ -783,6 +1185,49 @@ mod tests {
         panic!("TableSection payload not found");
     }
 
+    #[test]
+    fn test_section_table_section_with_init_round_trip() {
+        let mut module = Module::new();
+        let mut tables = wasm_encoder::TableSection::new();
+        tables.table_with_init(
+            wasm_encoder::TableType {
+                element_type: wasm_encoder::RefType::EXTERNREF,
+                table64: false,
+                minimum: 4,
+                maximum: None,
+                shared: false,
+            },
+            &wasm_encoder::ConstExpr::ref_null(wasm_encoder::HeapType::EXTERN),
+        );
+        tables.table(wasm_encoder::TableType {
+            element_type: wasm_encoder::RefType::FUNCREF,
+            table64: false,
+            minimum: 1,
+            maximum: Some(1),
+            shared: false,
+        });
+        module.section(&tables);
+        let original = module.finish();
+
+        for payload in Parser::new(0).parse_all(&original) {
+            if let Payload::TableSection(section) = payload.unwrap() {
+                let table_section = TableSection::from_wasmparser(section).unwrap();
+                let init_expr = table_section.types[0].init_expr.as_ref().unwrap();
+                assert_eq!(
+                    init_expr.operators[0].opcode,
+                    Some(OpCode::ReferenceTypesExtRefNull as i32)
+                );
+                assert!(table_section.types[1].init_expr.is_none());
+
+                let mut module = Module::new();
+                table_section.render_wasm(&mut module).unwrap();
+                assert_eq!(module.finish(), original);
+                return;
+            }
+        }
+        panic!("TableSection payload not found");
+    }
+
     #[test]
     fn test_section_from_wasmparser_memory_section() {
         let mut module = Module::new();
@@ -847,6 +1292,42 @@ mod tests {
         panic!("ExportSection payload not found");
     }
 
+    #[test]
+    fn test_section_from_wasmparser_start_section() {
+        let mut module = Module::new();
+        module.section(&wasm_encoder::StartSection { function_index: 3 });
+
+        let wasm_bytes = module.finish();
+        let parser = Parser::new(0);
+        for payload in parser.parse_all(&wasm_bytes) {
+            let payload = payload.unwrap();
+            if let Payload::StartSection { .. } = &payload {
+                let start = StartSection::from_wasmparser(&payload).unwrap();
+                assert_eq!(start.function_index, Some(3));
+                return;
+            }
+        }
+        panic!("StartSection payload not found");
+    }
+
+    #[test]
+    fn test_section_from_wasmparser_data_count_section() {
+        let mut module = Module::new();
+        module.section(&wasm_encoder::DataCountSection { count: 2 });
+
+        let wasm_bytes = module.finish();
+        let parser = Parser::new(0);
+        for payload in parser.parse_all(&wasm_bytes) {
+            let payload = payload.unwrap();
+            if let Payload::DataCountSection { .. } = &payload {
+                let data_count = DataCountSection::from_wasmparser(&payload).unwrap();
+                assert_eq!(data_count.count, Some(2));
+                return;
+            }
+        }
+        panic!("DataCountSection payload not found");
+    }
+
     #[test]
     fn test_section_from_wasmparser_code_section_entry() {
         let mut module = Module::new();
@@ -0,0 +1,621 @@
+use std::collections::BTreeMap;
+
+use crate::error::{Error, Result};
+use crate::libernet_wasm::*;
+use crate::operators::{simple, with_operand};
+use crate::remap::{IndexSpace, append_global, imported_count, operators_mut};
+
+/// Computes the frame size of every defined function: its locals, parameters included, the
+/// maximum height of its operand stack, and one for the activation itself, so that even a
+/// function without locals or operands is guarded.
+fn frame_sizes(program: &ProgramModule) -> Result<Vec<u32>> {
+    let bytes = crate::render_wasm(program.clone())?;
+    let mut validator = wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all());
+    let mut sizes = Vec::new();
+    for payload in wasmparser::Parser::new(0).parse_all(&bytes) {
+        if let wasmparser::ValidPayload::Func(function, body) = validator.payload(&payload?)? {
+            let mut function = function.into_validator(Default::default());
+            function.read_locals(&mut body.get_binary_reader())?;
+            let mut reader = body.get_operators_reader()?;
+            let mut max_height = 0;
+            while !reader.eof() {
+                let offset = reader.original_position();
+                function.op(offset, &reader.read()?)?;
+                max_height = max_height.max(function.operand_stack_height());
+            }
+            sizes.push(function.len_locals() + max_height + 1);
+        }
+    }
+    Ok(sizes)
+}
+
+fn i32_type() -> ValueType {
+    ValueType {
+        value_type: Some(PlainType::ValueTypeI32 as i32),
+        ..Default::default()
+    }
+}
+
+fn inject_counter(program: &mut ProgramModule) -> u32 {
+    let counter_type = GlobalType {
+        content_type: Some(i32_type()),
+        mutable: Some(true),
+        shared: Some(false),
+    };
+    append_global(
+        program,
+        counter_type,
+        with_operand(OpCode::I32Constant, operator::Operator::I32Value(0)),
+    )
+}
+
+/// Wraps calls to defined functions with the accounting of their frames.
+struct Limiter {
+    counter: u32,
+    limit: u32,
+    imported_functions: u32,
+    frame_sizes: Vec<u32>,
+}
+
+impl Limiter {
+    fn frame_size(&self, function_index: u32) -> u32 {
+        function_index
+            .checked_sub(self.imported_functions)
+            .and_then(|index| self.frame_sizes.get(index as usize))
+            .copied()
+            .unwrap_or(0)
+    }
+
+    /// Adds `delta` to the counter left on the stack and traps if that exceeds the limit.
+    fn grow(&self, delta: i32) -> [Operator; 9] {
+        let counter = operator::Operator::GlobalIndex(self.counter);
+        [
+            with_operand(OpCode::I32Constant, operator::Operator::I32Value(delta)),
+            simple(OpCode::I32Add),
+            with_operand(OpCode::GlobalSet, counter.clone()),
+            with_operand(OpCode::GlobalGet, counter),
+            with_operand(
+                OpCode::I32Constant,
+                operator::Operator::I32Value(self.limit as i32),
+            ),
+            simple(OpCode::I32GtUnsigned),
+            with_operand(
+                OpCode::If,
+                operator::Operator::BlockType(BlockType {
+                    block_type: Some(block_type::BlockType::Empty(0)),
+                }),
+            ),
+            simple(OpCode::Unreachable),
+            simple(OpCode::End),
+        ]
+    }
+
+    /// The call of `function_index`, growing the counter by its frame beforehand and trapping
+    /// if that exceeds the limit, then restoring it from the `saved` local after it returns,
+    /// since tail calls may have replaced the frame of the callee with another.
+    fn call(&self, function_index: u32, saved: u32) -> Vec<Operator> {
+        let call = with_operand(
+            OpCode::Call,
+            operator::Operator::FunctionIndex(function_index),
+        );
+        let frame_size = self.frame_size(function_index);
+        if frame_size == 0 {
+            return vec![call];
+        }
+        let counter = operator::Operator::GlobalIndex(self.counter);
+        let mut operators = Vec::with_capacity(14);
+        operators.extend([
+            with_operand(OpCode::GlobalGet, counter.clone()),
+            with_operand(OpCode::LocalTee, operator::Operator::LocalIndex(saved)),
+        ]);
+        operators.extend(self.grow(frame_size as i32));
+        operators.extend([
+            call,
+            with_operand(OpCode::LocalGet, operator::Operator::LocalIndex(saved)),
+            with_operand(OpCode::GlobalSet, counter),
+        ]);
+        operators
+    }
+
+    /// The tail call of `function_index` from `caller`, swapping the frame of the caller for
+    /// the one of the callee in the counter. The caller of `caller` restores the counter.
+    fn return_call(&self, caller: u32, function_index: u32) -> Vec<Operator> {
+        let return_call = with_operand(
+            OpCode::TailCallExtReturnCall,
+            operator::Operator::FunctionIndex(function_index),
+        );
+        let delta = self.frame_size(function_index) as i32 - self.frame_size(caller) as i32;
+        if delta == 0 {
+            return vec![return_call];
+        }
+        let mut operators = Vec::with_capacity(11);
+        operators.push(with_operand(
+            OpCode::GlobalGet,
+            operator::Operator::GlobalIndex(self.counter),
+        ));
+        operators.extend(self.grow(delta));
+        operators.push(return_call);
+        operators
+    }
+
+    /// Instruments the body of the defined function `function_index`, adding the local saving
+    /// the counter around its calls if any needs it.
+    fn instrument(
+        &self,
+        function_index: u32,
+        parameters: u32,
+        entry: &mut CodeSectionEntry,
+    ) -> Result<()> {
+        let saved = parameters
+            + entry
+                .locals
+                .iter()
+                .map(|locals| locals.count.unwrap_or(0))
+                .sum::<u32>();
+        let mut instrumented = Vec::with_capacity(entry.body.len());
+        let mut uses_saved = false;
+        for operator in std::mem::take(&mut entry.body) {
+            let opcode = OpCode::try_from(operator.opcode.ok_or(Error::Missing("Opcode"))?)?;
+            match (opcode, &operator.operator) {
+                (OpCode::Call, Some(operator::Operator::FunctionIndex(callee))) => {
+                    uses_saved |= self.frame_size(*callee) > 0;
+                    instrumented.extend(self.call(*callee, saved));
+                }
+                (
+                    OpCode::TailCallExtReturnCall,
+                    Some(operator::Operator::FunctionIndex(callee)),
+                ) => {
+                    instrumented.extend(self.return_call(function_index, *callee));
+                }
+                (OpCode::TailCallExtReturnCallIndirect | OpCode::TailCallExtReturnCallRef, _) => {
+                    return Err(Error::Unsupported(format!(
+                        "Indirect tail call in function {}: the frame of its callee is unknown",
+                        function_index
+                    )));
+                }
+                _ => instrumented.push(operator),
+            }
+        }
+        entry.body = instrumented;
+        if uses_saved {
+            entry.locals.push(Locals {
+                count: Some(1),
+                value_type: Some(i32_type()),
+            });
+        }
+        Ok(())
+    }
+}
+
+/// Visits every function that can be entered without a `call` operator: exports, the start
+/// function, element segments and `ref.func` targets, which reach `call_indirect` and `call_ref`.
+fn visit_entry_points(program: &mut ProgramModule, mut visit: impl FnMut(&mut u32)) {
+    let exports = program
+        .export_section
+        .iter_mut()
+        .flat_map(|section| section.exports.iter_mut())
+        .filter(|export| {
+            matches!(
+                export.kind.map(ExternalKind::try_from),
+                Some(Ok(ExternalKind::ExtFunc | ExternalKind::ExtFuncExact))
+            )
+        });
+    exports
+        .flat_map(|export| export.index.as_mut())
+        .for_each(&mut visit);
+    if let Some(index) = program
+        .start_section
+        .as_mut()
+        .and_then(|section| section.function_index.as_mut())
+    {
+        visit(index);
+    }
+    let elements = program
+        .element_section
+        .iter_mut()
+        .flat_map(|section| section.elements.iter_mut());
+    for element in elements {
+        if let Some(element::Items::Functions(functions)) = &mut element.items {
+            functions.functions.iter_mut().for_each(&mut visit);
+        }
+    }
+    for operator in operators_mut(program) {
+        let opcode = operator.opcode.map(OpCode::try_from);
+        if matches!(opcode, Some(Ok(OpCode::ReferenceTypesExtRefFunc)))
+            && let Some(operator::Operator::FunctionIndex(index)) = &mut operator.operator
+        {
+            visit(index);
+        }
+    }
+}
+
+fn parameter_count(program: &ProgramModule, defined_index: usize) -> Result<u32> {
+    let type_index = program
+        .function_section
+        .as_ref()
+        .and_then(|section| section.type_idxs.get(defined_index))
+        .ok_or(Error::Missing("Function type"))?;
+    let sub_type = program
+        .type_section
+        .as_ref()
+        .and_then(|section| section.types.get(*type_index as usize))
+        .ok_or(Error::Missing("Type"))?;
+    match &sub_type.kind {
+        Some(sub_type::Kind::Func(func_type)) => Ok(func_type.params.len() as u32),
+        _ => Err(Error::Invalid(format!(
+            "Type {} of function {} is not a function type",
+            type_index, defined_index
+        ))),
+    }
+}
+
+/// Redirects every entry point into a thunk accounting for the frame of the function it calls,
+/// since the callers of entry points can't be instrumented.
+fn add_thunks(program: &mut ProgramModule, limiter: &Limiter) -> Result<()> {
+    let function_count = limiter.imported_functions + limiter.frame_sizes.len() as u32;
+    let mut thunks: BTreeMap<u32, u32> = BTreeMap::new();
+    visit_entry_points(program, |index| {
+        if limiter.frame_size(*index) > 0 {
+            let thunk_count = thunks.len() as u32;
+            *index = *thunks.entry(*index).or_insert(function_count + thunk_count);
+        }
+    });
+    let mut thunks: Vec<(u32, u32)> = thunks.into_iter().collect();
+    thunks.sort_by_key(|&(_, thunk)| thunk);
+    for (function_index, _) in thunks {
+        let defined_index = (function_index - limiter.imported_functions) as usize;
+        let parameters = parameter_count(program, defined_index)?;
+        let mut body: Vec<Operator> = (0..parameters)
+            .map(|local_index| {
+                with_operand(
+                    OpCode::LocalGet,
+                    operator::Operator::LocalIndex(local_index),
+                )
+            })
+            .collect();
+        body.extend(limiter.call(function_index, parameters));
+        body.push(simple(OpCode::End));
+
+        let function_section = program
+            .function_section
+            .as_mut()
+            .ok_or(Error::Missing("Function section"))?;
+        let type_index = function_section.type_idxs[defined_index];
+        function_section.type_idxs.push(type_index);
+        program
+            .code_section
+            .as_mut()
+            .ok_or(Error::Missing("Code section"))?
+            .code_section_entry
+            .push(CodeSectionEntry {
+                locals: vec![Locals {
+                    count: Some(1),
+                    value_type: Some(i32_type()),
+                }],
+                body,
+            });
+    }
+    Ok(())
+}
+
+/// Instruments the module to trap once the frames on its call stack add up to more than
+/// `limit`, counted in values, before deep recursion can exhaust the native stack of the host.
+pub fn limit_stack_height(mut program: ProgramModule, limit: u32) -> Result<ProgramModule> {
+    let frame_sizes = frame_sizes(&program)?;
+    let limiter = Limiter {
+        counter: inject_counter(&mut program),
+        limit,
+        imported_functions: imported_count(&program, IndexSpace::Function),
+        frame_sizes,
+    };
+    for defined_index in 0..limiter.frame_sizes.len() {
+        let parameters = parameter_count(&program, defined_index)?;
+        let entry = program
+            .code_section
+            .as_mut()
+            .and_then(|section| section.code_section_entry.get_mut(defined_index))
+            .ok_or(Error::Missing("Code section entry"))?;
+        limiter.instrument(
+            limiter.imported_functions + defined_index as u32,
+            parameters,
+            entry,
+        )?;
+    }
+    add_thunks(&mut program, &limiter)?;
+    Ok(program)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{
+        base_types, body, code_section, funcref_table, log_import, validate,
+    };
+    use wasm_encoder::{
+        BlockType as EncoderBlockType, ConstExpr, ElementSection, Elements, ExportKind,
+        FunctionSection, Instruction, Module, StartSection, ValType,
+    };
+
+    /// Creates a module with an imported logger, a recursive factorial, exported and placed in
+    /// a table, and an exported start function calling both
+    fn create_module() -> Vec<u8> {
+        let mut module = Module::new();
+        module.section(&base_types());
+        module.section(&log_import(0));
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        functions.function(0);
+        module.section(&functions);
+        module.section(&funcref_table());
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("factorial", ExportKind::Func, 1);
+        exports.export("main", ExportKind::Func, 2);
+        module.section(&exports);
+        module.section(&StartSection { function_index: 2 });
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            Elements::Functions([1].as_slice().into()),
+        );
+        module.section(&elements);
+        module.section(&code_section(vec![
+            vec![
+                Instruction::LocalGet(0),
+                Instruction::I32Eqz,
+                Instruction::If(EncoderBlockType::Result(ValType::I32)),
+                Instruction::I32Const(1),
+                Instruction::Else,
+                Instruction::LocalGet(0),
+                Instruction::LocalGet(0),
+                Instruction::I32Const(1),
+                Instruction::I32Sub,
+                Instruction::Call(1),
+                Instruction::I32Mul,
+                Instruction::End,
+            ],
+            vec![
+                Instruction::I32Const(3),
+                Instruction::Call(1),
+                Instruction::Drop,
+                Instruction::Call(0),
+            ],
+        ]));
+        module.finish()
+    }
+
+    fn i32_constant(value: i32) -> Operator {
+        with_operand(OpCode::I32Constant, operator::Operator::I32Value(value))
+    }
+
+    fn global(opcode: OpCode) -> Operator {
+        with_operand(opcode, operator::Operator::GlobalIndex(0))
+    }
+
+    /// The guarded call of the factorial, whose frame holds 5 values, with a limit of 100,
+    /// saving the counter in the local `saved`
+    fn guarded_factorial_call(saved: u32) -> Vec<Operator> {
+        vec![
+            global(OpCode::GlobalGet),
+            with_operand(OpCode::LocalTee, operator::Operator::LocalIndex(saved)),
+            i32_constant(5),
+            simple(OpCode::I32Add),
+            global(OpCode::GlobalSet),
+            global(OpCode::GlobalGet),
+            i32_constant(100),
+            simple(OpCode::I32GtUnsigned),
+            with_operand(
+                OpCode::If,
+                operator::Operator::BlockType(BlockType {
+                    block_type: Some(block_type::BlockType::Empty(0)),
+                }),
+            ),
+            simple(OpCode::Unreachable),
+            simple(OpCode::End),
+            with_operand(OpCode::Call, operator::Operator::FunctionIndex(1)),
+            with_operand(OpCode::LocalGet, operator::Operator::LocalIndex(saved)),
+            global(OpCode::GlobalSet),
+        ]
+    }
+
+    #[test]
+    fn test_frame_sizes() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        assert_eq!(frame_sizes(&program).unwrap(), vec![5, 2]);
+    }
+
+    #[test]
+    fn test_limit_stack_height_instruments_calls() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let limited = limit_stack_height(program, 100).unwrap();
+        validate(&limited);
+
+        let global = &limited.global_section.as_ref().unwrap().globals[0];
+        assert_eq!(global.r#type.as_ref().unwrap().mutable, Some(true));
+
+        let mut expected = vec![i32_constant(3)];
+        expected.extend(guarded_factorial_call(0));
+        expected.extend([
+            simple(OpCode::Drop),
+            // imported functions run on the host stack and are left alone
+            with_operand(OpCode::Call, operator::Operator::FunctionIndex(0)),
+            simple(OpCode::End),
+        ]);
+        assert_eq!(body(&limited, 1), expected.as_slice());
+        assert_eq!(
+            limited.code_section.as_ref().unwrap().code_section_entry[1].locals,
+            vec![Locals {
+                count: Some(1),
+                value_type: Some(i32_type()),
+            }]
+        );
+    }
+
+    #[test]
+    fn test_limit_stack_height_thunks() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let limited = limit_stack_height(program, 100).unwrap();
+
+        let exports = &limited.export_section.as_ref().unwrap().exports;
+        assert_eq!(exports[0].index, Some(3));
+        assert_eq!(exports[1].index, Some(4));
+        assert_eq!(
+            limited.start_section.as_ref().unwrap().function_index,
+            Some(4)
+        );
+        assert_eq!(
+            limited.element_section.as_ref().unwrap().elements[0].items,
+            Some(element::Items::Functions(ElementFunctions {
+                functions: vec![3]
+            }))
+        );
+        assert_eq!(
+            limited.function_section.as_ref().unwrap().type_idxs,
+            vec![1, 0, 1, 0]
+        );
+
+        let mut expected = vec![with_operand(
+            OpCode::LocalGet,
+            operator::Operator::LocalIndex(0),
+        )];
+        expected.extend(guarded_factorial_call(1));
+        expected.push(simple(OpCode::End));
+        assert_eq!(body(&limited, 2), expected.as_slice());
+    }
+
+    /// Creates a module with a countdown and a step of different frame sizes, tail calling
+    /// each other until the counter reaches zero
+    fn create_tail_call_module() -> Vec<u8> {
+        let mut module = Module::new();
+        module.section(&base_types());
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        functions.function(1);
+        module.section(&functions);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("countdown", ExportKind::Func, 0);
+        module.section(&exports);
+        module.section(&code_section(vec![
+            vec![
+                Instruction::LocalGet(0),
+                Instruction::I32Eqz,
+                Instruction::If(EncoderBlockType::Result(ValType::I32)),
+                Instruction::I32Const(0),
+                Instruction::Else,
+                Instruction::LocalGet(0),
+                Instruction::I32Const(1),
+                Instruction::I32Sub,
+                Instruction::ReturnCall(1),
+                Instruction::End,
+            ],
+            vec![Instruction::LocalGet(0), Instruction::ReturnCall(0)],
+        ]));
+        module.finish()
+    }
+
+    #[test]
+    fn test_limit_stack_height_return_call() {
+        let program = crate::from_wasm(&create_tail_call_module()).unwrap();
+        let sizes = frame_sizes(&program).unwrap();
+        assert_ne!(sizes[0], sizes[1]);
+        let limited = limit_stack_height(program, 100).unwrap();
+        validate(&limited);
+
+        // the tail call swaps the frames in the counter and keeps its original target
+        let limiter = Limiter {
+            counter: 0,
+            limit: 100,
+            imported_functions: 0,
+            frame_sizes: sizes.clone(),
+        };
+        let mut expected = vec![
+            with_operand(OpCode::LocalGet, operator::Operator::LocalIndex(0)),
+            global(OpCode::GlobalGet),
+        ];
+        expected.extend(limiter.grow(sizes[0] as i32 - sizes[1] as i32));
+        expected.extend([
+            with_operand(
+                OpCode::TailCallExtReturnCall,
+                operator::Operator::FunctionIndex(0),
+            ),
+            simple(OpCode::End),
+        ]);
+        assert_eq!(body(&limited, 1), expected.as_slice());
+        assert!(
+            limited.code_section.as_ref().unwrap().code_section_entry[1]
+                .locals
+                .is_empty()
+        );
+    }
+
+    #[test]
+    fn test_limit_stack_height_return_call_runs_in_constant_space() {
+        let program = crate::from_wasm(&create_tail_call_module()).unwrap();
+        let limited = limit_stack_height(program, 100).unwrap();
+        let bytes = crate::render_wasm(limited).unwrap();
+
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, &bytes).unwrap();
+        let mut store = wasmi::Store::new(&engine, ());
+        let instance = wasmi::Linker::new(&engine)
+            .instantiate_and_start(&mut store, &module)
+            .unwrap();
+        let countdown = instance
+            .get_typed_func::<i32, i32>(&store, "countdown")
+            .unwrap();
+        assert_eq!(countdown.call(&mut store, 10_000).unwrap(), 0);
+    }
+
+    #[test]
+    fn test_limit_stack_height_rejects_return_call_indirect() {
+        let mut module = Module::new();
+        module.section(&base_types());
+        let mut functions = FunctionSection::new();
+        functions.function(1);
+        module.section(&functions);
+        module.section(&funcref_table());
+        module.section(&code_section(vec![vec![
+            Instruction::LocalGet(0),
+            Instruction::I32Const(0),
+            Instruction::ReturnCallIndirect {
+                type_index: 1,
+                table_index: 0,
+            },
+        ]]));
+        let program = crate::from_wasm(&module.finish()).unwrap();
+        assert!(matches!(
+            limit_stack_height(program, 100),
+            Err(Error::Unsupported(_))
+        ));
+    }
+
+    #[test]
+    fn test_limit_stack_height_nullary_recursion_traps() {
+        // (func $f call $f) has no locals nor operands, but still takes a frame
+        let mut module = Module::new();
+        module.section(&base_types());
+        let mut functions = FunctionSection::new();
+        functions.function(0);
+        module.section(&functions);
+        let mut exports = wasm_encoder::ExportSection::new();
+        exports.export("run", ExportKind::Func, 0);
+        module.section(&exports);
+        module.section(&code_section(vec![vec![Instruction::Call(0)]]));
+        let program = crate::from_wasm(&module.finish()).unwrap();
+        let limited = limit_stack_height(program, 100).unwrap();
+        let bytes = crate::render_wasm(limited).unwrap();
+
+        let engine = wasmi::Engine::default();
+        let module = wasmi::Module::new(&engine, &bytes).unwrap();
+        let mut store = wasmi::Store::new(&engine, ());
+        let instance = wasmi::Linker::new(&engine)
+            .instantiate_and_start(&mut store, &module)
+            .unwrap();
+        let run = instance.get_typed_func::<(), ()>(&store, "run").unwrap();
+        let error = run.call(&mut store, ()).unwrap_err();
+        assert_eq!(
+            error.as_trap_code(),
+            Some(wasmi::TrapCode::UnreachableCodeReached)
+        );
+    }
+}
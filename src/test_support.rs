@@ -0,0 +1,68 @@
+use wasm_encoder::{
+    CodeSection, EntityType, Function, ImportSection, Instruction, RefType, TableSection,
+    TableType, TypeSection, ValType,
+};
+
+use crate::libernet_wasm::*;
+
+/// The `[] -> []` and `[i32] -> [i32]` function types the transform tests build on.
+pub(crate) fn base_types() -> TypeSection {
+    let mut types = TypeSection::new();
+    types.ty().function([], []);
+    types.ty().function([ValType::I32], [ValType::I32]);
+    types
+}
+
+/// The `env.log` function import, of type `type_index`.
+pub(crate) fn log_import(type_index: u32) -> ImportSection {
+    let mut imports = ImportSection::new();
+    imports.import("env", "log", EntityType::Function(type_index));
+    imports
+}
+
+/// A table of one `funcref`.
+pub(crate) fn funcref_table() -> TableSection {
+    let mut tables = TableSection::new();
+    tables.table(TableType {
+        element_type: RefType::FUNCREF,
+        table64: false,
+        minimum: 1,
+        maximum: None,
+        shared: false,
+    });
+    tables
+}
+
+/// A code section of functions without locals, each body closed by an `end`.
+pub(crate) fn code_section(bodies: Vec<Vec<Instruction>>) -> CodeSection {
+    let mut code = CodeSection::new();
+    for instructions in bodies {
+        let mut function = Function::new(vec![]);
+        for instruction in &instructions {
+            function.instruction(instruction);
+        }
+        function.instruction(&Instruction::End);
+        code.function(&function);
+    }
+    code
+}
+
+pub(crate) fn body(program: &ProgramModule, index: usize) -> &[Operator] {
+    &program.code_section.as_ref().unwrap().code_section_entry[index].body
+}
+
+pub(crate) fn operand(
+    program: &ProgramModule,
+    function: usize,
+    operator: usize,
+) -> operator::Operator {
+    body(program, function)[operator].operator.clone().unwrap()
+}
+
+/// Renders the module and validates it with every feature enabled.
+pub(crate) fn validate(program: &ProgramModule) {
+    let bytes = crate::render_wasm(program.clone()).unwrap();
+    wasmparser::Validator::new_with_features(wasmparser::WasmFeatures::all())
+        .validate_all(&bytes)
+        .unwrap();
+}
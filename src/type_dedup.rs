@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+
+use prost::Message;
+
+use crate::libernet_wasm::*;
+use crate::remap::{IndexSpace, Remapping, renumbering, retain_live};
+
+/// Whether each type is alone in an implicit recursion group. The identity of a type in an
+/// explicit group depends on the whole group, so only those types are candidates.
+fn singletons(type_section: &TypeSection) -> Vec<bool> {
+    if type_section.rec_groups.is_empty() {
+        return vec![true; type_section.types.len()];
+    }
+    type_section
+        .rec_groups
+        .iter()
+        .flat_map(|group| {
+            let count = group.type_count.unwrap_or(0) as usize;
+            let singleton = count == 1 && group.explicit != Some(true);
+            std::iter::repeat_n(singleton, count)
+        })
+        .collect()
+}
+
+/// The first structurally identical singleton type for every type, which is itself when it has
+/// no earlier duplicate.
+fn canonical_types(type_section: &TypeSection) -> Vec<u32> {
+    let singletons = singletons(type_section);
+    let mut first: HashMap<Vec<u8>, u32> = HashMap::new();
+    type_section
+        .types
+        .iter()
+        .enumerate()
+        .map(|(index, sub_type)| {
+            let index = index as u32;
+            if singletons.get(index as usize).copied().unwrap_or(false) {
+                *first.entry(sub_type.encode_to_vec()).or_insert(index)
+            } else {
+                index
+            }
+        })
+        .collect()
+}
+
+/// Removes the type and field names of the duplicates, which would otherwise name their
+/// canonical type twice.
+fn retain_canonical_names(program: &mut ProgramModule, canonical: &[u32]) {
+    let is_canonical = |index: Option<u32>| {
+        index.is_none_or(|index| {
+            canonical
+                .get(index as usize)
+                .is_none_or(|&canonical| canonical == index)
+        })
+    };
+    let name_sections = program
+        .custom_sections
+        .iter_mut()
+        .filter_map(|section| section.name_section.as_mut());
+    for name_section in name_sections {
+        if let Some(type_names) = &mut name_section.type_names {
+            type_names.names.retain(|naming| is_canonical(naming.index));
+        }
+        if let Some(field_names) = &mut name_section.field_names {
+            field_names
+                .names
+                .retain(|naming| is_canonical(naming.index));
+        }
+    }
+}
+
+/// Collapses structurally identical types into the first of them and renumbers every reference
+/// to the types left. Collapsing types can make the types referring to them identical in turn,
+/// so this repeats until no duplicate is left.
+pub fn deduplicate_types(mut program: ProgramModule) -> ProgramModule {
+    loop {
+        let Some(type_section) = &mut program.type_section else {
+            return program;
+        };
+        let canonical = canonical_types(type_section);
+        let keep: Vec<bool> = canonical
+            .iter()
+            .enumerate()
+            .map(|(index, &canonical)| canonical as usize == index)
+            .collect();
+        if keep.iter().all(|&kept| kept) {
+            return program;
+        }
+        // a removed singleton is also a recursion group of its own
+        let mut first_type = 0;
+        let keep_groups: Vec<bool> = type_section
+            .rec_groups
+            .iter()
+            .map(|group| {
+                let count = group.type_count.unwrap_or(0) as usize;
+                let kept = count != 1 || keep.get(first_type).copied().unwrap_or(true);
+                first_type += count;
+                kept
+            })
+            .collect();
+        retain_live(&mut type_section.rec_groups, &keep_groups);
+        retain_live(&mut type_section.types, &keep);
+        retain_canonical_names(&mut program, &canonical);
+
+        let type_renumbering = renumbering(&keep);
+        Remapping::new()
+            .with(IndexSpace::Type, |index| {
+                canonical
+                    .get(index as usize)
+                    .map_or(index, |&canonical| type_renumbering[canonical as usize])
+            })
+            .apply(&mut program);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::test_support::{code_section, funcref_table, log_import, operand, validate};
+    use wasm_encoder::{
+        BlockType as EncoderBlockType, ConstExpr, ElementSection, Elements,
+        FunctionSection as EncoderFunctionSection, Instruction, Module, NameMap as EncoderNameMap,
+        NameSection as EncoderNameSection, TagKind as EncoderTagKind,
+        TagSection as EncoderTagSection, TagType as EncoderTagType,
+        TypeSection as EncoderTypeSection, ValType,
+    };
+
+    /// Creates a module with two copies of each of its function types, referenced from an
+    /// import, functions, a tag, a `call_indirect` and a block type
+    fn create_module() -> Vec<u8> {
+        let mut module = Module::new();
+        let mut types = EncoderTypeSection::new();
+        types.ty().function([], []);
+        types.ty().function([ValType::I32], [ValType::I32]);
+        types.ty().function([], []);
+        types.ty().function([ValType::I32], [ValType::I32]);
+        types.ty().function([ValType::I64], []);
+        module.section(&types);
+        module.section(&log_import(2));
+        let mut functions = EncoderFunctionSection::new();
+        functions.function(3);
+        functions.function(2);
+        functions.function(4);
+        module.section(&functions);
+        module.section(&funcref_table());
+        let mut tags = EncoderTagSection::new();
+        tags.tag(EncoderTagType {
+            kind: EncoderTagKind::Exception,
+            func_type_idx: 2,
+        });
+        module.section(&tags);
+        let mut elements = ElementSection::new();
+        elements.active(
+            Some(0),
+            &ConstExpr::i32_const(0),
+            Elements::Functions([1].as_slice().into()),
+        );
+        module.section(&elements);
+
+        module.section(&code_section(vec![
+            vec![
+                Instruction::LocalGet(0),
+                Instruction::Block(EncoderBlockType::FunctionType(3)),
+                Instruction::End,
+            ],
+            vec![
+                Instruction::I32Const(1),
+                Instruction::I32Const(0),
+                Instruction::CallIndirect {
+                    type_index: 3,
+                    table_index: 0,
+                },
+                Instruction::Drop,
+                Instruction::Call(0),
+            ],
+            vec![],
+        ]));
+
+        let mut names = EncoderNameSection::new();
+        let mut type_names = EncoderNameMap::new();
+        type_names.append(0, "empty");
+        type_names.append(2, "also_empty");
+        names.types(&type_names);
+        module.section(&names);
+        module.finish()
+    }
+
+    fn func_type(params: Vec<ValueType>) -> SubType {
+        SubType {
+            kind: Some(sub_type::Kind::Func(FuncType {
+                params,
+                results: vec![],
+            })),
+            is_final: Some(true),
+            supertype_index: None,
+        }
+    }
+
+    fn reference_to(type_index: u32) -> ValueType {
+        ValueType {
+            reference: Some(ReferenceType {
+                nullable: Some(true),
+                heap_type: Some(HeapType {
+                    heap_type: Some(heap_type::HeapType::ConcreteType(type_index)),
+                    shared: None,
+                }),
+            }),
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_deduplicate_types() {
+        let program = deduplicate_types(crate::from_wasm(&create_module()).unwrap());
+        validate(&program);
+
+        assert_eq!(program.type_section.as_ref().unwrap().types.len(), 3);
+        assert_eq!(
+            program.import_section.as_ref().unwrap().imports[0].ty,
+            Some(import::Ty::FunctionType(0))
+        );
+        assert_eq!(
+            program.function_section.as_ref().unwrap().type_idxs,
+            vec![1, 0, 2]
+        );
+        assert_eq!(
+            program.tag_section.as_ref().unwrap().tags[0].function_type_idx,
+            Some(0)
+        );
+        assert_eq!(
+            operand(&program, 0, 1),
+            operator::Operator::BlockType(BlockType {
+                block_type: Some(block_type::BlockType::TypeIndex(1)),
+            })
+        );
+        assert_eq!(
+            operand(&program, 1, 2),
+            operator::Operator::CallIndirect(CallIndirectOp {
+                type_index: Some(1),
+                table_index: Some(0),
+            })
+        );
+    }
+
+    #[test]
+    fn test_deduplicate_types_names() {
+        let program = deduplicate_types(crate::from_wasm(&create_module()).unwrap());
+        let names = program
+            .custom_sections
+            .iter()
+            .find_map(|section| section.name_section.as_ref())
+            .unwrap();
+        assert_eq!(
+            names.type_names,
+            Some(NameMap {
+                names: vec![Naming {
+                    index: Some(0),
+                    name: Some("empty".to_string()),
+                }],
+            })
+        );
+    }
+
+    #[test]
+    fn test_deduplicate_types_transitively() {
+        // the last two types only differ by referring to duplicates of each other
+        let program = ProgramModule {
+            type_section: Some(TypeSection {
+                types: vec![
+                    func_type(vec![]),
+                    func_type(vec![]),
+                    func_type(vec![reference_to(0)]),
+                    func_type(vec![reference_to(1)]),
+                ],
+                rec_groups: vec![],
+            }),
+            function_section: Some(FunctionSection {
+                type_idxs: vec![3, 1],
+            }),
+            ..Default::default()
+        };
+        let program = deduplicate_types(program);
+        assert_eq!(
+            program.type_section.unwrap().types,
+            vec![func_type(vec![]), func_type(vec![reference_to(0)])]
+        );
+        assert_eq!(program.function_section.unwrap().type_idxs, vec![1, 0]);
+    }
+
+    #[test]
+    fn test_deduplicate_types_keeps_rec_groups() {
+        let program = ProgramModule {
+            type_section: Some(TypeSection {
+                types: vec![func_type(vec![]); 4],
+                rec_groups: vec![
+                    RecGroup {
+                        type_count: Some(2),
+                        explicit: Some(true),
+                    },
+                    RecGroup {
+                        type_count: Some(1),
+                        explicit: Some(false),
+                    },
+                    RecGroup {
+                        type_count: Some(1),
+                        explicit: Some(false),
+                    },
+                ],
+            }),
+            function_section: Some(FunctionSection {
+                type_idxs: vec![0, 1, 2, 3],
+            }),
+            ..Default::default()
+        };
+        let program = deduplicate_types(program);
+        let type_section = program.type_section.unwrap();
+        assert_eq!(type_section.types.len(), 3);
+        assert_eq!(
+            type_section.rec_groups,
+            vec![
+                RecGroup {
+                    type_count: Some(2),
+                    explicit: Some(true),
+                },
+                RecGroup {
+                    type_count: Some(1),
+                    explicit: Some(false),
+                },
+            ]
+        );
+        assert_eq!(
+            program.function_section.unwrap().type_idxs,
+            vec![0, 1, 2, 2]
+        );
+    }
+
+    #[test]
+    fn test_deduplicate_types_without_duplicates() {
+        let program = crate::from_wasm(&create_module()).unwrap();
+        let deduplicated = deduplicate_types(program);
+        assert_eq!(deduplicate_types(deduplicated.clone()), deduplicated);
+    }
+}
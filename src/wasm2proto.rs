@@ -2,22 +2,23 @@ use prost::Message;
 use std::env;
 use std::fs::read;
 
-pub mod libernet_wasm {
-    include!(concat!(env!("OUT_DIR"), "/libernet.wasm.rs"));
-}
-
-mod helpers;
-mod operators;
-mod program_module;
-mod sections;
-
-use program_module::{from_wasm, render_wasm};
+use wasm2proto::libernet_wasm::ProgramModule;
+use wasm2proto::{check_determinism, extract_modules, from_wasm, render_wasm};
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
+    let mut args: Vec<String> = env::args().collect();
+    let deterministic = take_flag(&mut args, "--deterministic");
+    if args.len() == 4 && args[1] == "--extract-modules" {
+        extract(&args[2], &args[3], deterministic);
+        return;
+    }
     if args.len() != 4 {
         eprintln!(
-            "Usage: {} <input_wasm_file> <output_proto_file> <output_wasm_file>",
+            "Usage: {} [--deterministic] <input_wasm_file> <output_proto_file> <output_wasm_file>",
+            args[0]
+        );
+        eprintln!(
+            "       {} [--deterministic] --extract-modules <input_component_file> <output_proto_file>",
             args[0]
         );
         std::process::exit(1);
@@ -28,6 +29,9 @@ fn main() {
 
     let in_bytes = read(input_wasm_file).expect("Failed to read wasm file");
     let program_module = from_wasm(&in_bytes).expect("Failed to parse wasm file");
+    if deterministic {
+        enforce_determinism(&program_module);
+    }
     let proto_bytes = program_module.encode_to_vec();
     let out_bytes = render_wasm(program_module).expect("Failed to render wasm file");
 
@@ -41,15 +45,68 @@ fn main() {
         proto_bytes.len()
     );
 
+    validate(&out_bytes);
+}
+
+/// Writes the core modules of a component, with their wiring manifest, as one proto.
+fn extract(input_component_file: &str, output_proto_file: &str, deterministic: bool) {
+    let in_bytes = read(input_component_file).expect("Failed to read component file");
+    let component_modules = extract_modules(&in_bytes).expect("Failed to parse component file");
+    if deterministic {
+        component_modules
+            .modules
+            .iter()
+            .for_each(enforce_determinism);
+    }
+    let proto_bytes = component_modules.encode_to_vec();
+    std::fs::write(output_proto_file, &proto_bytes).expect("Failed to write proto file");
+
+    println!(
+        "in: {}, modules: {}, proto: {}",
+        in_bytes.len(),
+        component_modules.modules.len(),
+        proto_bytes.len()
+    );
+
+    for program_module in component_modules.modules {
+        validate(&render_wasm(program_module).expect("Failed to render wasm module"));
+    }
+}
+
+/// Removes `flag` from the arguments, returning whether it was present.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let count = args.len();
+    args.retain(|arg| arg != flag);
+    args.len() != count
+}
+
+/// Refuses the conversion if the module violates the deterministic-execution profile.
+fn enforce_determinism(program_module: &ProgramModule) {
+    let violations = check_determinism(program_module).expect("Failed to check determinism");
+    if !violations.is_empty() {
+        for violation in &violations {
+            eprintln!("Determinism violation: {}", violation);
+        }
+        std::process::exit(1);
+    }
+}
+
+fn validate(bytes: &[u8]) {
     let features = wasmparser::WasmFeatures::default()
         | wasmparser::WasmFeatures::EXCEPTIONS
         | wasmparser::WasmFeatures::LEGACY_EXCEPTIONS
         | wasmparser::WasmFeatures::BULK_MEMORY
         | wasmparser::WasmFeatures::SIGN_EXTENSION
-        | wasmparser::WasmFeatures::SATURATING_FLOAT_TO_INT;
+        | wasmparser::WasmFeatures::SATURATING_FLOAT_TO_INT
+        | wasmparser::WasmFeatures::THREADS
+        | wasmparser::WasmFeatures::TAIL_CALL
+        | wasmparser::WasmFeatures::COMPACT_IMPORTS
+        | wasmparser::WasmFeatures::MEMORY64
+        | wasmparser::WasmFeatures::MULTI_MEMORY
+        | wasmparser::WasmFeatures::CUSTOM_PAGE_SIZES;
     let mut validator = wasmparser::Validator::new_with_features(features);
     let parser = wasmparser::Parser::new(0);
-    parser.parse_all(&out_bytes).for_each(|payload| {
+    parser.parse_all(bytes).for_each(|payload| {
         if let Ok(payload) = payload
             && let Err(e) = validator.payload(&payload)
         {